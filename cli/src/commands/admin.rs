@@ -2,8 +2,8 @@ use std::fs::remove_file;
 
 use env_defs::{CloudProvider, ExtraData};
 use env_utils::{
-    create_temp_dir, download_zip, get_extra_environment_variables_all, store_backend_file,
-    store_tf_vars_json, unzip_file,
+    create_temp_dir, download_zip, get_extra_environment_variables_all, get_secret_references,
+    resolve_secret_references, store_backend_file, store_tf_vars_json, unzip_file,
 };
 
 use crate::current_region_handler;
@@ -99,6 +99,20 @@ pub async fn handle_setup_workspace(deployment_id: &str, environment_id: &str) {
         map.extend(extra_map);
     }
 
+    let mut secrets = std::collections::BTreeMap::new();
+    for name in get_secret_references(&all_variables) {
+        match handler.get_secret_value(&name).await {
+            Ok(value) => {
+                secrets.insert(name, value);
+            }
+            Err(e) => {
+                println!("Failed to resolve secret {}: {}", name, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let all_variables = resolve_secret_references(&all_variables, &secrets).unwrap();
+
     store_tf_vars_json(&all_variables, new_dir.to_str().unwrap());
     store_backend_file(
         handler.get_backend_provider(),
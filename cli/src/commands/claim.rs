@@ -19,9 +19,17 @@ pub async fn handle_plan(
         std::process::exit(1);
     }
 
-    run_claim_file(environment, claim, "plan", store_files, destroy, follow)
-        .await
-        .unwrap();
+    run_claim_file(
+        environment,
+        claim,
+        "plan",
+        store_files,
+        destroy,
+        follow,
+        None,
+    )
+    .await
+    .unwrap();
 }
 
 pub async fn handle_driftcheck(deployment_id: &str, environment: &str, remediate: bool) {
@@ -44,8 +52,24 @@ pub async fn handle_driftcheck(deployment_id: &str, environment: &str, remediate
     };
 }
 
-pub async fn handle_apply(environment: &str, claim: &str, store_files: bool, follow: bool) {
-    match run_claim_file(environment, claim, "apply", store_files, false, follow).await {
+pub async fn handle_apply(
+    environment: &str,
+    claim: &str,
+    store_files: bool,
+    follow: bool,
+    from_plan: Option<&str>,
+) {
+    match run_claim_file(
+        environment,
+        claim,
+        "apply",
+        store_files,
+        false,
+        follow,
+        from_plan,
+    )
+    .await
+    {
         Ok(_) => {
             info!("Successfully applied claim");
         }
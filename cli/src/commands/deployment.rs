@@ -1,7 +1,8 @@
 use log::error;
 
-use crate::current_region_handler;
-use env_defs::{CloudProvider, CloudProviderCommon};
+use crate::{current_region_handler, follow_execution, ClaimJobStruct};
+use env_common::logic::{cancel_deployment_job, move_deployment, rollback_infra};
+use env_defs::{CloudProvider, CloudProviderCommon, DeploymentStatus};
 use std::fs::File;
 use std::io::Write;
 
@@ -19,6 +20,170 @@ pub async fn handle_describe(deployment_id: &str, environment: &str) {
     }
 }
 
+pub async fn handle_cancel(deployment_id: &str, environment: &str) {
+    println!(
+        "Cancelling job for deployment {} in {}, waiting for it to stop...",
+        deployment_id, environment
+    );
+    match cancel_deployment_job(&current_region_handler().await, deployment_id, environment).await {
+        Ok(job_id) => {
+            println!("Job {} was cancelled", job_id);
+        }
+        Err(e) => {
+            error!("Failed to cancel job: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+pub async fn handle_rollback(deployment_id: &str, environment: &str, to_job_id: &str, yes: bool) {
+    let handler = current_region_handler().await;
+    let deployment = match handler
+        .get_deployment(deployment_id, environment, false)
+        .await
+    {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            error!("Deployment not found: {}", deployment_id);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            error!("Failed to get deployment: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Plan the rollback first so the changes can be reviewed before anything is applied
+    let plan_job_id = match rollback_infra(
+        &handler,
+        deployment_id,
+        environment,
+        to_job_id,
+        "plan",
+        None,
+    )
+    .await
+    {
+        Ok(job_id) => job_id,
+        Err(e) => {
+            error!("Failed to plan rollback: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "Planning rollback of {} to job {} (job id: {})",
+        deployment_id, to_job_id, plan_job_id
+    );
+    let plan_job = ClaimJobStruct {
+        job_id: plan_job_id.clone(),
+        deployment_id: deployment_id.to_string(),
+        environment: environment.to_string(),
+        region: handler.get_region().to_string(),
+    };
+    if let Err(e) = follow_execution(&vec![plan_job], "plan").await {
+        error!("Failed to follow rollback plan: {}", e);
+        std::process::exit(1);
+    }
+
+    let plan = handler
+        .get_plan_deployment(deployment_id, environment, &plan_job_id)
+        .await;
+    match plan {
+        Ok(Some(plan)) if plan.status == DeploymentStatus::Successful => {
+            println!(
+                "\nModule version: {} -> {}",
+                deployment.module_version, plan.module_version
+            );
+            print_changed_variables(&deployment.variables, &plan.variables);
+        }
+        Ok(Some(plan)) => {
+            error!(
+                "Rollback plan did not succeed ({}), not applying it",
+                plan.status
+            );
+            std::process::exit(1);
+        }
+        Ok(None) => {
+            error!("Rollback plan {} not found, not applying it", plan_job_id);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            error!("Failed to get rollback plan: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if !yes {
+        let confirmed = inquire::Confirm::new("Apply the rollback?")
+            .with_default(false)
+            .prompt()
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Rollback was not applied");
+            return;
+        }
+    }
+
+    // The reviewed plan is applied, not a new plan that may differ from it
+    let apply_job_id = match rollback_infra(
+        &handler,
+        deployment_id,
+        environment,
+        to_job_id,
+        "apply",
+        Some(&plan_job_id),
+    )
+    .await
+    {
+        Ok(job_id) => job_id,
+        Err(e) => {
+            error!("Failed to apply rollback: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "Applying rollback of {} to job {} (job id: {})",
+        deployment_id, to_job_id, apply_job_id
+    );
+    let apply_job = ClaimJobStruct {
+        job_id: apply_job_id,
+        deployment_id: deployment_id.to_string(),
+        environment: environment.to_string(),
+        region: handler.get_region().to_string(),
+    };
+    if let Err(e) = follow_execution(&vec![apply_job], "apply").await {
+        error!("Failed to follow rollback apply: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn print_changed_variables(current: &serde_json::Value, target: &serde_json::Value) {
+    let empty = serde_json::Map::new();
+    let current = current.as_object().unwrap_or(&empty);
+    let target = target.as_object().unwrap_or(&empty);
+    let mut names: Vec<&String> = current.keys().chain(target.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let changed: Vec<&String> = names
+        .into_iter()
+        .filter(|name| current.get(*name) != target.get(*name))
+        .collect();
+    if changed.is_empty() {
+        println!("Variables: unchanged");
+        return;
+    }
+    println!("Variables:");
+    for name in changed {
+        println!(
+            "  {}: {} -> {}",
+            name,
+            current.get(name).unwrap_or(&serde_json::Value::Null),
+            target.get(name).unwrap_or(&serde_json::Value::Null)
+        );
+    }
+}
+
 pub async fn handle_list() {
     let deployments = current_region_handler()
         .await
@@ -120,3 +285,48 @@ pub async fn handle_get_logs(job_id: &str, output_path: Option<&str>) {
         }
     }
 }
+
+pub async fn handle_move(
+    deployment_id: &str,
+    environment: &str,
+    new_deployment_id: &str,
+    new_environment: &str,
+    yes: bool,
+) {
+    if !yes {
+        let confirmed = inquire::Confirm::new(&format!(
+            "Move {} in {} to {} in {}?",
+            deployment_id, environment, new_deployment_id, new_environment
+        ))
+        .with_default(false)
+        .prompt()
+        .unwrap_or(false);
+        if !confirmed {
+            println!("Deployment was not moved");
+            return;
+        }
+    }
+
+    let handler = current_region_handler().await;
+    match move_deployment(
+        &handler,
+        deployment_id,
+        environment,
+        new_deployment_id,
+        new_environment,
+    )
+    .await
+    {
+        Ok(_) => {
+            println!(
+                "Moved {} in {} to {} in {}",
+                deployment_id, environment, new_deployment_id, new_environment
+            );
+            println!("Update the claim to the new name and namespace before applying it again");
+        }
+        Err(e) => {
+            error!("Failed to move deployment: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
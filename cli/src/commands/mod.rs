@@ -7,5 +7,6 @@ pub mod module;
 pub mod policy;
 pub mod project;
 pub mod provider;
+pub mod rollout;
 pub mod stack;
 pub mod upgrade;
@@ -1,11 +1,28 @@
-use env_common::logic::publish_policy;
+use std::path::Path;
+
+use colored::Colorize;
+use env_common::logic::{promote_policy, publish_policy};
+use env_utils::{get_violation_diff, run_policy_tests, write_policy_test_case};
 use log::{error, info};
 
 use crate::current_region_handler;
 use env_defs::CloudProvider;
 
-pub async fn handle_publish(file: &str, environment: &str) {
-    match publish_policy(&current_region_handler().await, file, environment).await {
+pub async fn handle_publish(
+    file: &str,
+    environment: &str,
+    reference: Option<&str>,
+    description: Option<&str>,
+) {
+    match publish_policy(
+        &current_region_handler().await,
+        file,
+        environment,
+        reference,
+        description,
+    )
+    .await
+    {
         Ok(_) => {
             info!("Policy published successfully");
         }
@@ -16,6 +33,123 @@ pub async fn handle_publish(file: &str, environment: &str) {
     }
 }
 
+pub async fn handle_promote(policy: &str, version: &str, from: &str, to: &str) {
+    match promote_policy(&current_region_handler().await, policy, version, from, to).await {
+        Ok(_) => {
+            info!("Policy promoted successfully");
+        }
+        Err(e) => {
+            error!("Failed to promote policy: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+pub fn handle_test(path: &str) {
+    let results = match run_policy_tests(Path::new(path)) {
+        Ok(results) => results,
+        Err(e) => {
+            error!("Failed to run policy tests: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for result in &results {
+        if result.passed() {
+            println!("{} {}", "PASS".green().bold(), result.name);
+            continue;
+        }
+        println!("{} {}", "FAIL".red().bold(), result.name);
+        match &result.actual {
+            Ok(actual) => {
+                for line in get_violation_diff(&result.expected, actual) {
+                    match line.starts_with('-') {
+                        true => println!("    {}", line.red()),
+                        false => println!("    {}", line.green()),
+                    }
+                }
+            }
+            Err(e) => println!("    Error evaluating policy: {}", e),
+        }
+    }
+
+    let failed = results.iter().filter(|r| !r.passed()).count();
+    println!("\n{} passed, {} failed", results.len() - failed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+pub async fn handle_generate_fixture(
+    path: &str,
+    name: &str,
+    deployment_id: &str,
+    job_id: &str,
+    environment: &str,
+    change_type: &str,
+) {
+    let handler = current_region_handler().await;
+    let change_record = match handler
+        .get_change_record(
+            environment,
+            deployment_id,
+            job_id,
+            &change_type.to_uppercase(),
+        )
+        .await
+    {
+        Ok(change_record) => change_record,
+        Err(e) => {
+            error!("Failed to get change record: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let plan = match handler
+        .get_change_record_json(environment, deployment_id, job_id, change_type)
+        .await
+    {
+        Ok(plan) => plan,
+        Err(e) => {
+            error!("Failed to get plan of change record: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // The runner evaluates policies with the cost estimate and risk assessment of the plan
+    let mut data = serde_json::Map::new();
+    if let Some(cost_estimate) = &change_record.cost_estimate {
+        data.insert(
+            "cost_estimate".to_string(),
+            serde_json::json!(cost_estimate),
+        );
+    }
+    if let Some(risk_assessment) = &change_record.risk_assessment {
+        data.insert(
+            "risk_assessment".to_string(),
+            serde_json::json!(risk_assessment),
+        );
+    }
+
+    match write_policy_test_case(Path::new(path), name, &plan, &data.into()) {
+        Ok(violations) => {
+            println!("Generated test case {} from job {}", name, job_id);
+            if violations.is_empty() {
+                println!("The policy has no violations for this plan");
+            }
+            for (package, violations) in &violations {
+                for violation in violations {
+                    println!("  {}: {}", package, violation);
+                }
+            }
+            println!("Review expected.json of the test case before committing it");
+        }
+        Err(e) => {
+            error!("Failed to generate test case: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 pub async fn handle_list(environment: &str) {
     let policies = current_region_handler()
         .await
@@ -0,0 +1,404 @@
+use env_common::interface::GenericCloudHandler;
+use env_common::logic::{
+    get_rollout, save_rollout, upgrade_infra, Rollout, RolloutState, RolloutStepStatus,
+};
+use env_defs::{CloudProvider, DeploymentStatus};
+use env_utils::plan_get_destructive_changes;
+use log::error;
+
+use crate::{current_region_handler, follow_execution, ClaimJobStruct};
+
+pub struct RolloutOptions {
+    pub to_version: Option<String>,
+    pub environment_order: Vec<String>,
+    pub canary_size: usize,
+    pub batch_size: usize,
+    pub allow_destructive: bool,
+    pub resume: bool,
+    pub restart: bool,
+    pub status: bool,
+    pub yes: bool,
+}
+
+/// Stores the rollout through the handler, so every operator and CI runner of the project sees
+/// and can resume the same rollout
+async fn store_rollout(handler: &GenericCloudHandler, rollout: &Rollout) {
+    if let Err(e) = save_rollout(handler, rollout).await {
+        error!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+pub async fn handle_rollout(module: &str, options: RolloutOptions) {
+    let handler = current_region_handler().await;
+    let existing = match get_rollout(&handler, module).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            error!("Failed to get rollout of {}: {}", module, e);
+            std::process::exit(1);
+        }
+    };
+
+    if options.status {
+        match existing {
+            Some(rollout) => print_rollout_status(&rollout),
+            None => println!("No rollout found for module {}", module),
+        }
+        return;
+    }
+
+    let mut rollout = if options.resume {
+        match existing {
+            Some(rollout) => rollout,
+            None => {
+                error!("No rollout to resume for module {}", module);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        let to_version = options.to_version.clone().unwrap_or_default();
+        if let Some(existing) = &existing
+            && existing.state() != RolloutState::Completed
+            && !options.restart
+        {
+            error!(
+                "A rollout of {} to {} is not completed, resume it with --resume or start a new one with --restart",
+                module, existing.to_version
+            );
+            std::process::exit(1);
+        }
+        let deployments = match handler
+            .get_deployments_using_module(module, "", false)
+            .await
+        {
+            Ok(deployments) => deployments,
+            Err(e) => {
+                error!("Failed to get deployments using module {}: {}", module, e);
+                std::process::exit(1);
+            }
+        };
+        match Rollout::new(
+            module,
+            &to_version,
+            handler.get_project_id(),
+            handler.get_region(),
+            &deployments,
+            &options.environment_order,
+            options.canary_size,
+            options.batch_size,
+            options.allow_destructive,
+        ) {
+            Ok(rollout) => rollout,
+            Err(e) => {
+                error!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+    if options.allow_destructive {
+        rollout.allow_destructive = true;
+    }
+
+    if rollout.deployments.is_empty() {
+        println!(
+            "No deployments of {} on an older version of the track of {}",
+            module, rollout.to_version
+        );
+        return;
+    }
+    println!(
+        "Rolling out {} to version {}: {} deployments in {} batches",
+        module,
+        rollout.to_version,
+        rollout.deployments.len(),
+        rollout.batch_count()
+    );
+    store_rollout(&handler, &rollout).await;
+
+    plan_rollout(&handler, &mut rollout).await;
+    store_rollout(&handler, &rollout).await;
+    stop_if_blocked(&rollout);
+
+    while let Some(batch) = rollout.next_batch() {
+        let size = rollout
+            .deployments
+            .iter()
+            .filter(|d| d.batch == batch)
+            .count();
+        let description = format!(
+            "batch {}/{}{} ({} deployments)",
+            batch + 1,
+            rollout.batch_count(),
+            if rollout.is_canary_batch(batch) {
+                ", canary"
+            } else {
+                ""
+            },
+            size
+        );
+        if !options.yes {
+            let confirmed = inquire::Confirm::new(&format!("Apply {}?", description))
+                .with_default(false)
+                .prompt()
+                .unwrap_or(false);
+            if !confirmed {
+                println!("Rollout paused, continue it with --resume");
+                return;
+            }
+        }
+        println!("\nApplying {}", description);
+        apply_batch(&handler, &mut rollout, batch).await;
+        store_rollout(&handler, &rollout).await;
+        stop_if_blocked(&rollout);
+    }
+
+    println!(
+        "\nRollout of {} to version {} completed",
+        module, rollout.to_version
+    );
+    print_rollout_status(&rollout);
+}
+
+/// Plans every deployment of the rollout that needs a plan on the new version and records
+/// whether it succeeded and if it deletes or replaces resources
+async fn plan_rollout(handler: &GenericCloudHandler, rollout: &mut Rollout) {
+    let mut jobs = vec![];
+    for index in 0..rollout.deployments.len() {
+        if !rollout.needs_plan(&rollout.deployments[index]) {
+            continue;
+        }
+        let to_version = rollout.to_version.clone();
+        let deployment = &mut rollout.deployments[index];
+        match upgrade_infra(
+            handler,
+            &deployment.deployment_id,
+            &deployment.environment,
+            &to_version,
+            "plan",
+            None,
+        )
+        .await
+        {
+            Ok(job_id) => {
+                deployment.job_id = Some(job_id.clone());
+                deployment.plan_job_id = None;
+                jobs.push((index, job_id));
+            }
+            Err(e) => {
+                deployment.plan_job_id = None;
+                deployment.status = RolloutStepStatus::PlanFailed;
+                deployment.error = Some(e.to_string());
+            }
+        }
+    }
+    if jobs.is_empty() {
+        return;
+    }
+
+    println!("Planning {} deployments on the new version", jobs.len());
+    let claim_jobs: Vec<ClaimJobStruct> = jobs
+        .iter()
+        .map(|(index, job_id)| {
+            let deployment = &rollout.deployments[*index];
+            ClaimJobStruct {
+                job_id: job_id.clone(),
+                deployment_id: deployment.deployment_id.clone(),
+                environment: deployment.environment.clone(),
+                region: deployment.region.clone(),
+            }
+        })
+        .collect();
+    if let Err(e) = follow_execution(&claim_jobs, "plan").await {
+        error!("Failed to follow rollout plans: {}", e);
+        std::process::exit(1);
+    }
+
+    for (index, job_id) in jobs {
+        let deployment = &mut rollout.deployments[index];
+        let plan = handler
+            .get_plan_deployment(&deployment.deployment_id, &deployment.environment, &job_id)
+            .await;
+        deployment.destructive_changes = vec![];
+        match plan {
+            Ok(Some(plan)) if plan.status == DeploymentStatus::Successful => {
+                let plan_json = handler
+                    .get_change_record_json(
+                        &deployment.environment,
+                        &deployment.deployment_id,
+                        &job_id,
+                        "plan",
+                    )
+                    .await;
+                match plan_json {
+                    Ok(plan_json) => {
+                        deployment.destructive_changes = plan_get_destructive_changes(&plan_json)
+                            .into_iter()
+                            .map(|c| format!("{}: {}", c.action, c.address))
+                            .collect();
+                        deployment.status = if deployment.destructive_changes.is_empty() {
+                            RolloutStepStatus::Planned
+                        } else {
+                            RolloutStepStatus::Destructive
+                        };
+                        deployment.plan_job_id = Some(job_id.clone());
+                        deployment.error = None;
+                    }
+                    Err(e) => {
+                        deployment.status = RolloutStepStatus::PlanFailed;
+                        deployment.error = Some(format!("Failed to read plan: {}", e));
+                    }
+                }
+            }
+            Ok(Some(plan)) => {
+                deployment.status = RolloutStepStatus::PlanFailed;
+                deployment.error = Some(format!("{}: {}", plan.status, plan.error_text));
+            }
+            Ok(None) => {
+                deployment.status = RolloutStepStatus::PlanFailed;
+                deployment.error = Some(format!("Plan {} not found", job_id));
+            }
+            Err(e) => {
+                deployment.status = RolloutStepStatus::PlanFailed;
+                deployment.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+async fn apply_batch(handler: &GenericCloudHandler, rollout: &mut Rollout, batch: usize) {
+    let mut jobs = vec![];
+    for index in 0..rollout.deployments.len() {
+        let deployment = &rollout.deployments[index];
+        if deployment.batch != batch || !rollout.is_ready_to_apply(deployment) {
+            continue;
+        }
+        let to_version = rollout.to_version.clone();
+        let deployment = &mut rollout.deployments[index];
+        // Apply the plan that was checked for destructive changes, not a new one
+        let Some(plan_job_id) = deployment.plan_job_id.clone() else {
+            deployment.status = RolloutStepStatus::ApplyFailed;
+            deployment.error = Some("No checked plan to apply".to_string());
+            continue;
+        };
+        match upgrade_infra(
+            handler,
+            &deployment.deployment_id,
+            &deployment.environment,
+            &to_version,
+            "apply",
+            Some(&plan_job_id),
+        )
+        .await
+        {
+            Ok(job_id) => {
+                deployment.job_id = Some(job_id.clone());
+                jobs.push((index, job_id));
+            }
+            Err(e) => {
+                deployment.status = RolloutStepStatus::ApplyFailed;
+                deployment.error = Some(e.to_string());
+            }
+        }
+    }
+
+    let claim_jobs: Vec<ClaimJobStruct> = jobs
+        .iter()
+        .map(|(index, job_id)| {
+            let deployment = &rollout.deployments[*index];
+            ClaimJobStruct {
+                job_id: job_id.clone(),
+                deployment_id: deployment.deployment_id.clone(),
+                environment: deployment.environment.clone(),
+                region: deployment.region.clone(),
+            }
+        })
+        .collect();
+    if !claim_jobs.is_empty()
+        && let Err(e) = follow_execution(&claim_jobs, "apply").await
+    {
+        error!("Failed to follow rollout applies: {}", e);
+        std::process::exit(1);
+    }
+
+    for (index, job_id) in jobs {
+        let to_version = rollout.to_version.clone();
+        let deployment = &mut rollout.deployments[index];
+        let result = handler
+            .get_deployment(&deployment.deployment_id, &deployment.environment, false)
+            .await;
+        match result {
+            Ok(Some(current))
+                if current.job_id == job_id
+                    && current.status == DeploymentStatus::Successful
+                    && current.module_version == to_version =>
+            {
+                deployment.status = RolloutStepStatus::Applied;
+                deployment.error = None;
+            }
+            Ok(Some(current)) => {
+                deployment.status = RolloutStepStatus::ApplyFailed;
+                deployment.error = Some(format!("{}: {}", current.status, current.error_text));
+            }
+            Ok(None) => {
+                deployment.status = RolloutStepStatus::ApplyFailed;
+                deployment.error = Some("Deployment not found after apply".to_string());
+            }
+            Err(e) => {
+                deployment.status = RolloutStepStatus::ApplyFailed;
+                deployment.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+fn stop_if_blocked(rollout: &Rollout) {
+    if let RolloutState::Stopped(blocking) = rollout.state() {
+        print_rollout_status(rollout);
+        error!(
+            "Rollout stopped since these deployments failed or have destructive changes: {}",
+            blocking.join(", ")
+        );
+        eprintln!(
+            "Fix the deployments (or pass --allow-destructive) and continue the rollout with --resume"
+        );
+        std::process::exit(1);
+    }
+}
+
+fn print_rollout_status(rollout: &Rollout) {
+    println!(
+        "\nRollout of {} to {} (started {}): {}",
+        rollout.module,
+        rollout.to_version,
+        rollout.started_at,
+        match rollout.state() {
+            RolloutState::InProgress => "in progress".to_string(),
+            RolloutState::Stopped(_) => "stopped".to_string(),
+            RolloutState::Completed => "completed".to_string(),
+        }
+    );
+    println!(
+        "{:<6} {:<40} {:<30} {:<12} {:<14} Details",
+        "Batch", "Deployment", "Environment", "From", "Status"
+    );
+    for deployment in &rollout.deployments {
+        let status = serde_json::to_value(deployment.status)
+            .ok()
+            .and_then(|s| s.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let details = match (&deployment.error, deployment.destructive_changes.is_empty()) {
+            (Some(error), _) => error.clone(),
+            (None, false) => deployment.destructive_changes.join(", "),
+            (None, true) => deployment.job_id.clone().unwrap_or_default(),
+        };
+        println!(
+            "{:<6} {:<40} {:<30} {:<12} {:<14} {}",
+            deployment.batch + 1,
+            deployment.deployment_id,
+            deployment.environment,
+            deployment.from_version,
+            status,
+            details
+        );
+    }
+}
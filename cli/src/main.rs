@@ -105,6 +105,10 @@ enum Commands {
         /// Follow the apply operation progress
         #[arg(long)]
         follow: bool,
+        /// Job id of a plan of the claim, its saved plan file is applied instead of planning again.
+        /// Refused if the state or module version changed since the plan
+        #[arg(long)]
+        from_plan: Option<String>,
     },
     /// Delete resources in cloud
     Destroy {
@@ -140,6 +144,7 @@ enum Commands {
         output: Option<String>,
     },
     /// Work with deployments
+    #[command(alias = "deployment")]
     Deployments {
         #[command(subcommand)]
         command: DeploymentCommands,
@@ -273,6 +278,14 @@ v0.1.2     Active       2025-10-10 09:15:00
         #[arg(short, long)]
         message: Option<String>,
     },
+    /// Upgrade every deployment of a module to a new version in batches, canary batch first
+    #[command(after_help = r#"Example:
+```
+$ infraweave module rollout s3bucket --to 0.2.0 --batch-size 10
+$ infraweave module rollout s3bucket --status
+$ infraweave module rollout s3bucket --resume --allow-destructive
+```"#)]
+    Rollout(ModuleRolloutArgs),
 }
 
 #[derive(Args)]
@@ -295,6 +308,40 @@ struct ModulePublishArgs {
     no_fail_on_exist: bool,
 }
 
+#[derive(Args)]
+struct ModuleRolloutArgs {
+    /// Module name to roll out, e.g. s3bucket
+    module: String,
+    /// Version to upgrade the deployments to, e.g. 0.2.0
+    #[arg(long, required_unless_present_any = ["resume", "status"])]
+    to: Option<String>,
+    /// Environments in the order they are rolled out, e.g. "dev*,staging*,prod*", remaining
+    /// environments follow in alphabetical order
+    #[arg(long, value_delimiter = ',')]
+    environment_order: Vec<String>,
+    /// Number of deployments in the first (canary) batch, 0 to skip the canary batch
+    #[arg(long, default_value_t = env_common::logic::DEFAULT_ROLLOUT_CANARY_SIZE)]
+    canary: usize,
+    /// Number of deployments applied together in the following batches
+    #[arg(long, default_value_t = env_common::logic::DEFAULT_ROLLOUT_BATCH_SIZE)]
+    batch_size: usize,
+    /// Do not stop the rollout on plans that delete or replace resources
+    #[arg(long)]
+    allow_destructive: bool,
+    /// Continue a stopped or paused rollout of the module
+    #[arg(long, conflicts_with_all = ["to", "status"])]
+    resume: bool,
+    /// Replace a rollout of the module that is not completed with a new one
+    #[arg(long, conflicts_with_all = ["resume", "status"])]
+    restart: bool,
+    /// Show the progress of the latest rollout of the module
+    #[arg(long, conflicts_with = "to")]
+    status: bool,
+    /// Apply the batches without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
 #[derive(Args)]
 struct ModulePrecheckArgs {
     /// Environment id to publish to, e.g. cli/default (optional, will prompt if not provided)
@@ -398,28 +445,65 @@ struct StackPublishArgs {
 
 #[derive(Subcommand)]
 enum PolicyCommands {
-    /// Upload and publish a policy to a specific environment (not yet functional)
+    /// Upload and publish a policy to a specific environment
     Publish {
-        /// Environment id to publish to, e.g. cli/default (optional, will prompt if not provided)
-        environment_id: Option<String>,
-        /// Path to the policy to publish, e.g. ./src
+        /// Environment id to publish to, e.g. cli/default
+        environment_id: String,
+        /// Path to the policy to publish, a directory with policy.yaml and .rego files, e.g. ./src
         file: String,
         /// Metadata field for storing any type of reference, e.g. a git commit hash
         r#ref: Option<String>,
         /// Metadata field for storing a description of the policy, e.g. a git commit message
         description: Option<String>,
     },
+    /// Publish a version of a policy from one environment to another
+    Promote {
+        /// Policy name to promote, e.g. allowed-regions
+        policy: String,
+        /// Version to promote, e.g. 0.1.4
+        version: String,
+        /// Environment id to promote from, e.g. cli/staging
+        #[arg(long)]
+        from: String,
+        /// Environment id to promote to, e.g. cli/production
+        #[arg(long)]
+        to: String,
+    },
+    /// Evaluate a policy against the test cases in its tests directory
+    Test {
+        /// Path to the policy with a tests/<case> directory per test case, e.g. ./src
+        path: String,
+    },
+    /// Generate a test case for a policy from the plan of a stored change record
+    GenerateFixture {
+        /// Path to the policy to add the test case to, e.g. ./src
+        path: String,
+        /// Name of the test case, e.g. public-bucket
+        name: String,
+        /// Deployment id of the change record, e.g. s3bucket/my-s3-bucket
+        #[arg(long)]
+        deployment_id: String,
+        /// Job id of the change record
+        #[arg(long)]
+        job_id: String,
+        /// Environment id of the deployment, e.g. cli/default (optional, will prompt if not provided)
+        #[arg(short, long)]
+        environment_id: Option<String>,
+        /// Type of the change record: plan, apply or destroy
+        #[arg(long, default_value = "plan")]
+        change_type: String,
+    },
     /// List all latest versions of policies from a specific environment
     List {
-        /// Environment to list from, e.g. aws, azure (optional, will prompt if not provided)
+        /// Environment id to list from, e.g. cli/default (optional, will prompt if not provided)
         environment_id: Option<String>,
     },
     /// List information about specific version of a policy
     Get {
-        /// Policy name to get, e.g. s3bucket
+        /// Policy name to get, e.g. allowed-regions
         policy: String,
-        /// Environment id to get from, e.g. cli/default (optional, will prompt if not provided)
-        environment_id: Option<String>,
+        /// Environment id to get from, e.g. cli/default
+        environment_id: String,
         /// Version to get, e.g. 0.1.4
         version: String,
     },
@@ -453,6 +537,44 @@ enum DeploymentCommands {
         /// Deployment id to describe, e.g. s3bucket/my-s3-bucket (optional, will prompt if not provided)
         deployment_id: Option<String>,
     },
+    /// Roll a deployment back to the module version and variables of a previous apply
+    Rollback {
+        /// Deployment id to roll back, e.g. s3bucket/my-s3-bucket (optional, will prompt if not provided)
+        deployment_id: Option<String>,
+        /// Environment id where the deployment exists, e.g. cli/default (optional, will prompt if not provided)
+        #[arg(short, long)]
+        environment_id: Option<String>,
+        /// Job id of the apply to roll back to
+        #[arg(long)]
+        to: String,
+        /// Apply the reviewed rollback plan without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Move a deployment to a new name and/or environment, keeping its state and history
+    Move {
+        /// Deployment id to move, e.g. s3bucket/my-s3-bucket (optional, will prompt if not provided)
+        deployment_id: Option<String>,
+        /// Environment id where the deployment exists, e.g. cli/default (optional, will prompt if not provided)
+        #[arg(short, long)]
+        environment_id: Option<String>,
+        /// New deployment id with the same module, e.g. s3bucket/my-renamed-s3-bucket
+        #[arg(long)]
+        to_deployment_id: Option<String>,
+        /// New environment id, e.g. cli/prod
+        #[arg(long)]
+        to_environment_id: Option<String>,
+        /// Move the deployment without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Cancel the job in progress for a deployment
+    Cancel {
+        /// Environment id where the deployment exists, e.g. cli/default (optional, will prompt if not provided)
+        environment_id: Option<String>,
+        /// Deployment id to cancel the job for, e.g. s3bucket/my-s3-bucket (optional, will prompt if not provided)
+        deployment_id: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -554,6 +676,23 @@ async fn main() {
                 commands::module::handle_deprecate(&module, &track, &version, message.as_deref())
                     .await;
             }
+            ModuleCommands::Rollout(args) => {
+                commands::rollout::handle_rollout(
+                    &args.module,
+                    commands::rollout::RolloutOptions {
+                        to_version: args.to,
+                        environment_order: args.environment_order,
+                        canary_size: args.canary,
+                        batch_size: args.batch_size,
+                        allow_destructive: args.allow_destructive,
+                        resume: args.resume,
+                        restart: args.restart,
+                        status: args.status,
+                        yes: args.yes,
+                    },
+                )
+                .await;
+            }
         },
         Commands::Stack { command } => match command {
             StackCommands::Preview { path } => {
@@ -591,12 +730,54 @@ async fn main() {
             PolicyCommands::Publish {
                 environment_id,
                 file,
-                r#ref: _,
-                description: _,
+                r#ref,
+                description,
+            } => {
+                let env = get_environment(&environment_id);
+                commands::policy::handle_publish(
+                    &file,
+                    &env,
+                    r#ref.as_deref(),
+                    description.as_deref(),
+                )
+                .await;
+            }
+            PolicyCommands::Promote {
+                policy,
+                version,
+                from,
+                to,
+            } => {
+                commands::policy::handle_promote(
+                    &policy,
+                    &version,
+                    &get_environment(&from),
+                    &get_environment(&to),
+                )
+                .await;
+            }
+            PolicyCommands::Test { path } => {
+                commands::policy::handle_test(&path);
+            }
+            PolicyCommands::GenerateFixture {
+                path,
+                name,
+                deployment_id,
+                job_id,
+                environment_id,
+                change_type,
             } => {
                 let environment_id = resolve_environment_id(environment_id).await;
                 let env = get_environment(&environment_id);
-                commands::policy::handle_publish(&file, &env).await;
+                commands::policy::handle_generate_fixture(
+                    &path,
+                    &name,
+                    &deployment_id,
+                    &job_id,
+                    &env,
+                    &change_type,
+                )
+                .await;
             }
             PolicyCommands::List { environment_id } => {
                 let environment_id = resolve_environment_id(environment_id).await;
@@ -608,7 +789,6 @@ async fn main() {
                 environment_id,
                 version,
             } => {
-                let environment_id = resolve_environment_id(environment_id).await;
                 let env = get_environment(&environment_id);
                 commands::policy::handle_get(&policy, &env, &version).await;
             }
@@ -694,10 +874,12 @@ async fn main() {
             claim,
             store_files,
             follow,
+            from_plan,
         } => {
             let environment_id = resolve_environment_id(environment_id).await;
             let env = get_environment(&environment_id);
-            commands::claim::handle_apply(&env, &claim, store_files, follow).await;
+            commands::claim::handle_apply(&env, &claim, store_files, follow, from_plan.as_deref())
+                .await;
         }
         Commands::Destroy {
             environment_id,
@@ -730,6 +912,53 @@ async fn main() {
                     resolve_environment_and_deployment(environment_id, deployment_id).await;
                 commands::deployment::handle_describe(&deployment_id, &environment_id).await;
             }
+            DeploymentCommands::Rollback {
+                deployment_id,
+                environment_id,
+                to,
+                yes,
+            } => {
+                let (environment_id, deployment_id) =
+                    resolve_environment_and_deployment(environment_id, deployment_id).await;
+                let env = get_environment(&environment_id);
+                commands::deployment::handle_rollback(&deployment_id, &env, &to, yes).await;
+            }
+            DeploymentCommands::Move {
+                deployment_id,
+                environment_id,
+                to_deployment_id,
+                to_environment_id,
+                yes,
+            } => {
+                if to_deployment_id.is_none() && to_environment_id.is_none() {
+                    eprintln!("Specify --to-deployment-id and/or --to-environment-id");
+                    std::process::exit(1);
+                }
+                let (environment_id, deployment_id) =
+                    resolve_environment_and_deployment(environment_id, deployment_id).await;
+                let env = get_environment(&environment_id);
+                let new_deployment_id = to_deployment_id.unwrap_or(deployment_id.clone());
+                let new_env = to_environment_id
+                    .map(|e| get_environment(&e))
+                    .unwrap_or(env.clone());
+                commands::deployment::handle_move(
+                    &deployment_id,
+                    &env,
+                    &new_deployment_id,
+                    &new_env,
+                    yes,
+                )
+                .await;
+            }
+            DeploymentCommands::Cancel {
+                environment_id,
+                deployment_id,
+            } => {
+                let (environment_id, deployment_id) =
+                    resolve_environment_and_deployment(environment_id, deployment_id).await;
+                let env = get_environment(&environment_id);
+                commands::deployment::handle_cancel(&deployment_id, &env).await;
+            }
         },
         Commands::Admin { command } => match command {
             AdminCommands::SetupWorkspace {
@@ -6,7 +6,7 @@ use env_common::{
     interface::{get_region_env_var, GenericCloudHandler},
     logic::{is_deployment_in_progress, is_deployment_plan_in_progress},
 };
-use env_defs::{pretty_print_resource_changes, CloudProvider, DeploymentResp};
+use env_defs::{pretty_print_resource_changes, CloudProvider, DeploymentResp, DeploymentStatus};
 use prettytable::{row, Table};
 
 use log::error;
@@ -67,7 +67,7 @@ pub async fn follow_execution(
                         } else {
                             "completed"
                         },
-                        status
+                        status.map(|s| s.to_string()).unwrap_or_default()
                     );
                     all_successful = false;
                 }
@@ -141,7 +141,7 @@ pub async fn follow_execution(
             println!("{}", "=".repeat(80));
 
             // Get change record for the operation (only if job didn't fail during init)
-            if deployment.status != "failed_init" {
+            if deployment.status != DeploymentStatus::FailedInit {
                 let record_type = operation.to_uppercase();
                 match GenericCloudHandler::region(region)
                     .await
@@ -150,6 +150,29 @@ pub async fn follow_execution(
                 {
                     Ok(change_record) => {
                         println!("\nOutput:\n{}", change_record.plan_std_output);
+                        if !change_record.upgrade_warnings.is_empty() {
+                            println!("\nModule upgrade warnings:");
+                            for warning in change_record.upgrade_warnings.iter() {
+                                println!("  - {}", warning);
+                            }
+                        }
+                        if let Some(cost_estimate) = &change_record.cost_estimate {
+                            println!("\nEstimated monthly cost: {}", cost_estimate);
+                            for resource in cost_estimate.resources.iter() {
+                                println!(
+                                    "  - {}: {:.2} -> {:.2}",
+                                    resource.address,
+                                    resource.monthly_cost_before,
+                                    resource.monthly_cost_after
+                                );
+                            }
+                        }
+                        if let Some(risk_assessment) = &change_record.risk_assessment {
+                            println!("\nRisk: {}", risk_assessment);
+                            for finding in risk_assessment.findings.iter() {
+                                println!("  - {}", finding.description);
+                            }
+                        }
                         std_output_table.add_row(row![
                             format!("{}\n({})", deployment_id, environment),
                             change_record.plan_std_output
@@ -174,7 +197,7 @@ pub async fn follow_execution(
             }
 
             // Display policy violations for all operations
-            if deployment.status == "failed_policy" {
+            if deployment.status == DeploymentStatus::FailedPolicy {
                 println!("\nPolicy Validation Failed:");
                 for result in deployment.policy_results.iter().filter(|p| p.failed) {
                     println!("  Policy: {}", result.policy);
@@ -191,6 +214,37 @@ pub async fn follow_execution(
             } else if !deployment.policy_results.is_empty() {
                 println!("\nPolicy Validation: Passed");
             }
+
+            let warnings: Vec<String> = deployment
+                .policy_results
+                .iter()
+                .flat_map(|r| {
+                    r.get_warnings()
+                        .into_iter()
+                        .map(|w| format!("{}: {}", r.policy, w))
+                })
+                .collect();
+            if !warnings.is_empty() {
+                println!("\nPolicy warnings:");
+                for warning in warnings.iter() {
+                    println!("  - {}", warning);
+                }
+            }
+            let waived: Vec<String> = deployment
+                .policy_results
+                .iter()
+                .flat_map(|r| {
+                    r.get_waived_violations()
+                        .into_iter()
+                        .map(|v| format!("{}: {}", r.policy, v))
+                })
+                .collect();
+            if !waived.is_empty() {
+                println!("\nWaived policy violations:");
+                for violation in waived.iter() {
+                    println!("  - {}", violation);
+                }
+            }
         }
     }
 
@@ -1,5 +1,8 @@
 use anyhow::Result;
-use env_common::{interface::GenericCloudHandler, logic::run_claim};
+use env_common::{
+    interface::GenericCloudHandler,
+    logic::{run_claim, run_claim_from_plan},
+};
 use env_defs::{DeploymentManifest, ExtraData};
 use serde::Deserialize;
 use std::vec;
@@ -13,6 +16,7 @@ pub async fn run_claim_file(
     store_files: bool,
     destroy: bool,
     follow: bool,
+    from_plan: Option<&str>,
 ) -> Result<(), anyhow::Error> {
     // Read claim yaml file:
     let file_content = std::fs::read_to_string(claim).expect("Failed to read claim file");
@@ -32,6 +36,15 @@ pub async fn run_claim_file(
         }
     };
 
+    // A plan file belongs to a single deployment
+    if from_plan.is_some() && claims.len() != 1 {
+        return Err(anyhow::anyhow!(
+            "Applying a saved plan requires a claim file with a single claim, {} has {}",
+            claim,
+            claims.len()
+        ));
+    }
+
     log::info!("Applying {} claims in file", claims.len());
     for yaml in claims.iter() {
         let flags = if destroy {
@@ -41,18 +54,36 @@ pub async fn run_claim_file(
         };
         let deployment_manifest: DeploymentManifest = serde_yaml::from_value(yaml.clone())?;
         let region = &deployment_manifest.spec.region;
-        let (job_id, deployment_id) = match run_claim(
-            &GenericCloudHandler::region(region).await,
-            yaml,
-            environment,
-            command,
-            flags,
-            ExtraData::None,
-            &reference_fallback,
-        )
-        .await
-        {
+        let handler = GenericCloudHandler::region(region).await;
+        let result = match from_plan {
+            Some(plan_job_id) => {
+                run_claim_from_plan(
+                    &handler,
+                    yaml,
+                    environment,
+                    plan_job_id,
+                    ExtraData::None,
+                    &reference_fallback,
+                )
+                .await
+            }
+            None => {
+                run_claim(
+                    &handler,
+                    yaml,
+                    environment,
+                    command,
+                    flags,
+                    ExtraData::None,
+                    &reference_fallback,
+                )
+                .await
+            }
+        };
+        let (job_id, deployment_id) = match result {
             Ok((job_id, deployment_id, _)) => (job_id, deployment_id),
+            // The saved plan must not be silently skipped
+            Err(e) if from_plan.is_some() => return Err(e),
             Err(e) => {
                 println!("Failed to run a manifest in claim {}: {}", claim, e);
                 continue;
@@ -6,7 +6,7 @@ use super::state::{
 };
 use super::utils::NavItem;
 use crate::current_region_handler;
-use env_defs::{CloudProvider, CloudProviderCommon, ModuleResp};
+use env_defs::{CloudProvider, CloudProviderCommon, DeploymentStatus, ModuleResp};
 
 // Re-export EventsLogView for backward compatibility with existing code
 pub use super::state::events_state::EventsLogView;
@@ -36,6 +36,7 @@ pub enum PendingAction {
     LoadChangeRecord(String, String, String, String), // job_id, environment, deployment_id, change_type
     ReapplyDeployment(usize),
     DestroyDeployment(usize),
+    CancelDeploymentJob(usize),
     ReloadCurrentDeploymentDetail,
     SaveClaimToFile,
     RunClaimFromBuilder,
@@ -72,7 +73,7 @@ pub struct GroupedModule {
 
 #[derive(Debug, Clone)]
 pub struct Deployment {
-    pub status: String,
+    pub status: DeploymentStatus,
     pub deployment_id: String,
     pub module: String,
     pub module_version: String,
@@ -458,6 +459,21 @@ impl App {
                 }
                 self.clear_loading();
             }
+            BackgroundMessage::DeploymentJobCancelled(result) => {
+                let message = match result {
+                    Ok((deployment_id, environment, job_id)) => format!(
+                        "✅ Job cancelled successfully!\n\nDeployment ID: {}\nEnvironment: {}\nJob ID: {}",
+                        deployment_id, environment, job_id
+                    ),
+                    Err(e) => format!("❌ Failed to cancel job:\n\n{}", e),
+                };
+                self.detail_state.show_message(message.clone());
+                self.detail_content = message;
+                self.showing_detail = true;
+                self.detail_scroll = 0;
+                self.clear_loading();
+                self.schedule_action(PendingAction::LoadDeployments);
+            }
             // Handle other message types as needed
             _ => {
                 // For now, just clear loading for unhandled messages
@@ -659,6 +675,10 @@ impl App {
                 self.selected_index = index;
                 self.destroy_deployment().await?;
             }
+            PendingAction::CancelDeploymentJob(index) => {
+                self.selected_index = index;
+                self.cancel_deployment_job().await?;
+            }
             PendingAction::ReloadCurrentDeploymentDetail => {
                 // Reload the current deployment detail in the background
                 self.reload_deployment_detail_background().await?;
@@ -727,6 +747,9 @@ impl App {
             PendingAction::DestroyDeployment(_) => {
                 self.set_loading("Destroying deployment...");
             }
+            PendingAction::CancelDeploymentJob(_) => {
+                self.set_loading("Cancelling job, waiting for it to stop...");
+            }
             PendingAction::ReloadCurrentDeploymentDetail => {
                 self.set_loading("Reloading deployment details...");
             }
@@ -1194,6 +1217,46 @@ impl App {
         Ok(())
     }
 
+    pub async fn cancel_deployment_job(&mut self) -> Result<()> {
+        use crate::tui::background::BackgroundMessage;
+        use env_common::logic::cancel_deployment_job;
+
+        let filtered_deployments = self.get_filtered_deployments();
+        if let Some(deployment) = filtered_deployments.get(self.selected_index) {
+            let deployment_id = deployment.deployment_id.clone();
+            let environment = deployment.environment.clone();
+
+            // Waiting for the job to stop takes a while, so it is done in the background
+            let cancel = async move {
+                cancel_deployment_job(
+                    &current_region_handler().await,
+                    &deployment_id,
+                    &environment,
+                )
+                .await
+                .map(|job_id| (deployment_id, environment, job_id))
+                .map_err(|e| e.to_string())
+            };
+            match &self.background_sender {
+                Some(sender) => {
+                    let sender_clone = sender.clone();
+                    tokio::spawn(async move {
+                        let result = cancel.await;
+                        let _ =
+                            sender_clone.send(BackgroundMessage::DeploymentJobCancelled(result));
+                    });
+                }
+                None => {
+                    let result = cancel.await;
+                    self.process_background_message(BackgroundMessage::DeploymentJobCancelled(
+                        result,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub async fn destroy_deployment(&mut self) -> Result<()> {
         use env_common::logic::destroy_infra;
         use env_defs::ExtraData;
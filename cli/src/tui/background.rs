@@ -33,6 +33,7 @@ pub enum BackgroundMessage {
     // Actions
     DeploymentReapplied(Result<(String, String, String), String>),
     DeploymentDestroyed(Result<String, String>),
+    DeploymentJobCancelled(Result<(String, String, String), String>), // deployment_id, environment, job_id
 }
 
 /// Create a channel for background task communication
@@ -115,6 +115,43 @@ impl MainHandler {
                     }
                     return Ok(());
                 }
+                KeyCode::Char('x') => {
+                    if matches!(app.current_view, crate::tui::app::View::Deployments) {
+                        let filtered_deployments = app.get_filtered_deployments();
+                        if app.selected_index < filtered_deployments.len() {
+                            let deployment = &filtered_deployments[app.selected_index];
+                            let message = format!(
+                                "Are you sure you want to cancel the job of this deployment?\n\n\
+                                Deployment ID: {}\n\
+                                Module: {} ({})\n\
+                                Environment: {}\n\
+                                Status: {}\n\n\
+                                Terraform is interrupted and killed if it does not stop in time.\n\
+                                Press 'y' to confirm or 'n' to cancel.",
+                                deployment.deployment_id,
+                                deployment.module,
+                                deployment.module_version,
+                                deployment.environment,
+                                deployment.status
+                            );
+
+                            // Update modal_state directly
+                            app.modal_state.show_confirmation(
+                                message.clone(),
+                                app.selected_index,
+                                PendingAction::CancelDeploymentJob(app.selected_index),
+                            );
+
+                            // Also update legacy fields for handler compatibility
+                            app.showing_confirmation = true;
+                            app.confirmation_message = message;
+                            app.confirmation_deployment_index = Some(app.selected_index);
+                            app.confirmation_action =
+                                PendingAction::CancelDeploymentJob(app.selected_index);
+                        }
+                    }
+                    return Ok(());
+                }
                 _ => {}
             }
         }
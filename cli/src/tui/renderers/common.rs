@@ -1,3 +1,4 @@
+use env_defs::DeploymentStatus;
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
@@ -11,6 +12,19 @@ use crate::tui::widgets::footer::FooterBar;
 use crate::tui::widgets::loading::LoadingWidget;
 use crate::tui::widgets::navigation::NavigationBar;
 
+/// Icon and color used when showing a deployment or event status
+pub fn status_icon_and_color(status: &DeploymentStatus) -> (&'static str, Color) {
+    if *status == DeploymentStatus::Successful {
+        ("✓", Color::Green)
+    } else if status.is_failure() {
+        ("✗", Color::Red)
+    } else if status.is_in_progress() {
+        ("⏳", Color::Yellow)
+    } else {
+        ("•", Color::White)
+    }
+}
+
 /// Render loading screen
 pub fn render_loading(frame: &mut Frame, area: Rect, app: &App) {
     let widget = LoadingWidget::new(&app.loading_message);
@@ -195,6 +209,7 @@ fn get_footer_actions(app: &App) -> Vec<(&'static str, &'static str)> {
             ("r", "Reload"),
             ("Ctrl+R", "Reapply"),
             ("Ctrl+D", "Destroy"),
+            ("Ctrl+X", "Cancel Job"),
             ("Ctrl+C", "Quit"),
         ]
     } else {
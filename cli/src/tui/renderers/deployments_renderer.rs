@@ -7,6 +7,7 @@ use ratatui::{
 };
 
 use crate::tui::app::App;
+use crate::tui::renderers::common::status_icon_and_color;
 
 /// Helper function to truncate strings
 fn truncate(s: &str, max_len: usize) -> String {
@@ -29,12 +30,7 @@ pub fn render_deployments(frame: &mut Frame, area: Rect, app: &App) {
     let items: Vec<ListItem> = filtered_deployments
         .iter()
         .map(|deployment| {
-            let (status_icon, status_color) = match deployment.status.as_str() {
-                "DEPLOYED" => ("✓", Color::Green),
-                "FAILED" => ("✗", Color::Red),
-                "IN_PROGRESS" => ("⏳", Color::Yellow),
-                _ => ("•", Color::White),
-            };
+            let (status_icon, status_color) = status_icon_and_color(&deployment.status);
 
             let content = vec![
                 Span::styled(
@@ -49,7 +45,7 @@ pub fn render_deployments(frame: &mut Frame, area: Rect, app: &App) {
                     Style::default().fg(Color::DarkGray),
                 ),
                 Span::styled(
-                    format!("{:<14}", truncate(&deployment.status, 13)),
+                    format!("{:<14}", truncate(deployment.status.as_str(), 13)),
                     Style::default().fg(status_color),
                 ),
                 Span::styled(
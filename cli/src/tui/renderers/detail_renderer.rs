@@ -10,6 +10,7 @@ use ratatui::{
 };
 
 use crate::tui::app::{App, PendingAction, View};
+use crate::tui::renderers::common::status_icon_and_color;
 use crate::tui::utils::{is_variable_required, to_camel_case, NavItem};
 
 /// Render detail view (module/stack/deployment details)
@@ -404,12 +405,7 @@ fn build_deployment_detail_content(
             ),
         ]));
 
-        let (status_icon, status_color) = match deployment.status.as_str() {
-            "DEPLOYED" => ("✓", Color::Green),
-            "FAILED" => ("✗", Color::Red),
-            "IN_PROGRESS" => ("⏳", Color::Yellow),
-            _ => ("•", Color::White),
-        };
+        let (status_icon, status_color) = status_icon_and_color(&deployment.status);
 
         lines.push(Line::from(vec![
             Span::styled("Status: ", Style::default().fg(Color::DarkGray)),
@@ -421,7 +417,7 @@ fn build_deployment_detail_content(
             ),
             Span::raw(" "),
             Span::styled(
-                deployment.status.clone(),
+                deployment.status.to_string(),
                 Style::default()
                     .fg(status_color)
                     .add_modifier(Modifier::BOLD),
@@ -523,6 +519,16 @@ fn build_deployment_detail_content(
             ),
         ]));
 
+        if let Some(expires_at_epoch) = deployment.expires_at_epoch {
+            let expires_at = chrono::DateTime::from_timestamp((expires_at_epoch / 1000) as i64, 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .unwrap_or_else(|| "Unknown".to_string());
+            lines.push(Line::from(vec![
+                Span::styled("Expires: ", Style::default().fg(Color::DarkGray)),
+                Span::styled(expires_at, Style::default().fg(Color::Yellow)),
+            ]));
+        }
+
         lines.push(Line::from(""));
 
         // Drift Detection subsection
@@ -573,6 +579,41 @@ fn build_deployment_detail_content(
                     }),
                 ),
             ]));
+
+            if let Some(jitter) = &deployment.drift_detection.jitter {
+                lines.push(Line::from(vec![
+                    Span::styled("Jitter: ", Style::default().fg(Color::DarkGray)),
+                    Span::styled(jitter.clone(), Style::default().fg(Color::White)),
+                ]));
+            }
+
+            if !deployment.drift_detection.quiet_hours.is_empty() {
+                let quiet_hours = deployment
+                    .drift_detection
+                    .quiet_hours
+                    .iter()
+                    .map(|q| format!("{}-{} UTC", q.start, q.end))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                lines.push(Line::from(vec![
+                    Span::styled("Quiet Hours: ", Style::default().fg(Color::DarkGray)),
+                    Span::styled(quiet_hours, Style::default().fg(Color::White)),
+                ]));
+            }
+
+            // Computed by the same schedule as the reconciler when the last job finished
+            if deployment.next_drift_check_epoch > 0 {
+                let next_check = chrono::DateTime::from_timestamp(
+                    (deployment.next_drift_check_epoch / 1000) as i64,
+                    0,
+                )
+                .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .unwrap_or_else(|| "Unknown".to_string());
+                lines.push(Line::from(vec![
+                    Span::styled("Next Check: ", Style::default().fg(Color::DarkGray)),
+                    Span::styled(next_check, Style::default().fg(Color::White)),
+                ]));
+            }
         }
 
         lines.push(Line::from(""));
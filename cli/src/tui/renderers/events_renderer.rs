@@ -7,7 +7,8 @@ use ratatui::{
 };
 
 use crate::tui::app::{App, EventsLogView};
-use env_defs::EventData;
+use crate::tui::renderers::common::status_icon_and_color;
+use env_defs::{EventData, RiskLevel};
 
 /// Helper function to truncate strings
 fn truncate(s: &str, max_len: usize) -> String {
@@ -126,12 +127,7 @@ pub fn render_events(frame: &mut Frame, area: Rect, app: &mut App) {
             };
 
             // Color code based on status
-            let (status_icon, status_color) = match status.as_str() {
-                "completed" | "success" => ("✓", Color::Green),
-                "failed" | "error" => ("✗", Color::Red),
-                "in_progress" | "running" => ("⏳", Color::Yellow),
-                _ => ("•", Color::White),
-            };
+            let (status_icon, status_color) = status_icon_and_color(status);
 
             // Get timestamp from last event
             let timestamp = &last_event.timestamp;
@@ -152,7 +148,10 @@ pub fn render_events(frame: &mut Frame, area: Rect, app: &mut App) {
                             .add_modifier(Modifier::BOLD),
                     ),
                     Span::raw(" "),
-                    Span::styled(truncate(status, 15), Style::default().fg(status_color)),
+                    Span::styled(
+                        truncate(status.as_str(), 15),
+                        Style::default().fg(status_color),
+                    ),
                 ]),
                 Line::from(vec![
                     Span::raw("  "),
@@ -352,12 +351,7 @@ fn render_events_content<'a>(
 
     // Get last event for overall status
     let last_event = events.last().unwrap();
-    let (status_icon, status_color) = match last_event.status.as_str() {
-        "completed" | "success" => ("✓", Color::Green),
-        "failed" | "error" => ("✗", Color::Red),
-        "in_progress" | "running" => ("⏳", Color::Yellow),
-        _ => ("•", Color::White),
-    };
+    let (status_icon, status_color) = status_icon_and_color(&last_event.status);
 
     // Show job header with action and status
     log_lines.push(Line::from(vec![
@@ -376,7 +370,7 @@ fn render_events_content<'a>(
         ),
         Span::raw(" "),
         Span::styled(
-            &last_event.status,
+            last_event.status.as_str(),
             Style::default()
                 .fg(status_color)
                 .add_modifier(Modifier::BOLD),
@@ -390,12 +384,7 @@ fn render_events_content<'a>(
 
     // Show events for this job
     for (idx, event) in events.iter().enumerate() {
-        let (evt_icon, evt_color) = match event.status.as_str() {
-            "completed" | "success" => ("✓", Color::Green),
-            "failed" | "error" => ("✗", Color::Red),
-            "in_progress" | "running" => ("⏳", Color::Yellow),
-            _ => ("•", Color::White),
-        };
+        let (evt_icon, evt_color) = status_icon_and_color(&event.status);
 
         log_lines.push(Line::from(Span::styled(
             "━".repeat(70),
@@ -421,7 +410,7 @@ fn render_events_content<'a>(
         log_lines.push(Line::from(vec![
             Span::styled("  Status: ", Style::default().fg(Color::DarkGray)),
             Span::styled(
-                &event.status,
+                event.status.as_str(),
                 Style::default().fg(evt_color).add_modifier(Modifier::BOLD),
             ),
             Span::raw("  │  "),
@@ -531,6 +520,63 @@ fn render_changelog_content<'a>(
 
     // If change record is available, display the plan/apply output
     if let Some(record) = change_record {
+        if let Some(cost_estimate) = &record.cost_estimate {
+            let cost_color = if cost_estimate.monthly_delta > 0.0 {
+                Color::Yellow
+            } else {
+                Color::Green
+            };
+            log_lines.push(Line::from(vec![
+                Span::styled("Estimated cost: ", Style::default().fg(Color::DarkGray)),
+                Span::styled(cost_estimate.to_string(), Style::default().fg(cost_color)),
+            ]));
+            for resource in cost_estimate.resources.iter() {
+                log_lines.push(Line::from(Span::styled(
+                    format!(
+                        "  {}: {:.2} -> {:.2}",
+                        resource.address, resource.monthly_cost_before, resource.monthly_cost_after
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            log_lines.push(Line::from(""));
+        }
+        if let Some(risk_assessment) = &record.risk_assessment {
+            let risk_color = match risk_assessment.level {
+                RiskLevel::Low => Color::Green,
+                RiskLevel::Medium => Color::Yellow,
+                RiskLevel::High | RiskLevel::Critical => Color::Red,
+            };
+            log_lines.push(Line::from(vec![
+                Span::styled("Risk: ", Style::default().fg(Color::DarkGray)),
+                Span::styled(risk_assessment.to_string(), Style::default().fg(risk_color)),
+            ]));
+            for finding in risk_assessment.findings.iter() {
+                log_lines.push(Line::from(Span::styled(
+                    format!("  {}", finding.description),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            log_lines.push(Line::from(""));
+        }
+        if let Some(protected_override) = &record.protected_resource_override {
+            log_lines.push(Line::from(vec![
+                Span::styled(
+                    "Protected resources overridden: ",
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    protected_override.resources.join(", "),
+                    Style::default().fg(Color::Red),
+                ),
+            ]));
+            log_lines.push(Line::from(Span::styled(
+                format!("  Reason: {}", protected_override.reason),
+                Style::default().fg(Color::DarkGray),
+            )));
+            log_lines.push(Line::from(""));
+        }
+
         // Display the plan/apply output with diff-style coloring
         for line in record.plan_std_output.lines() {
             let trimmed = line.trim_start();
@@ -597,12 +643,7 @@ fn render_changelog_content<'a>(
 
         // Show a timeline of events with timestamps
         for event in events.iter() {
-            let (status_icon, status_color) = match event.status.as_str() {
-                "completed" | "success" => ("✓", Color::Green),
-                "failed" | "error" => ("✗", Color::Red),
-                "in_progress" | "running" => ("⏳", Color::Yellow),
-                _ => ("•", Color::White),
-            };
+            let (status_icon, status_color) = status_icon_and_color(&event.status);
 
             log_lines.push(Line::from(vec![
                 Span::styled(
@@ -663,7 +663,7 @@ impl ClaimBuilderState {
             variables,
             // Fill in other required fields with defaults
             epoch: 0,
-            status: String::new(),
+            status: env_defs::DeploymentStatus::Unknown(String::new()),
             job_id: String::new(),
             project_id: String::new(),
             module: module.module_name.clone(),
@@ -674,6 +674,8 @@ impl ClaimBuilderState {
                 interval: "24h".to_string(),
                 auto_remediate: false,
                 webhooks: Vec::new(),
+                jitter: None,
+                quiet_hours: Vec::new(),
             },
             next_drift_check_epoch: 0,
             has_drifted: false,
@@ -687,6 +689,8 @@ impl ClaimBuilderState {
             memory: String::new(),
             reference: String::new(),
             tf_resources: None,
+            expires_at_epoch: None,
+            labels: std::collections::BTreeMap::new(),
         };
 
         // Use the existing generate_deployment_claim function
@@ -129,6 +129,9 @@ pub trait CloudProvider: Send + Sync {
         include_deleted: bool,
     ) -> Result<Option<DeploymentResp>, anyhow::Error>;
     async fn get_job_status(&self, job_id: &str) -> Result<Option<JobStatus>, anyhow::Error>;
+    /// Asks the runner of the job to stop (terraform is interrupted gracefully and killed if it
+    /// does not stop in time), the runner records the cancellation
+    async fn cancel_job(&self, job_id: &str) -> Result<(), anyhow::Error>;
     async fn get_deployments_using_module(
         &self,
         module: &str,
@@ -168,6 +171,8 @@ pub trait CloudProvider: Send + Sync {
         job_id: &str,
         change_type: &str,
     ) -> Result<InfraChangeRecord, anyhow::Error>;
+    // Rollout
+    async fn get_rollout(&self, module: &str) -> Result<Option<Value>, anyhow::Error>;
     // Policy
     async fn get_newest_policy_version(
         &self,
@@ -189,4 +194,13 @@ pub trait CloudProvider: Send + Sync {
         deployment_id: &str,
         output: Option<String>,
     ) -> Result<(), anyhow::Error>;
+    async fn move_state_file(
+        &self,
+        environment: &str,
+        deployment_id: &str,
+        new_environment: &str,
+        new_deployment_id: &str,
+    ) -> Result<(), anyhow::Error>;
+    /// Value of a secret referenced as `{{ secret::name }}` in claim variables
+    async fn get_secret_value(&self, name: &str) -> Result<String, anyhow::Error>;
 }
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// Monthly cost of the resources in a plan, estimated with a price catalog
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CostEstimate {
+    pub currency: String,
+    /// Version of the price catalog the estimate is based on
+    pub catalog_version: String,
+    pub monthly_cost_before: f64,
+    pub monthly_cost_after: f64,
+    pub monthly_delta: f64,
+    /// Priced resources whose cost changes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<ResourceCostEstimate>,
+    /// Resource types in the plan that are not in the price catalog and not part of the totals
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unpriced_resource_types: Vec<String>,
+}
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ResourceCostEstimate {
+    pub address: String,
+    pub resource_type: String,
+    pub monthly_cost_before: f64,
+    pub monthly_cost_after: f64,
+}
+
+impl std::fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{:.2} {}/month ({:.2} -> {:.2}, price catalog {})",
+            if self.monthly_delta < 0.0 { "-" } else { "+" },
+            self.monthly_delta.abs(),
+            self.currency,
+            self.monthly_cost_before,
+            self.monthly_cost_after,
+            self.catalog_version
+        )
+    }
+}
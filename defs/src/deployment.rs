@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::WebhookEventType;
 
 pub fn get_deployment_identifier(
     project_id: &str,
@@ -51,6 +54,14 @@ pub struct DeploymentSpec {
     pub dependencies: Option<Vec<DependencySpec>>,
     #[serde(rename = "driftDetection")]
     pub drift_detection: Option<DriftDetection>,
+    /// The deployment is destroyed when this duration (e.g. `8h`, `3d`) has passed since the claim was applied
+    pub ttl: Option<String>,
+    /// The deployment is destroyed at this RFC 3339 timestamp, cannot be combined with `ttl`
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<String>,
+    /// Existing resources to adopt instead of creating them, resource address (within the module,
+    /// or within the stack for stacks) to the id of the resource in the cloud
+    pub imports: Option<BTreeMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -66,7 +77,8 @@ pub struct DependencySpec {
 pub struct DeploymentResp {
     pub epoch: u128,
     pub deployment_id: String,
-    pub status: String,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub status: crate::DeploymentStatus,
     pub job_id: String,
     pub environment: String,
     pub project_id: String,
@@ -89,6 +101,12 @@ pub struct DeploymentResp {
     pub memory: String,
     pub reference: String,
     pub tf_resources: Option<Vec<String>>,
+    /// Epoch (ms) when the reconciler destroys the deployment, set from `ttl` or `expiresAt` in the claim
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at_epoch: Option<u128>,
+    /// Labels of the claim, used to select the policies that apply to the deployment
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
 }
 
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
@@ -142,6 +160,7 @@ pub struct DriftDetection {
     #[serde(default = "default_drift_detection_false")]
     pub enabled: bool,
 
+    /// Duration between checks (`6h`) or a cron expression in UTC (`0 */6 * * 1-5`)
     #[serde(default = "default_drift_detection_interval")]
     pub interval: String,
 
@@ -151,13 +170,48 @@ pub struct DriftDetection {
 
     #[serde(default = "default_drift_detection_empty_list")]
     pub webhooks: Vec<Webhook>,
+
+    /// Upper bound of a delay (`10m`) added to each check, the same for every run of a deployment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<String>,
+
+    /// Time windows in which neither drift checks nor auto-remediation run
+    #[serde(default, rename = "quietHours", skip_serializing_if = "Vec::is_empty")]
+    pub quiet_hours: Vec<QuietHours>,
+}
+
+/// A daily window in UTC, `start` and `end` are `HH:MM` and the window can cross midnight
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
 }
 
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Webhook {
     pub url: Option<String>,
-    // TODO: Add alias to provide option to avoid having to provide sensitive url in the config
+
+    /// Name of a secret holding the url, or a JSON object with `url` and `signingSecret`,
+    /// to avoid having to provide a sensitive url in the claim
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+
+    /// Name of a secret holding the key used to sign the payload
+    #[serde(
+        default,
+        rename = "signingSecret",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub signing_secret: Option<String>,
+
+    #[serde(default = "default_webhook_events")]
+    pub events: Vec<WebhookEventType>,
+}
+
+fn default_webhook_events() -> Vec<WebhookEventType> {
+    vec![WebhookEventType::Drift]
 }
 
 fn default_drift_detection_false() -> bool {
@@ -0,0 +1,208 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Status of a deployment (and of the events recorded for it).
+///
+/// Stored as a plain string in the database, values that are not recognized (e.g. written by
+/// an older version) are kept as `Unknown` so they can still be read and written back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum DeploymentStatus {
+    /// A job has been requested but the runner has not started yet
+    Requested,
+    /// The runner has started working on the job
+    Initiated,
+    Successful,
+    Failed,
+    FailedInit,
+    FailedPrepare,
+    FailedIntegrityCheck,
+    FailedValidate,
+    FailedPlan,
+    FailedShowPlan,
+    FailedPolicy,
+    FailedOutput,
+    /// Apply was stopped since one or more dependencies are not successful yet
+    WaitingOnDependency,
+    /// Destroy was stopped since other deployments depend on this deployment
+    HasDependants,
+    /// The job was cancelled before it finished
+    Cancelled,
+    Error,
+    Unknown(String),
+}
+
+impl DeploymentStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            DeploymentStatus::Requested => "requested",
+            DeploymentStatus::Initiated => "initiated",
+            DeploymentStatus::Successful => "successful",
+            DeploymentStatus::Failed => "failed",
+            DeploymentStatus::FailedInit => "failed_init",
+            DeploymentStatus::FailedPrepare => "failed_prepare",
+            DeploymentStatus::FailedIntegrityCheck => "failed_integrity_check",
+            DeploymentStatus::FailedValidate => "failed_validate",
+            DeploymentStatus::FailedPlan => "failed_plan",
+            DeploymentStatus::FailedShowPlan => "failed_show_plan",
+            DeploymentStatus::FailedPolicy => "failed_policy",
+            DeploymentStatus::FailedOutput => "failed_output",
+            DeploymentStatus::WaitingOnDependency => "waiting-on-dependency",
+            DeploymentStatus::HasDependants => "has-dependants",
+            DeploymentStatus::Cancelled => "cancelled",
+            DeploymentStatus::Error => "error",
+            DeploymentStatus::Unknown(status) => status,
+        }
+    }
+
+    /// A job is requested or running for the deployment
+    pub fn is_in_progress(&self) -> bool {
+        matches!(
+            self,
+            DeploymentStatus::Requested | DeploymentStatus::Initiated
+        )
+    }
+
+    /// The job for the deployment has finished, no more updates are expected until a new job is requested
+    pub fn is_final(&self) -> bool {
+        !self.is_in_progress()
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            DeploymentStatus::Failed
+                | DeploymentStatus::FailedInit
+                | DeploymentStatus::FailedPrepare
+                | DeploymentStatus::FailedIntegrityCheck
+                | DeploymentStatus::FailedValidate
+                | DeploymentStatus::FailedPlan
+                | DeploymentStatus::FailedShowPlan
+                | DeploymentStatus::FailedPolicy
+                | DeploymentStatus::FailedOutput
+                | DeploymentStatus::Error
+        )
+    }
+
+    /// Legal transitions are:
+    /// * `requested` -> `initiated`, or directly to a final status if the job could not be started
+    /// * `initiated` -> any final status, or back to `requested` if the job was abandoned
+    /// * any final status -> `requested` when a new job is requested
+    /// * a failed status -> `cancelled` when the job failed since it was cancelled
+    ///
+    /// Keeping the same status is always allowed.
+    pub fn can_transition_to(&self, next: &DeploymentStatus) -> bool {
+        self == next
+            || self.is_in_progress()
+            || *next == DeploymentStatus::Requested
+            || (self.is_failure() && *next == DeploymentStatus::Cancelled)
+    }
+}
+
+impl From<&str> for DeploymentStatus {
+    fn from(status: &str) -> Self {
+        match status {
+            "requested" => DeploymentStatus::Requested,
+            "initiated" => DeploymentStatus::Initiated,
+            "successful" => DeploymentStatus::Successful,
+            // "failure" has been used interchangeably with "failed"
+            "failed" | "failure" => DeploymentStatus::Failed,
+            "failed_init" => DeploymentStatus::FailedInit,
+            "failed_prepare" => DeploymentStatus::FailedPrepare,
+            "failed_integrity_check" => DeploymentStatus::FailedIntegrityCheck,
+            "failed_validate" => DeploymentStatus::FailedValidate,
+            "failed_plan" => DeploymentStatus::FailedPlan,
+            "failed_show_plan" => DeploymentStatus::FailedShowPlan,
+            "failed_policy" => DeploymentStatus::FailedPolicy,
+            "failed_output" => DeploymentStatus::FailedOutput,
+            "waiting-on-dependency" => DeploymentStatus::WaitingOnDependency,
+            "has-dependants" => DeploymentStatus::HasDependants,
+            "cancelled" => DeploymentStatus::Cancelled,
+            "error" => DeploymentStatus::Error,
+            _ => DeploymentStatus::Unknown(status.to_string()),
+        }
+    }
+}
+
+impl From<String> for DeploymentStatus {
+    fn from(status: String) -> Self {
+        DeploymentStatus::from(status.as_str())
+    }
+}
+
+impl From<DeploymentStatus> for String {
+    fn from(status: DeploymentStatus) -> Self {
+        status.as_str().to_string()
+    }
+}
+
+impl fmt::Display for DeploymentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_legacy_statuses() {
+        let statuses: Vec<DeploymentStatus> = serde_json::from_str(
+            r#"["requested", "initiated", "successful", "failure", "failed_policy", "waiting-on-dependency", "something-else"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            statuses,
+            vec![
+                DeploymentStatus::Requested,
+                DeploymentStatus::Initiated,
+                DeploymentStatus::Successful,
+                DeploymentStatus::Failed,
+                DeploymentStatus::FailedPolicy,
+                DeploymentStatus::WaitingOnDependency,
+                DeploymentStatus::Unknown("something-else".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_serialize_status() {
+        assert_eq!(
+            serde_json::to_string(&DeploymentStatus::HasDependants).unwrap(),
+            r#""has-dependants""#
+        );
+        assert_eq!(
+            serde_json::to_string(&DeploymentStatus::Unknown("legacy".to_string())).unwrap(),
+            r#""legacy""#
+        );
+    }
+
+    #[test]
+    fn test_legal_transitions() {
+        use DeploymentStatus::*;
+        assert!(Requested.can_transition_to(&Initiated));
+        assert!(Requested.can_transition_to(&Failed));
+        assert!(Initiated.can_transition_to(&Successful));
+        assert!(Initiated.can_transition_to(&FailedPlan));
+        assert!(Initiated.can_transition_to(&Requested));
+        assert!(Successful.can_transition_to(&Requested));
+        assert!(Successful.can_transition_to(&Successful));
+        assert!(Unknown("legacy".to_string()).can_transition_to(&Requested));
+        assert!(Initiated.can_transition_to(&Cancelled));
+        assert!(FailedPlan.can_transition_to(&Cancelled));
+        assert!(Cancelled.can_transition_to(&Requested));
+    }
+
+    #[test]
+    fn test_illegal_transitions() {
+        use DeploymentStatus::*;
+        assert!(!Successful.can_transition_to(&Initiated));
+        assert!(!Successful.can_transition_to(&Failed));
+        assert!(!FailedPolicy.can_transition_to(&Successful));
+        assert!(!Error.can_transition_to(&Initiated));
+        assert!(!Successful.can_transition_to(&Cancelled));
+        assert!(!Cancelled.can_transition_to(&Successful));
+    }
+}
@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crate::{
     deployment::{Dependency, DriftDetection},
-    ExtraData,
+    ExtraData, ModuleUpgradeWarning,
 };
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -27,6 +28,32 @@ pub struct ApiInfraPayload {
     pub memory: String,
     pub reference: String,
     pub extra_data: ExtraData,
+    /// Set when the job re-applies a dependent deployment since the outputs of a deployment it depends on changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triggered_by: Option<CascadeTrigger>,
+    /// Epoch (ms) when the deployment expires and is destroyed by the reconciler
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at_epoch: Option<u128>,
+    /// Job id of a plan whose saved plan file is applied instead of planning again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_plan: Option<String>,
+    /// Existing resources to import, resource address to cloud id (see `DeploymentSpec::imports`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub imports: BTreeMap<String, String>,
+    /// Variables of the claim affected by changing the module version of the deployment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upgrade_warnings: Vec<ModuleUpgradeWarning>,
+    /// Labels of the claim, used to select the policies that apply to the deployment
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// The job whose changed outputs caused dependent deployments to be re-applied
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CascadeTrigger {
+    pub deployment_id: String,
+    pub environment: String,
+    pub job_id: String,
 }
 
 #[derive(Clone)]
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::resource_change::SanitizedResourceChange;
+use crate::{CostEstimate, ModuleUpgradeWarning, ProtectedResourceOverride, RiskAssessment};
 
 pub fn get_change_record_identifier(
     project_id: &str,
@@ -41,4 +42,26 @@ pub struct InfraChangeRecord {
     /// Optional for backward compatibility with older change records.
     #[serde(default)]
     pub variables: Value,
+    /// Storage key for the binary Terraform plan file, only set for plans.
+    /// Allows applying exactly what was planned with `apply --from-plan`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_file_key: Option<String>,
+    /// Serial of the Terraform state the plan was created from, `None` if there was no state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_serial: Option<u64>,
+    /// Lineage of the Terraform state the plan was created from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_lineage: Option<String>,
+    /// Variables of the claim affected by changing the module version, see `ApiInfraPayload`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upgrade_warnings: Vec<ModuleUpgradeWarning>,
+    /// Monthly cost before and after the change, `None` for records without a plan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_estimate: Option<CostEstimate>,
+    /// Risk score and summary of the resource changes, `None` for records without a plan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_assessment: Option<RiskAssessment>,
+    /// Protected resources deleted or replaced by this change with the reason given in the claim
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protected_resource_override: Option<ProtectedResourceOverride>,
 }
@@ -1,6 +1,8 @@
 mod api;
 mod cloudprovider;
+mod cost;
 mod deployment;
+mod deployment_status;
 mod environment;
 mod errors;
 mod event;
@@ -12,18 +14,23 @@ mod module;
 mod notification;
 mod oci;
 mod policy;
+mod protected_resource;
 mod resource;
 mod resource_change;
+mod risk;
 mod stack;
 mod tfprovider;
+mod webhook;
 
 pub use api::GenericFunctionResponse;
 pub use cloudprovider::{CloudProvider, CloudProviderCommon};
+pub use cost::{CostEstimate, ResourceCostEstimate};
 pub use deployment::{
     get_deployment_identifier, Dependency, DependencySpec, Dependent, DeploymentManifest,
     DeploymentResp, DeploymentSpec, DriftDetection, JobStatus, Metadata as DeploymentMetadata,
-    ProjectData, Webhook, DEFAULT_DRIFT_DETECTION_INTERVAL,
+    ProjectData, QuietHours, Webhook, DEFAULT_DRIFT_DETECTION_INTERVAL,
 };
+pub use deployment_status::DeploymentStatus;
 pub use environment::EnvironmentResp;
 pub use errors::CloudHandlerError;
 pub use event::{get_event_identifier, EventData};
@@ -31,26 +38,33 @@ pub use gitprovider::{
     CheckRun, CheckRunOutput, ExtraData, GitHubCheckRun, Installation, JobDetails, Owner,
     Repository, User,
 };
-pub use infra::{ApiInfraPayload, ApiInfraPayloadWithVariables};
+pub use infra::{ApiInfraPayload, ApiInfraPayloadWithVariables, CascadeTrigger};
 pub use infra_change_record::{get_change_record_identifier, InfraChangeRecord};
 pub use log::LogData;
 pub use module::{
-    deserialize_module_manifest, get_module_identifier, Metadata, ModuleDiffAddition,
-    ModuleDiffChange, ModuleDiffRemoval, ModuleExample, ModuleManifest, ModuleResp, ModuleSpec,
-    ModuleStackData, ModuleVersionDiff, Provider, StackModule, TfLockProvider, TfOutput,
-    TfRequiredProvider, TfValidation, TfVariable,
+    deserialize_module_manifest, get_module_identifier, get_rollout_identifier, Metadata,
+    ModuleDiffAddition, ModuleDiffChange, ModuleDiffRemoval, ModuleExample, ModuleManifest,
+    ModuleResp, ModuleSpec, ModuleStackData, ModuleUpgradeWarning, ModuleUpgradeWarningKind,
+    ModuleVersionDiff, Provider, StackModule, TfLockProvider, TfOutput, TfRequiredProvider,
+    TfValidation, TfVariable,
 };
 pub use notification::NotificationData;
 pub use oci::{
     ArtifactType, Blob, IndexEntry, IndexJson, LayerDesc, LayoutFile, OciArtifactSet, OciManifest,
 };
 pub use policy::{
-    deserialize_policy_manifest, get_policy_identifier, PolicyManifest, PolicyResp, PolicyResult,
+    deserialize_policy_manifest, get_finding_id, get_policy_identifier, PolicyManifest, PolicyResp,
+    PolicyResult, PolicySpec, PolicyWaiver,
+};
+pub use protected_resource::{
+    ProtectedResourceOverride, PROTECTED_RESOURCES_OVERRIDE_ANNOTATION,
 };
 pub use resource::ResourceResp;
 pub use resource_change::{
     pretty_print_resource_changes, sanitize_resource_changes, sanitize_resource_changes_from_plan,
     ResourceAction, ResourceMode, SanitizedResourceChange,
 };
+pub use risk::{RiskAssessment, RiskFinding, RiskFindingKind, RiskLevel};
 pub use stack::StackManifest;
 pub use tfprovider::{Metadata as ProviderMetaData, ProviderManifest, ProviderResp, ProviderSpec};
+pub use webhook::{WebhookEventType, WebhookPayload, WEBHOOK_PAYLOAD_SCHEMA_VERSION};
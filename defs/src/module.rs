@@ -7,6 +7,10 @@ pub fn get_module_identifier(module: &str, track: &str) -> String {
     format!("{}::{}", track, module)
 }
 
+pub fn get_rollout_identifier(project_id: &str, region: &str, module: &str) -> String {
+    format!("{}::{}::{}", project_id, region, module)
+}
+
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TfVariable {
@@ -25,6 +29,8 @@ pub struct TfVariable {
     pub nullable: bool,
     #[serde(default)]
     pub sensitive: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validations: Vec<TfValidation>,
 }
 
 fn default_tf_variable_type() -> serde_json::Value {
@@ -46,7 +52,10 @@ where
     Ok(Some(v))
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+/// A `validation` block of a Terraform variable, `expression` is the HCL source of the
+/// condition and `message` the error message template
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct TfValidation {
     pub expression: String,
     pub message: String,
@@ -122,6 +131,79 @@ pub struct ModuleVersionDiff {
     pub previous_version: String,
 }
 
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleUpgradeWarningKind {
+    /// The claim does not set the variable and relies on its default, which changed
+    DefaultChanged,
+    /// The variable does not exist in the new version
+    Removed,
+    /// The variable has no default in the new version and the claim does not set it
+    BecameRequired,
+}
+
+/// A variable of the claim affected by changing the module version
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ModuleUpgradeWarning {
+    /// Variable name as written in the claim (camelCase)
+    pub variable: String,
+    pub kind: ModuleUpgradeWarningKind,
+    pub from_version: String,
+    pub to_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_default: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_default: Option<serde_json::Value>,
+    #[serde(default)]
+    pub set_in_claim: bool,
+}
+
+impl ModuleUpgradeWarning {
+    /// Errors make the claim invalid for the new version, the rest only changes its behaviour
+    pub fn is_error(&self) -> bool {
+        match self.kind {
+            ModuleUpgradeWarningKind::DefaultChanged => false,
+            ModuleUpgradeWarningKind::Removed => self.set_in_claim,
+            ModuleUpgradeWarningKind::BecameRequired => true,
+        }
+    }
+}
+
+impl std::fmt::Display for ModuleUpgradeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let default = |value: &Option<serde_json::Value>| match value {
+            Some(value) => value.to_string(),
+            None => "no default".to_string(),
+        };
+        match self.kind {
+            ModuleUpgradeWarningKind::DefaultChanged => write!(
+                f,
+                "Default of {} changes from {} to {} ({} -> {}), the claim does not set it",
+                self.variable,
+                default(&self.old_default),
+                default(&self.new_default),
+                self.from_version,
+                self.to_version
+            ),
+            ModuleUpgradeWarningKind::Removed if self.set_in_claim => write!(
+                f,
+                "Variable {} is removed in {}, remove it from the claim",
+                self.variable, self.to_version
+            ),
+            ModuleUpgradeWarningKind::Removed => write!(
+                f,
+                "Variable {} is removed in {}",
+                self.variable, self.to_version
+            ),
+            ModuleUpgradeWarningKind::BecameRequired => write!(
+                f,
+                "Variable {} is required in {}, set it in the claim",
+                self.variable, self.to_version
+            ),
+        }
+    }
+}
+
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ModuleResp {
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 pub fn get_policy_identifier(policy: &str, environment: &str) -> String {
     format!("{}::{}", environment, policy)
@@ -73,7 +74,60 @@ pub struct PolicySpec {
     pub version: String,
     pub description: String,
     pub reference: String,
+    #[serde(default = "default_policy_data")]
     pub data: serde_json::Value,
+    /// Modules and stacks the policy applies to, `*` matches any characters. Applies to all
+    /// modules if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<String>,
+    /// Labels a claim must have for the policy to apply to it. Applies to all claims if empty
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Exceptions to the `deny` rules of the policy for specific deployments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waivers: Vec<PolicyWaiver>,
+}
+
+/// Allows one finding of the `deny` rules of a policy package for a deployment until it expires
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyWaiver {
+    /// Package of the policy below `infraweave`, such as `terraform_plan`
+    pub rule: String,
+    /// The finding that is allowed, the message of a `deny` rule or the `id` of a structured
+    /// result such as `{"id": "region", "msg": ...}`, see `get_finding_id`
+    #[serde(default)]
+    pub finding: String,
+    pub deployment_id: String,
+    /// RFC 3339 timestamp when the waiver stops applying, such as `2026-12-31T00:00:00Z`
+    pub expires_at: String,
+    pub justification: String,
+    /// Who signed off on the waiver. It is only checked to be someone else than the publisher of
+    /// the policy, it is not an enforced sign-off
+    pub approved_by: String,
+}
+
+/// Identifier of a `deny` finding that waivers refer to: the message of the finding, or its `id`
+/// if it is a structured result
+pub fn get_finding_id(violation: &serde_json::Value) -> Option<&str> {
+    match violation {
+        serde_json::Value::String(message) => Some(message),
+        _ => violation.get("id").and_then(|id| id.as_str()),
+    }
+}
+
+impl PolicyWaiver {
+    /// Whether the waiver allows the finding `violation` of `package`
+    pub fn applies_to(&self, package: &str, violation: &serde_json::Value) -> bool {
+        self.rule == package
+            && !self.finding.is_empty()
+            && get_finding_id(violation) == Some(self.finding.as_str())
+    }
+}
+
+fn default_policy_data() -> serde_json::Value {
+    serde_json::json!({})
 }
 
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
@@ -92,6 +146,126 @@ pub struct PolicyResult {
     pub environment: String,
     pub description: String,
     pub policy_name: String,
+    /// Whether any `deny` rule has violations that are not waived
     pub failed: bool,
+    /// Violations of the `deny` rules per package, without the waived ones
     pub violations: serde_json::Value,
+    /// Violations of the `warn` rules per package, they do not fail the job
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub warnings: BTreeMap<String, Vec<serde_json::Value>>,
+    /// Violations of the `info` rules per package
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub info: BTreeMap<String, Vec<serde_json::Value>>,
+    /// Violations of the `deny` rules per package that are allowed by `waivers`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub waived: BTreeMap<String, Vec<serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waivers: Vec<PolicyWaiver>,
+}
+
+fn format_violation(package: &str, violation: &serde_json::Value) -> String {
+    match violation.as_str() {
+        Some(violation) => format!("{}: {}", package, violation),
+        None => format!("{}: {}", package, violation),
+    }
+}
+
+fn format_violations(violations: &BTreeMap<String, Vec<serde_json::Value>>) -> Vec<String> {
+    violations
+        .iter()
+        .flat_map(|(package, violations)| violations.iter().map(|v| format_violation(package, v)))
+        .collect()
+}
+
+impl PolicyResult {
+    /// Violations of the `deny` rules as `<package>: <violation>`
+    pub fn get_violations(&self) -> Vec<String> {
+        format_violations(&serde_json::from_value(self.violations.clone()).unwrap_or_default())
+    }
+
+    /// Violations of the `warn` rules as `<package>: <violation>`
+    pub fn get_warnings(&self) -> Vec<String> {
+        format_violations(&self.warnings)
+    }
+
+    /// Waived violations of the `deny` rules as `<package>: <violation>` with their waiver
+    pub fn get_waived_violations(&self) -> Vec<String> {
+        let mut lines = vec![];
+        for (package, violations) in &self.waived {
+            for violation in violations {
+                let waiver = self
+                    .waivers
+                    .iter()
+                    .find(|w| w.applies_to(package, violation));
+                let violation = format_violation(package, violation);
+                lines.push(match waiver {
+                    Some(waiver) => format!(
+                        "{} (waived until {} by {}: {})",
+                        violation, waiver.expires_at, waiver.approved_by, waiver.justification
+                    ),
+                    None => violation,
+                });
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_policy_result_violations() {
+        let result: PolicyResult = serde_json::from_value(json!({
+            "policy": "allowed-regions",
+            "environment": "cli/prod",
+            "description": "",
+            "policy_name": "Allowed regions",
+            "failed": true,
+            "violations": {"regions": ["Invalid region: 'us-east-1'"]},
+            "warnings": {"tags": ["Missing tag owner", {"tag": "cost-center"}]},
+            "waived": {"budget": ["Over budget"]},
+            "waivers": [{
+                "rule": "budget",
+                "finding": "Over budget",
+                "deploymentId": "s3bucket/logs",
+                "expiresAt": "2026-12-31T00:00:00Z",
+                "justification": "Migration, CHG-1234",
+                "approvedBy": "security"
+            }]
+        }))
+        .unwrap();
+        assert_eq!(
+            result.get_violations(),
+            vec!["regions: Invalid region: 'us-east-1'"]
+        );
+        assert_eq!(
+            result.get_warnings(),
+            vec!["tags: Missing tag owner", "tags: {\"tag\":\"cost-center\"}"]
+        );
+        assert_eq!(
+            result.get_waived_violations(),
+            vec!["budget: Over budget (waived until 2026-12-31T00:00:00Z by security: Migration, CHG-1234)"]
+        );
+        assert!(result.info.is_empty());
+    }
+
+    #[test]
+    fn test_policy_waiver_applies_to() {
+        let waiver = |finding: &str| PolicyWaiver {
+            rule: "regions".to_string(),
+            finding: finding.to_string(),
+            deployment_id: "s3bucket/logs".to_string(),
+            expires_at: "2026-12-31T00:00:00Z".to_string(),
+            justification: "Migration, CHG-1234".to_string(),
+            approved_by: "security".to_string(),
+        };
+        assert!(waiver("Invalid region").applies_to("regions", &json!("Invalid region")));
+        assert!(!waiver("Invalid region").applies_to("budget", &json!("Invalid region")));
+        assert!(!waiver("Invalid region").applies_to("regions", &json!("Missing tag")));
+        assert!(waiver("region").applies_to("regions", &json!({"id": "region", "msg": "Invalid"})));
+        assert!(!waiver("").applies_to("regions", &json!("")));
+    }
 }
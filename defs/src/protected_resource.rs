@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Claim annotation allowing a job to delete or replace protected resources, its value is the
+/// reason for the override which is recorded on the change record
+pub const PROTECTED_RESOURCES_OVERRIDE_ANNOTATION: &str =
+    "infraweave.io/override-protected-resources";
+
+/// Protected resources a job deleted or replaced because the claim overrode the protection
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ProtectedResourceOverride {
+    pub reason: String,
+    pub resources: Vec<String>,
+}
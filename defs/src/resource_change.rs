@@ -73,6 +73,9 @@ pub struct SanitizedResourceChange {
     /// after_unknown indicates if the after value is "known after apply"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub changes: Option<serde_json::Map<String, Value>>,
+    /// Cloud id the resource is imported from, if the change adopts an existing resource
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import_id: Option<String>,
 }
 
 impl SanitizedResourceChange {
@@ -118,6 +121,12 @@ impl SanitizedResourceChange {
         // Extract index if present (for count/for_each resources)
         let index = resource.get("index").cloned();
 
+        let import_id = change
+            .get("importing")
+            .and_then(|i| i.get("id"))
+            .and_then(|id| id.as_str())
+            .map(|s| s.to_string());
+
         // Extract dependency changes
         let depends_on = Self::compute_dependency_change(
             change.get("before_depends_on"),
@@ -167,6 +176,7 @@ impl SanitizedResourceChange {
             before,
             after,
             changes,
+            import_id,
         })
     }
 
@@ -599,8 +609,26 @@ pub fn pretty_print_resource_changes(changes: &[SanitizedResourceChange]) -> Str
     if !no_ops.is_empty() {
         output.push_str(&format!("  = No-op: {}\n", no_ops.len()));
     }
+    let imports: Vec<&SanitizedResourceChange> =
+        changes.iter().filter(|c| c.import_id.is_some()).collect();
+    if !imports.is_empty() {
+        output.push_str(&format!("  <= Import: {}\n", imports.len()));
+    }
     output.push('\n');
 
+    if !imports.is_empty() {
+        output.push_str("Resources to import:\n");
+        for change in imports {
+            output.push_str(&format!(
+                "  <= {} ({}) from {}\n",
+                change.address,
+                change.resource_type,
+                change.import_id.as_deref().unwrap_or_default()
+            ));
+        }
+        output.push('\n');
+    }
+
     // Print detailed changes
     if !creates.is_empty() {
         output.push_str("Resources to create:\n");
@@ -959,6 +987,33 @@ mod tests {
         assert!(sanitized[0].after.is_none());
     }
 
+    #[test]
+    fn test_sanitize_imported_resource() {
+        let resource_changes = json!([{
+            "address": "module.s3bucket.aws_s3_bucket.bucket",
+            "type": "aws_s3_bucket",
+            "name": "bucket",
+            "mode": "managed",
+            "change": {
+                "actions": ["no-op"],
+                "before": {"bucket": "my-bucket"},
+                "after": {"bucket": "my-bucket"},
+                "importing": {"id": "my-bucket"}
+            }
+        }]);
+
+        let sanitized = sanitize_resource_changes(&resource_changes);
+        assert_eq!(sanitized.len(), 1);
+        assert_eq!(sanitized[0].action, ResourceAction::NoOp);
+        assert_eq!(sanitized[0].import_id, Some("my-bucket".to_string()));
+
+        let output = pretty_print_resource_changes(&sanitized);
+        assert!(output.contains("  <= Import: 1\n"));
+        assert!(output.contains(
+            "  <= module.s3bucket.aws_s3_bucket.bucket (aws_s3_bucket) from my-bucket\n"
+        ));
+    }
+
     #[test]
     fn test_enum_serialization() {
         // Test serialization in struct context
@@ -975,6 +1030,7 @@ mod tests {
             before: None,
             after: Some(serde_json::json!({"bucket": "test"})),
             changes: None,
+            import_id: None,
         };
 
         let json = serde_json::to_value(&change).unwrap();
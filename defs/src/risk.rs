@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskLevel::Low => write!(f, "low"),
+            RiskLevel::Medium => write!(f, "medium"),
+            RiskLevel::High => write!(f, "high"),
+            RiskLevel::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskFindingKind {
+    /// A resource holding data, such as a database or bucket, is replaced
+    StatefulReplace,
+    /// A resource holding data, such as a database or bucket, is deleted
+    StatefulDelete,
+    /// A role, policy or permission changes
+    IamChange,
+    /// A network, subnet, route, firewall or load balancer changes
+    NetworkChange,
+    /// Attributes marked as sensitive by the provider change
+    SensitiveChange,
+}
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RiskFinding {
+    pub kind: RiskFindingKind,
+    pub address: String,
+    pub description: String,
+    /// What the finding adds to the score of the assessment
+    pub score: u32,
+}
+
+/// How risky the changes of a plan are, one signal for policies, approvals and pull requests
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RiskAssessment {
+    /// 0 (nothing changes) to 100
+    pub score: u32,
+    pub level: RiskLevel,
+    pub creates: u32,
+    pub updates: u32,
+    pub replaces: u32,
+    pub deletes: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<RiskFinding>,
+}
+
+impl std::fmt::Display for RiskAssessment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (score {}): {} to create, {} to update, {} to replace, {} to delete",
+            self.level, self.score, self.creates, self.updates, self.replaces, self.deletes
+        )
+    }
+}
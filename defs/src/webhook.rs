@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// Version of [`WebhookPayload`], increased when a field is removed or changes meaning
+pub const WEBHOOK_PAYLOAD_SCHEMA_VERSION: u32 = 1;
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventType {
+    /// A drift check found resources that differ from the state
+    Drift,
+    /// A job failed
+    Failure,
+    /// A job finished successfully
+    Success,
+}
+
+impl std::fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookEventType::Drift => write!(f, "drift"),
+            WebhookEventType::Failure => write!(f, "failure"),
+            WebhookEventType::Success => write!(f, "success"),
+        }
+    }
+}
+
+/// Body posted to webhooks.
+///
+/// When a signing secret is configured the request carries `X-InfraWeave-Timestamp` (unix
+/// seconds) and `X-InfraWeave-Signature: sha256=<hex>`, the HMAC-SHA256 of
+/// `<timestamp>.<body>` with the secret as key.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct WebhookPayload {
+    pub schema_version: u32,
+    /// Unique per job and event, the same for every retry of a delivery
+    pub id: String,
+    pub event: WebhookEventType,
+    pub timestamp: String,
+    pub project_id: String,
+    pub region: String,
+    pub environment: String,
+    pub deployment_id: String,
+    pub module: String,
+    pub module_version: String,
+    pub job_id: String,
+    pub command: String,
+    /// Human readable summary, also lets Slack and Teams incoming webhooks show the message
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_text: Option<String>,
+    /// Addresses of the drifted resources for `drift` events
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drifted_resources: Vec<String>,
+}
@@ -3,7 +3,8 @@ use aws_sdk_lambda::types::InvocationType;
 use aws_sdk_sts::types::Credentials;
 use env_defs::{
     get_change_record_identifier, get_deployment_identifier, get_event_identifier,
-    get_module_identifier, get_policy_identifier, CloudHandlerError, GenericFunctionResponse,
+    get_module_identifier, get_policy_identifier, get_rollout_identifier, CloudHandlerError,
+    GenericFunctionResponse,
 };
 use env_utils::{get_epoch, sanitize_payload_for_logging, zero_pad_semver};
 use log::{error, info};
@@ -332,6 +333,15 @@ pub fn get_job_status_query(job_id: &str) -> Value {
     })
 }
 
+pub fn get_cancel_job_query(job_id: &str) -> Value {
+    json!({
+        "event": "cancel_job",
+        "data": {
+            "job_id": job_id
+        }
+    })
+}
+
 pub fn get_environment_variables_query() -> Value {
     json!({
         "event": "get_environment_variables"
@@ -578,6 +588,18 @@ pub fn get_change_records_query(
     })
 }
 
+// Rollout
+
+pub fn get_rollout_query(project_id: &str, region: &str, module: &str) -> Value {
+    json!({
+        "KeyConditionExpression": "PK = :pk AND SK = :sk",
+        "ExpressionAttributeValues": {
+            ":pk": format!("ROLLOUT#{}", get_rollout_identifier(project_id, region, module)),
+            ":sk": "ROLLOUT"
+        }
+    })
+}
+
 // Policy
 
 pub fn get_newest_policy_version_query(policy: &str, environment: &str) -> Value {
@@ -592,7 +614,7 @@ pub fn get_newest_policy_version_query(policy: &str, environment: &str) -> Value
 pub fn get_all_policies_query(environment: &str) -> Value {
     json!({
         "KeyConditionExpression": "PK = :current AND begins_with(SK, :policy_prefix)",
-        "ExpressionAttributeValues": {":current": "CURRENT", ":policy_prefix": format!("POLICY#{}", environment)},
+        "ExpressionAttributeValues": {":current": "CURRENT", ":policy_prefix": format!("POLICY#{}::", environment)},
     })
 }
 
@@ -2,6 +2,7 @@ mod api;
 mod backend;
 mod job_id;
 mod provider;
+mod secret;
 mod utils;
 
 pub use api::{
@@ -17,6 +18,7 @@ pub use api::{
     get_all_projects_query,
     get_all_regions_query,
     get_all_stack_versions_query,
+    get_cancel_job_query,
     get_change_records_query,
     get_current_project_query,
     get_dependents_query,
@@ -37,6 +39,7 @@ pub use api::{
     get_policy_query,
     get_project_id,
     get_project_map_query,
+    get_rollout_query,
     get_stack_version_query,
     get_user_id,
     read_db,
@@ -45,4 +48,5 @@ pub use api::{
 pub use backend::set_backend;
 pub use job_id::get_current_job_id;
 pub use provider::AwsCloudProvider;
+pub use secret::get_secret_value;
 pub use utils::get_region;
@@ -177,6 +177,19 @@ impl CloudProvider for AwsCloudProvider {
             Err(e) => Err(e.into()),
         }
     }
+    async fn cancel_job(&self, job_id: &str) -> Result<(), anyhow::Error> {
+        match crate::run_function(
+            &self.function_endpoint,
+            &crate::get_cancel_job_query(job_id),
+            &self.project_id,
+            &self.region,
+        )
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
     async fn get_latest_provider_version(
         &self,
         provider: &str,
@@ -405,6 +418,15 @@ impl CloudProvider for AwsCloudProvider {
         )
         .await
     }
+    // Rollout
+    async fn get_rollout(&self, module: &str) -> Result<Option<Value>, anyhow::Error> {
+        self.read_db_generic(
+            "deployments",
+            &crate::get_rollout_query(&self.project_id, &self.region, module),
+        )
+        .await
+        .map(|mut items| items.pop())
+    }
     // Policy
     async fn get_newest_policy_version(
         &self,
@@ -509,4 +531,64 @@ impl CloudProvider for AwsCloudProvider {
 
         Ok(())
     }
+
+    async fn move_state_file(
+        &self,
+        environment: &str,
+        deployment_id: &str,
+        new_environment: &str,
+        new_deployment_id: &str,
+    ) -> Result<(), anyhow::Error> {
+        let backend_args = self
+            .get_backend_provider_arguments(environment, deployment_id)
+            .await;
+        let new_backend_args = self
+            .get_backend_provider_arguments(new_environment, new_deployment_id)
+            .await;
+
+        let bucket = backend_args
+            .get("bucket")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("bucket not found in backend args"))?;
+        let key = backend_args
+            .get("key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("key not found in backend args"))?;
+        let new_key = new_backend_args
+            .get("key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("key not found in backend args"))?;
+        let region = backend_args
+            .get("region")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&self.region);
+
+        let config = aws_config::from_env()
+            .region(aws_config::Region::new(region.to_string()))
+            .load()
+            .await;
+        let client = aws_sdk_s3::Client::new(&config);
+
+        let resp = client.get_object().bucket(bucket).key(key).send().await?;
+        let data = resp.body.collect().await?.into_bytes();
+        client
+            .put_object()
+            .bucket(bucket)
+            .key(new_key)
+            .body(aws_sdk_s3::primitives::ByteStream::from(data))
+            .send()
+            .await?;
+        client
+            .delete_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_secret_value(&self, name: &str) -> Result<String, anyhow::Error> {
+        crate::get_secret_value(name, &self.region).await
+    }
 }
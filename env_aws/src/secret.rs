@@ -0,0 +1,21 @@
+/// Reads a secret from SSM Parameter Store, `name` is the name (or path) of the parameter
+pub async fn get_secret_value(name: &str, region: &str) -> Result<String, anyhow::Error> {
+    let config = aws_config::from_env()
+        .region(aws_config::Region::new(region.to_string()))
+        .load()
+        .await;
+    let client = aws_sdk_ssm::Client::new(&config);
+
+    let resp = client
+        .get_parameter()
+        .name(name)
+        .with_decryption(true)
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get parameter {}: {}", name, e))?;
+
+    match resp.parameter.and_then(|parameter| parameter.value) {
+        Some(value) => Ok(value),
+        None => Err(anyhow::anyhow!("Parameter {} has no value", name)),
+    }
+}
@@ -5,7 +5,7 @@ use azure_core::auth::TokenCredential;
 use azure_identity::{DefaultAzureCredential, TokenCredentialOptions};
 use env_defs::{
     get_change_record_identifier, get_deployment_identifier, get_event_identifier,
-    get_module_identifier, get_policy_identifier, GenericFunctionResponse,
+    get_module_identifier, get_policy_identifier, get_rollout_identifier, GenericFunctionResponse,
 };
 use env_utils::{get_epoch, sanitize_payload_for_logging, zero_pad_semver};
 use log::{error, info};
@@ -200,6 +200,15 @@ pub fn get_job_status_query(job_id: &str) -> Value {
     })
 }
 
+pub fn get_cancel_job_query(job_id: &str) -> Value {
+    json!({
+        "event": "cancel_job",
+        "data": {
+            "job_id": job_id
+        }
+    })
+}
+
 pub fn get_environment_variables_query() -> Value {
     json!({
         "event": "get_environment_variables"
@@ -594,6 +603,24 @@ pub fn get_change_records_query(
     })
 }
 
+// Rollout
+
+pub fn get_rollout_query(project_id: &str, region: &str, module: &str) -> Value {
+    json!({
+        "query": "SELECT * FROM c WHERE c.PK = @pk AND c.SK = @sk",
+        "parameters": [
+            {
+                "name": "@pk",
+                "value": format!("ROLLOUT#{}", get_rollout_identifier(project_id, region, module))
+            },
+            {
+                "name": "@sk",
+                "value": "ROLLOUT"
+            }
+        ]
+    })
+}
+
 // Policy
 
 pub fn get_newest_policy_version_query(policy: &str, environment: &str) -> Value {
@@ -613,7 +640,7 @@ pub fn get_all_policies_query(environment: &str) -> Value {
         "query": "SELECT * FROM c WHERE c.PK = @current AND STARTSWITH(c.SK, @policy_prefix)",
         "parameters": [
             { "name": "@current", "value": "CURRENT" },
-            { "name": "@policy_prefix", "value": format!("POLICY#{}", environment) }
+            { "name": "@policy_prefix", "value": format!("POLICY#{}::", environment) }
         ]
     })
 }
@@ -146,6 +146,19 @@ impl CloudProvider for AzureCloudProvider {
             Err(e) => Err(e),
         }
     }
+    async fn cancel_job(&self, job_id: &str) -> Result<(), anyhow::Error> {
+        match crate::run_function(
+            &self.function_endpoint,
+            &crate::get_cancel_job_query(job_id),
+            &self.project_id,
+            &self.region,
+        )
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
     async fn get_latest_provider_version(
         &self,
         provider: &str,
@@ -374,6 +387,15 @@ impl CloudProvider for AzureCloudProvider {
         )
         .await
     }
+    // Rollout
+    async fn get_rollout(&self, module: &str) -> Result<Option<Value>, anyhow::Error> {
+        self.read_db_generic(
+            "deployments",
+            &crate::get_rollout_query(&self.project_id, &self.region, module),
+        )
+        .await
+        .map(|mut items| items.pop())
+    }
     // Policy
     async fn get_newest_policy_version(
         &self,
@@ -483,4 +505,69 @@ impl CloudProvider for AzureCloudProvider {
 
         Ok(())
     }
+
+    async fn move_state_file(
+        &self,
+        environment: &str,
+        deployment_id: &str,
+        new_environment: &str,
+        new_deployment_id: &str,
+    ) -> Result<(), anyhow::Error> {
+        let backend_args = self
+            .get_backend_provider_arguments(environment, deployment_id)
+            .await;
+        let new_backend_args = self
+            .get_backend_provider_arguments(new_environment, new_deployment_id)
+            .await;
+
+        let key = backend_args
+            .get("key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("key not found in backend args"))?;
+        let new_key = new_backend_args
+            .get("key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("key not found in backend args"))?;
+
+        let container = backend_args
+            .get("container_name")
+            .or_else(|| backend_args.get("container"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("container not found in backend args"))?;
+
+        let storage_account = backend_args
+            .get("storage_account_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("storage_account_name not found in backend args"))?;
+
+        let credential: std::sync::Arc<dyn azure_core::auth::TokenCredential + 'static> =
+            std::sync::Arc::new(azure_identity::DefaultAzureCredential::create(
+                azure_identity::TokenCredentialOptions::default(),
+            )?);
+
+        let container_client =
+            azure_storage_blobs::prelude::BlobServiceClient::new(storage_account, credential)
+                .container_client(container);
+        let blob_client = container_client.blob_client(key);
+
+        let data = blob_client
+            .get_content()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to download state file from Azure: {}", e))?;
+        container_client
+            .blob_client(new_key)
+            .put_block_blob(data)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to upload state file to Azure: {}", e))?;
+        blob_client
+            .delete()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to delete state file from Azure: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn get_secret_value(&self, name: &str) -> Result<String, anyhow::Error> {
+        crate::get_secret_value(name).await
+    }
 }
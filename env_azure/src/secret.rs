@@ -0,0 +1,47 @@
+use azure_core::auth::TokenCredential;
+use azure_identity::{DefaultAzureCredential, TokenCredentialOptions};
+use serde_json::Value;
+
+use crate::custom::CustomImdsCredential;
+
+const KEY_VAULT_SCOPE: &str = "https://vault.azure.net/.default";
+const KEY_VAULT_API_VERSION: &str = "7.4";
+
+/// Reads a secret from the Key Vault named by `KEY_VAULT_NAME`
+pub async fn get_secret_value(name: &str) -> Result<String, anyhow::Error> {
+    let vault = std::env::var("KEY_VAULT_NAME")
+        .map_err(|_| anyhow::anyhow!("KEY_VAULT_NAME is not set, cannot read secret {}", name))?;
+
+    let token = if std::env::var("AZURE_CONTAINER_INSTANCE").is_ok() {
+        CustomImdsCredential::new()
+            .get_token(&[KEY_VAULT_SCOPE])
+            .await?
+    } else {
+        DefaultAzureCredential::create(TokenCredentialOptions::default())?
+            .get_token(&[KEY_VAULT_SCOPE])
+            .await?
+    };
+
+    let url = format!(
+        "https://{}.vault.azure.net/secrets/{}?api-version={}",
+        vault, name, KEY_VAULT_API_VERSION
+    );
+    let response = reqwest::Client::new()
+        .get(&url)
+        .bearer_auth(token.token.secret())
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to get secret {} from Key Vault {}: {}",
+            name,
+            vault,
+            response.status()
+        ));
+    }
+    let body: Value = response.json().await?;
+    match body.get("value").and_then(|v| v.as_str()) {
+        Some(value) => Ok(value.to_string()),
+        None => Err(anyhow::anyhow!("Secret {} has no value", name)),
+    }
+}
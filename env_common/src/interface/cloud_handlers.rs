@@ -9,6 +9,7 @@ use env_defs::{
     GenericFunctionResponse, InfraChangeRecord, JobStatus, LogData, ModuleResp, NotificationData,
     PolicyResp, ProjectData, ProviderResp,
 };
+use env_local::LocalCloudProvider;
 use serde_json::Value;
 
 use crate::logic::{
@@ -96,6 +97,27 @@ impl GenericCloudHandler {
                     function_endpoint,
                 })
             }
+            "local" => {
+                let project_id = match project_id {
+                    Some(p) => p,
+                    None => match env_local::get_project_id().await {
+                        Ok(p) => p,
+                        Err(e) => {
+                            eprintln!("Error initializing: {:?}", e);
+                            exit(1);
+                        }
+                    },
+                };
+                let region = match region {
+                    Some(r) => r,
+                    None => env_local::get_region().await,
+                };
+                Arc::new(LocalCloudProvider {
+                    project_id: project_id.to_string(),
+                    region: region.to_string(),
+                    function_endpoint,
+                })
+            }
             "none" => Arc::new(super::NoCloudProvider::default()),
             _ => panic!("Unsupported provider: {}", provider_name()),
         };
@@ -162,7 +184,9 @@ impl CloudProviderCommon for GenericCloudHandler {
         manifest_path: &str,
         environment: &str,
     ) -> Result<(), anyhow::Error> {
-        publish_policy(self, manifest_path, environment).await
+        publish_policy(self, manifest_path, environment, None, None)
+            .await
+            .map(|_| ())
     }
 }
 
@@ -331,6 +355,9 @@ impl CloudProvider for GenericCloudHandler {
     async fn get_job_status(&self, job_id: &str) -> Result<Option<JobStatus>, anyhow::Error> {
         self.provider.get_job_status(job_id).await
     }
+    async fn cancel_job(&self, job_id: &str) -> Result<(), anyhow::Error> {
+        self.provider.cancel_job(job_id).await
+    }
     async fn get_deployments_using_module(
         &self,
         module: &str,
@@ -398,6 +425,10 @@ impl CloudProvider for GenericCloudHandler {
             .get_change_record(environment, deployment_id, job_id, change_type)
             .await
     }
+    // Rollout
+    async fn get_rollout(&self, module: &str) -> Result<Option<Value>, anyhow::Error> {
+        self.provider.get_rollout(module).await
+    }
     // Policy
     async fn get_newest_policy_version(
         &self,
@@ -435,6 +466,20 @@ impl CloudProvider for GenericCloudHandler {
             .download_state_file(environment, deployment_id, output)
             .await
     }
+    async fn move_state_file(
+        &self,
+        environment: &str,
+        deployment_id: &str,
+        new_environment: &str,
+        new_deployment_id: &str,
+    ) -> Result<(), anyhow::Error> {
+        self.provider
+            .move_state_file(environment, deployment_id, new_environment, new_deployment_id)
+            .await
+    }
+    async fn get_secret_value(&self, name: &str) -> Result<String, anyhow::Error> {
+        self.provider.get_secret_value(name).await
+    }
 }
 
 impl GenericCloudHandler {
@@ -455,21 +500,36 @@ impl GenericCloudHandler {
             .generate_presigned_url(&change_record.plan_raw_json_key, "change_records")
             .await?;
 
-        let client = reqwest::Client::new();
-        let json_content = client
-            .get(&presigned_url)
-            .send()
+        let json_content = env_utils::download_zip_to_vec(&presigned_url)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to download Terraform JSON output: {}", e))?
-            .text()
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to read Terraform JSON response: {}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to download Terraform JSON output: {}", e))?;
 
-        let terraform_json: Value = serde_json::from_str(&json_content)
+        let terraform_json: Value = serde_json::from_slice(&json_content)
             .map_err(|e| anyhow::anyhow!("Failed to parse Terraform JSON output: {}", e))?;
 
         Ok(terraform_json)
     }
+
+    /// Downloads the binary plan file stored with a plan change record
+    pub async fn get_plan_file(
+        &self,
+        change_record: &InfraChangeRecord,
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let plan_file_key = change_record.plan_file_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No plan file is stored for job {}",
+                change_record.job_id
+            )
+        })?;
+
+        let presigned_url = self
+            .generate_presigned_url(plan_file_key, "change_records")
+            .await?;
+
+        env_utils::download_zip_to_vec(&presigned_url)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to download plan file: {}", e))
+    }
 }
 
 pub async fn initialize_project_id_and_region() -> String {
@@ -505,7 +565,10 @@ pub async fn initialize_project_id_and_region() -> String {
 }
 
 pub async fn get_current_identity() -> String {
-    let current_identity = env_aws::get_user_id().await.unwrap();
+    let current_identity = match provider_name().as_str() {
+        "local" => env_local::get_user_id().await.unwrap(),
+        _ => env_aws::get_user_id().await.unwrap(),
+    };
     eprintln!("Current identity: {}", &current_identity);
     current_identity
 }
@@ -1,8 +1,11 @@
-use env_defs::{Dependency, DeploymentResp, DriftDetection, EventData, PolicyResult};
-use env_utils::{get_epoch, get_timestamp};
-use humantime::parse_duration;
+use env_defs::{
+    CascadeTrigger, Dependency, DeploymentResp, DeploymentStatus, DriftDetection, EventData,
+    PolicyResult,
+};
+use env_utils::{get_epoch, get_next_drift_check_epoch, get_timestamp};
 use log::{debug, error, info};
-use serde_json::Value;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 
 use crate::logic::{insert_event, set_deployment};
 
@@ -14,7 +17,7 @@ pub struct DeploymentStatusHandler<'a> {
     module_version: &'a str,
     module_type: &'a str,
     module_track: &'a str,
-    status: String,
+    status: DeploymentStatus,
     environment: &'a str,
     deployment_id: &'a str,
     project_id: &'a str,
@@ -38,6 +41,9 @@ pub struct DeploymentStatusHandler<'a> {
     memory: String,
     reference: String,
     tf_resources: Option<Vec<String>>,
+    expires_at_epoch: Option<u128>,
+    labels: BTreeMap<String, String>,
+    metadata: Value,
 }
 
 impl<'a> DeploymentStatusHandler<'a> {
@@ -50,7 +56,7 @@ impl<'a> DeploymentStatusHandler<'a> {
         module_version: &'a str,
         module_type: &'a str,
         module_track: &'a str,
-        status: String,
+        status: DeploymentStatus,
         environment: &'a str,
         deployment_id: &'a str,
         project_id: &'a str,
@@ -99,11 +105,27 @@ impl<'a> DeploymentStatusHandler<'a> {
             memory,
             reference,
             tf_resources: None,
+            expires_at_epoch: None,
+            labels: BTreeMap::new(),
+            metadata: Value::Null,
         }
     }
 
-    pub fn set_status(&mut self, status: String) {
+    /// Fails without changing the status if it is not a legal transition from the current status
+    pub fn set_status(&mut self, status: DeploymentStatus) -> Result<(), anyhow::Error> {
+        if !self.status.can_transition_to(&status) {
+            error!(
+                "Illegal deployment status transition for {}: {} -> {}",
+                self.deployment_id, self.status, status
+            );
+            return Err(anyhow::anyhow!(
+                "Illegal deployment status transition: {} -> {}",
+                self.status,
+                status
+            ));
+        }
         self.status = status;
+        Ok(())
     }
 
     pub fn set_command(&mut self, command: &'a str) {
@@ -153,17 +175,42 @@ impl<'a> DeploymentStatusHandler<'a> {
         self.tf_resources = tf_resources
     }
 
+    pub fn set_expires_at_epoch(&mut self, expires_at_epoch: Option<u128>) {
+        self.expires_at_epoch = expires_at_epoch;
+    }
+
+    pub fn set_labels(&mut self, labels: BTreeMap<String, String>) {
+        self.labels = labels;
+    }
+
+    /// Links all following events to the job that triggered this job
+    pub fn set_triggered_by(&mut self, triggered_by: &CascadeTrigger) {
+        self.set_metadata_field("triggered_by", json!(triggered_by));
+    }
+
+    /// Sets a field in the metadata of the following events
+    pub fn set_metadata_field(&mut self, field: &str, value: Value) {
+        if !self.metadata.is_object() {
+            self.metadata = json!({});
+        }
+        self.metadata[field] = value;
+    }
+
     pub fn get_variables(&self) -> Value {
         self.variables.clone()
     }
 
+    pub fn get_output(&self) -> Value {
+        self.output.clone()
+    }
+
     pub async fn send_event(&self, handler: &GenericCloudHandler) {
         let epoch = get_epoch();
         let event = EventData {
             environment: self.environment.to_string(),
             event: self.command.to_string(),
             epoch,
-            status: self.status.to_string(),
+            status: self.status.clone(),
             module: self.module.to_string(),
             drift_detection: self.drift_detection.clone(),
             next_drift_check_epoch: self.next_drift_check_epoch,
@@ -177,7 +224,7 @@ impl<'a> DeploymentStatusHandler<'a> {
                 self.module, self.deployment_id, epoch, self.command, self.status
             ),
             job_id: self.job_id.to_string(),
-            metadata: serde_json::Value::Null,
+            metadata: self.metadata.clone(),
             name: self.name.to_string(),
             output: self.output.clone(),
             policy_results: self.policy_results.clone(),
@@ -208,26 +255,18 @@ impl<'a> DeploymentStatusHandler<'a> {
             debug!("Destroy command, not scheduling next drift detection");
             return -1;
         }
-        match parse_duration(&self.drift_detection.interval) {
-            Ok(dur) => {
+        let seed = format!("{}/{}", self.environment, self.deployment_id);
+        match get_next_drift_check_epoch(&self.drift_detection, &seed, get_epoch()) {
+            Ok(next_epoch) => {
                 info!("Final step, deployment either succeeded or failed, scheduling next drift detection");
                 debug!(
-                    "{} -> {} milliseconds",
-                    &self.drift_detection.interval,
-                    dur.as_millis()
-                );
-                let epoch: i128 = get_epoch().try_into().unwrap();
-                let wait_duration: i128 = dur.as_millis() as i128;
-                debug!(
-                    "Current epoch: {} + {} = {}",
-                    epoch,
-                    wait_duration,
-                    epoch + wait_duration
+                    "{} -> next drift detection at {}",
+                    &self.drift_detection.interval, next_epoch
                 );
-                epoch + wait_duration
+                next_epoch
             }
             Err(e) => {
-                error!("Error parsing {}: {}", &self.drift_detection.interval, e);
+                error!("Error scheduling drift detection: {}", e);
                 -1
             }
         }
@@ -242,7 +281,7 @@ impl<'a> DeploymentStatusHandler<'a> {
             deployment_id: self.deployment_id.to_string(),
             project_id: self.project_id.to_string(),
             region: self.region.to_string(),
-            status: self.status.to_string(),
+            status: self.status.clone(),
             job_id: self.job_id.to_string(),
             environment: self.environment.to_string(),
             module: self.module.to_string(),
@@ -263,6 +302,8 @@ impl<'a> DeploymentStatusHandler<'a> {
             memory: self.memory.to_string(),
             reference: self.reference.to_string(),
             tf_resources: self.tf_resources.clone(),
+            expires_at_epoch: self.expires_at_epoch,
+            labels: self.labels.clone(),
         };
 
         match set_deployment(handler, &deployment, self.is_plan()).await {
@@ -296,6 +337,9 @@ impl<'a> DeploymentStatusHandler<'a> {
     }
 
     fn is_final_update(&self) -> bool {
-        ["successful", "failed"].contains(&self.status.as_str())
+        matches!(
+            self.status,
+            DeploymentStatus::Successful | DeploymentStatus::Failed | DeploymentStatus::Cancelled
+        )
     }
 }
@@ -152,6 +152,10 @@ impl CloudProvider for NoCloudProvider {
         Ok(None)
     }
 
+    async fn cancel_job(&self, _job_id: &str) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
     async fn get_latest_provider_version(
         &self,
         _provider: &str,
@@ -303,6 +307,10 @@ impl CloudProvider for NoCloudProvider {
         Err(anyhow::anyhow!("no change record"))
     }
 
+    async fn get_rollout(&self, _module: &str) -> Result<Option<Value>, anyhow::Error> {
+        Ok(None)
+    }
+
     async fn get_newest_policy_version(
         &self,
         _policy: &str,
@@ -340,4 +348,18 @@ impl CloudProvider for NoCloudProvider {
     ) -> Result<(), anyhow::Error> {
         Err(anyhow::anyhow!("not supported"))
     }
+
+    async fn move_state_file(
+        &self,
+        _environment: &str,
+        _deployment_id: &str,
+        _new_environment: &str,
+        _new_deployment_id: &str,
+    ) -> Result<(), anyhow::Error> {
+        Err(anyhow::anyhow!("not supported"))
+    }
+
+    async fn get_secret_value(&self, _name: &str) -> Result<String, anyhow::Error> {
+        Err(anyhow::anyhow!("not supported"))
+    }
 }
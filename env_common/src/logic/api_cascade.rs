@@ -0,0 +1,276 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use env_defs::{get_deployment_identifier, CascadeTrigger, CloudProvider, Dependent};
+use log::{error, info, warn};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::api_infra::reapply_dependent_infra;
+use crate::interface::GenericCloudHandler;
+
+/// Result of re-applying a dependent deployment, stored in the event metadata of the triggering job
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TriggeredDependent {
+    pub deployment_id: String,
+    pub environment: String,
+    pub region: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Compares the values of two sets of terraform outputs (as returned by `terraform output -json`)
+pub fn outputs_changed(old: &Value, new: &Value) -> bool {
+    output_values(old) != output_values(new)
+}
+
+fn output_values(output: &Value) -> BTreeMap<&String, &Value> {
+    match output.as_object() {
+        Some(outputs) => outputs
+            .iter()
+            .map(|(name, output)| (name, output.get("value").unwrap_or(output)))
+            .collect(),
+        None => BTreeMap::new(),
+    }
+}
+
+fn get_dependent_identifier(dependent: &Dependent) -> String {
+    get_deployment_identifier(
+        &dependent.project_id,
+        &dependent.region,
+        &dependent.dependent_id,
+        &dependent.environment,
+    )
+}
+
+/// Orders the dependents so that every deployment comes after the deployments it depends on.
+///
+/// `edges` are `(deployment, dependent)` pairs reachable from `root`. Dependents that are part
+/// of (or depend on) a dependency cycle cannot be ordered and are returned separately.
+pub fn order_dependents(
+    root: &str,
+    edges: &[(String, Dependent)],
+) -> (Vec<Dependent>, Vec<Dependent>) {
+    let mut nodes: Vec<String> = vec![];
+    let mut dependents: HashMap<String, Dependent> = HashMap::new();
+    let mut in_degree: HashMap<String, usize> = HashMap::new();
+    let mut outgoing: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut seen_edges: HashSet<(&str, String)> = HashSet::new();
+
+    for (upstream, dependent) in edges {
+        let key = get_dependent_identifier(dependent);
+        if key == root {
+            warn!(
+                "Dependency cycle: {} depends on {} which depends on it, not triggering it again",
+                root, upstream
+            );
+            continue;
+        }
+        if !seen_edges.insert((upstream.as_str(), key.clone())) {
+            continue;
+        }
+        if !dependents.contains_key(&key) {
+            nodes.push(key.clone());
+            dependents.insert(key.clone(), dependent.clone());
+            in_degree.insert(key.clone(), 0);
+        }
+        // The root is the deployment that just finished, it is not waited for
+        if upstream != root {
+            *in_degree.get_mut(&key).unwrap() += 1;
+            outgoing.entry(upstream.as_str()).or_default().push(key);
+        }
+    }
+
+    let mut ready: VecDeque<String> = nodes
+        .iter()
+        .filter(|key| in_degree[*key] == 0)
+        .cloned()
+        .collect();
+    let mut ordered = vec![];
+    while let Some(key) = ready.pop_front() {
+        for next in outgoing.get(key.as_str()).into_iter().flatten() {
+            let degree = in_degree.get_mut(next).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push_back(next.clone());
+            }
+        }
+        ordered.push(dependents[&key].clone());
+    }
+
+    let cyclic = nodes
+        .iter()
+        .filter(|key| in_degree[*key] > 0)
+        .map(|key| dependents[key].clone())
+        .collect();
+    (ordered, cyclic)
+}
+
+/// Returns every deployment depending directly or indirectly on the deployment, as `(deployment, dependent)` pairs
+async fn get_dependent_edges(
+    handler: &GenericCloudHandler,
+    root: &str,
+    deployment_id: &str,
+    environment: &str,
+) -> Result<Vec<(String, Dependent)>, anyhow::Error> {
+    let mut edges = vec![];
+    let mut visited: HashSet<String> = HashSet::from([root.to_string()]);
+    let mut queue: VecDeque<(String, Vec<Dependent>)> = VecDeque::from([(
+        root.to_string(),
+        handler.get_dependents(deployment_id, environment).await?,
+    )]);
+
+    while let Some((upstream, dependents)) = queue.pop_front() {
+        for dependent in dependents {
+            let key = get_dependent_identifier(&dependent);
+            edges.push((upstream.clone(), dependent.clone()));
+            if visited.insert(key.clone()) {
+                let next = GenericCloudHandler::region(&dependent.region)
+                    .await
+                    .get_dependents(&dependent.dependent_id, &dependent.environment)
+                    .await?;
+                queue.push_back((key, next));
+            }
+        }
+    }
+    Ok(edges)
+}
+
+/// Requests an apply for every deployment depending directly or indirectly on the deployment of the
+/// triggering job, in dependency order. The applies are linked to the triggering job.
+pub async fn trigger_dependent_deployments(
+    handler: &GenericCloudHandler,
+    triggered_by: &CascadeTrigger,
+) -> Result<Vec<TriggeredDependent>, anyhow::Error> {
+    let root = get_deployment_identifier(
+        handler.get_project_id(),
+        handler.get_region(),
+        &triggered_by.deployment_id,
+        &triggered_by.environment,
+    );
+    let edges = get_dependent_edges(
+        handler,
+        &root,
+        &triggered_by.deployment_id,
+        &triggered_by.environment,
+    )
+    .await?;
+    let (ordered, cyclic) = order_dependents(&root, &edges);
+
+    let mut triggered = vec![];
+    for dependent in ordered {
+        let result = reapply_dependent_infra(
+            &GenericCloudHandler::region(&dependent.region).await,
+            &dependent.dependent_id,
+            &dependent.environment,
+            triggered_by.clone(),
+        )
+        .await;
+        let (job_id, error) = match result {
+            Ok(job_id) => {
+                info!(
+                    "Requested apply of dependent {} in {}: {}",
+                    dependent.dependent_id, dependent.environment, job_id
+                );
+                (Some(job_id), None)
+            }
+            Err(e) => {
+                error!(
+                    "Failed to request apply of dependent {} in {}: {}",
+                    dependent.dependent_id, dependent.environment, e
+                );
+                (None, Some(e.to_string()))
+            }
+        };
+        triggered.push(TriggeredDependent {
+            deployment_id: dependent.dependent_id,
+            environment: dependent.environment,
+            region: dependent.region,
+            job_id,
+            error,
+        });
+    }
+
+    for dependent in cyclic {
+        warn!(
+            "Not triggering {} in {} since it is part of a dependency cycle",
+            dependent.dependent_id, dependent.environment
+        );
+        triggered.push(TriggeredDependent {
+            deployment_id: dependent.dependent_id,
+            environment: dependent.environment,
+            region: dependent.region,
+            job_id: None,
+            error: Some("Part of a dependency cycle".to_string()),
+        });
+    }
+
+    Ok(triggered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn dependent(id: &str) -> Dependent {
+        Dependent {
+            project_id: "p".to_string(),
+            region: "r".to_string(),
+            dependent_id: id.to_string(),
+            environment: "dev".to_string(),
+        }
+    }
+
+    fn key(id: &str) -> String {
+        get_dependent_identifier(&dependent(id))
+    }
+
+    fn ids(dependents: &[Dependent]) -> Vec<&str> {
+        dependents.iter().map(|d| d.dependent_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_outputs_changed() {
+        let old = json!({"bucket": {"sensitive": false, "type": "string", "value": "a"}});
+        let same = json!({"bucket": {"sensitive": true, "type": "string", "value": "a"}});
+        let new = json!({"bucket": {"sensitive": false, "type": "string", "value": "b"}});
+        assert_eq!(outputs_changed(&old, &same), false);
+        assert_eq!(outputs_changed(&old, &new), true);
+        assert_eq!(outputs_changed(&Value::Null, &json!({})), false);
+        assert_eq!(outputs_changed(&Value::Null, &old), true);
+    }
+
+    #[test]
+    fn test_order_dependents_topological() {
+        // root -> a, root -> b, a -> b, b -> c
+        let root = key("root");
+        let edges = vec![
+            (root.clone(), dependent("b")),
+            (root.clone(), dependent("a")),
+            (key("b"), dependent("c")),
+            (key("a"), dependent("b")),
+        ];
+        let (ordered, cyclic) = order_dependents(&root, &edges);
+        assert_eq!(ids(&ordered), vec!["a", "b", "c"]);
+        assert_eq!(cyclic.is_empty(), true);
+    }
+
+    #[test]
+    fn test_order_dependents_cycle() {
+        // root -> a, a -> b, b -> a, b -> root, root -> c
+        let root = key("root");
+        let edges = vec![
+            (root.clone(), dependent("a")),
+            (root.clone(), dependent("c")),
+            (key("a"), dependent("b")),
+            (key("b"), dependent("a")),
+            (key("b"), dependent("root")),
+        ];
+        let (ordered, cyclic) = order_dependents(&root, &edges);
+        assert_eq!(ids(&ordered), vec!["c"]);
+        assert_eq!(ids(&cyclic), vec!["a", "b"]);
+    }
+}
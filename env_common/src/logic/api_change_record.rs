@@ -10,10 +10,10 @@ pub async fn insert_infra_change_record(
     infra_change_record: InfraChangeRecord,
     plan_output_raw: &str,
 ) -> Result<String, anyhow::Error> {
-    match upload_plan_output_file(
+    match upload_change_record_file(
         handler,
         &infra_change_record.plan_raw_json_key,
-        plan_output_raw,
+        plan_output_raw.as_bytes(),
     )
     .await
     {
@@ -25,6 +25,14 @@ pub async fn insert_infra_change_record(
         }
     }
 
+    put_infra_change_record(handler, &infra_change_record).await
+}
+
+/// Inserts the change record in the database without uploading any files
+pub(crate) async fn put_infra_change_record(
+    handler: &GenericCloudHandler,
+    infra_change_record: &InfraChangeRecord,
+) -> Result<String, anyhow::Error> {
     let pk_prefix = match infra_change_record.change_type.as_str() {
         "apply" => "APPLY",
         "plan" => "PLAN",
@@ -48,7 +56,7 @@ pub async fn insert_infra_change_record(
         "SK": &infra_change_record.job_id,
     }))
     .unwrap();
-    let infra_change_record_value = serde_json::to_value(&infra_change_record).unwrap();
+    let infra_change_record_value = serde_json::to_value(infra_change_record).unwrap();
     merge_json_dicts(&mut infra_change_record_payload, &infra_change_record_value);
 
     println!(
@@ -68,10 +76,11 @@ pub async fn insert_infra_change_record(
     }
 }
 
-async fn upload_plan_output_file<T: CloudProvider>(
+/// Uploads a file to the change records bucket, such as the plan output or the binary plan file
+pub async fn upload_change_record_file<T: CloudProvider>(
     handler: &T,
     key: &str,
-    content: &str,
+    content: &[u8],
 ) -> Result<String, anyhow::Error> {
     let base64_content = base64.encode(content);
 
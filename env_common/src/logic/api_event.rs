@@ -6,6 +6,22 @@ use crate::interface::GenericCloudHandler;
 pub async fn insert_event(
     handler: &GenericCloudHandler,
     event: EventData,
+) -> Result<String, anyhow::Error> {
+    put_event(handler, &event, get_epoch()).await
+}
+
+/// Inserts the event keeping its own epoch as sort key, used when events are copied
+pub(crate) async fn copy_event(
+    handler: &GenericCloudHandler,
+    event: &EventData,
+) -> Result<String, anyhow::Error> {
+    put_event(handler, event, event.epoch).await
+}
+
+async fn put_event(
+    handler: &GenericCloudHandler,
+    event: &EventData,
+    sk: u128,
 ) -> Result<String, anyhow::Error> {
     let id: String = format!(
         "EVENT#{}",
@@ -21,12 +37,12 @@ pub async fn insert_event(
 
     let mut event_payload = serde_json::to_value(serde_json::json!({
         "PK": id.clone(),
-        "SK": get_epoch().to_string(),
+        "SK": sk.to_string(),
         "PK_base_region": pk_base_region,
     }))
     .unwrap();
 
-    let event_value = serde_json::to_value(&event).unwrap();
+    let event_value = serde_json::to_value(event).unwrap();
     merge_json_dicts(&mut event_payload, &event_value);
 
     let payload = serde_json::json!({
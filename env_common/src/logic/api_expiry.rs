@@ -0,0 +1,390 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use env_defs::{CloudProvider, DeploymentResp, DeploymentSpec, EventData, ExtraData};
+use env_utils::get_epoch;
+use humantime::{parse_duration, parse_rfc3339_weak};
+use log::{error, info, warn};
+use serde::Serialize;
+use serde_json::json;
+
+use super::api_infra::{destroy_infra, get_deployment_status_handler};
+use crate::interface::GenericCloudHandler;
+
+/// How long before a deployment expires a warning event is recorded for it
+pub const EXPIRY_WARNING_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+const EXPIRY_WARNING_EVENT: &str = "expiry_warning";
+const EXPIRY_BLOCKED_EVENT: &str = "expiry_blocked";
+const EXPIRED_EVENT: &str = "expired";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryState {
+    /// No `ttl` or `expiresAt` is set for the deployment
+    NotExpiring,
+    Active,
+    /// The deployment expires within `EXPIRY_WARNING_PERIOD`
+    ExpiringSoon,
+    Expired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryAction {
+    /// A warning event was recorded since the deployment expires soon
+    Warned,
+    /// The deployment has expired but was not destroyed since other deployments depend on it
+    Blocked,
+    /// A destroy job was requested for the expired deployment
+    Destroyed,
+}
+
+/// Result of handling an expiring deployment, returned by the reconciler
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExpiryResult {
+    pub deployment_id: String,
+    pub environment: String,
+    pub expires_at_epoch: u128,
+    pub action: ExpiryAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Returns the epoch (ms) when a claim applied at `now` expires, from either `ttl` or `expiresAt`
+pub fn get_expires_at_epoch(
+    spec: &DeploymentSpec,
+    now: u128,
+) -> Result<Option<u128>, anyhow::Error> {
+    match (&spec.ttl, &spec.expires_at) {
+        (None, None) => Ok(None),
+        (Some(_), Some(_)) => Err(anyhow::anyhow!(
+            "Both ttl and expiresAt are set, only one should be set"
+        )),
+        (Some(ttl), None) => {
+            let ttl = parse_duration(ttl)
+                .map_err(|e| anyhow::anyhow!("Invalid ttl \"{}\": {}", ttl, e))?;
+            Ok(Some(now + ttl.as_millis()))
+        }
+        (None, Some(expires_at)) => {
+            let expires_at_epoch = parse_rfc3339_weak(expires_at)
+                .map_err(|e| anyhow::anyhow!("Invalid expiresAt \"{}\": {}", expires_at, e))?
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| anyhow::anyhow!("Invalid expiresAt \"{}\"", expires_at))?
+                .as_millis();
+            if expires_at_epoch <= now {
+                return Err(anyhow::anyhow!(
+                    "expiresAt {} has already passed",
+                    expires_at
+                ));
+            }
+            Ok(Some(expires_at_epoch))
+        }
+    }
+}
+
+pub fn get_expiry_state(expires_at_epoch: Option<u128>, now: u128) -> ExpiryState {
+    match expires_at_epoch {
+        None => ExpiryState::NotExpiring,
+        Some(expires_at_epoch) if expires_at_epoch <= now => ExpiryState::Expired,
+        Some(expires_at_epoch) if expires_at_epoch - now <= EXPIRY_WARNING_PERIOD.as_millis() => {
+            ExpiryState::ExpiringSoon
+        }
+        Some(_) => ExpiryState::Active,
+    }
+}
+
+/// The event has already been recorded for the current expiry time of the deployment
+fn has_expiry_event(events: &[EventData], event: &str, expires_at_epoch: u128) -> bool {
+    events
+        .iter()
+        .any(|e| e.event == event && e.metadata["expires_at_epoch"] == json!(expires_at_epoch))
+}
+
+/// Destroys the expired deployments in the project and region of the handler, and records a
+/// warning event for deployments expiring within `EXPIRY_WARNING_PERIOD`.
+///
+/// An expired deployment that other deployments depend on is not destroyed, an event is recorded
+/// instead and it is destroyed by a later run once it no longer has dependents.
+pub async fn expire_deployments(
+    handler: &GenericCloudHandler,
+) -> Result<Vec<ExpiryResult>, anyhow::Error> {
+    let now = get_epoch();
+    let deployments = handler.get_all_deployments("", false).await?;
+
+    let mut results = vec![];
+    for deployment in deployments {
+        let expires_at_epoch = match deployment.expires_at_epoch {
+            Some(expires_at_epoch) => expires_at_epoch,
+            None => continue,
+        };
+        // Jobs in progress are left alone, the deployment is handled by a later run
+        if deployment.status.is_in_progress() {
+            continue;
+        }
+        let (action, result) = match get_expiry_state(Some(expires_at_epoch), now) {
+            ExpiryState::ExpiringSoon => (
+                ExpiryAction::Warned,
+                warn_expiring_deployment(handler, &deployment).await,
+            ),
+            ExpiryState::Expired => (
+                ExpiryAction::Destroyed,
+                expire_deployment(handler, &deployment).await,
+            ),
+            ExpiryState::NotExpiring | ExpiryState::Active => continue,
+        };
+        match result {
+            Ok(Some(result)) => results.push(result),
+            Ok(None) => {}
+            Err(e) => {
+                error!(
+                    "Failed to handle expiry of {} in {}: {}",
+                    deployment.deployment_id, deployment.environment, e
+                );
+                results.push(ExpiryResult {
+                    deployment_id: deployment.deployment_id.clone(),
+                    environment: deployment.environment.clone(),
+                    expires_at_epoch,
+                    action,
+                    job_id: None,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+    Ok(results)
+}
+
+async fn warn_expiring_deployment(
+    handler: &GenericCloudHandler,
+    deployment: &DeploymentResp,
+) -> Result<Option<ExpiryResult>, anyhow::Error> {
+    let expires_at_epoch = deployment.expires_at_epoch.unwrap_or_default();
+    let events = handler
+        .get_events(&deployment.deployment_id, &deployment.environment)
+        .await?;
+    if has_expiry_event(&events, EXPIRY_WARNING_EVENT, expires_at_epoch) {
+        return Ok(None);
+    }
+
+    let minutes_left = expires_at_epoch.saturating_sub(get_epoch()) / 60_000;
+    info!(
+        "Deployment {} in {} expires in {} minutes",
+        deployment.deployment_id, deployment.environment, minutes_left
+    );
+    insert_expiry_event(
+        handler,
+        deployment,
+        EXPIRY_WARNING_EVENT,
+        format!(
+            "The deployment expires in {} minutes and will then be destroyed",
+            minutes_left
+        ),
+        &[],
+    )
+    .await;
+    Ok(Some(ExpiryResult {
+        deployment_id: deployment.deployment_id.clone(),
+        environment: deployment.environment.clone(),
+        expires_at_epoch,
+        action: ExpiryAction::Warned,
+        job_id: None,
+        error: None,
+    }))
+}
+
+async fn expire_deployment(
+    handler: &GenericCloudHandler,
+    deployment: &DeploymentResp,
+) -> Result<Option<ExpiryResult>, anyhow::Error> {
+    let expires_at_epoch = deployment.expires_at_epoch.unwrap_or_default();
+    let dependents = handler
+        .get_dependents(&deployment.deployment_id, &deployment.environment)
+        .await?;
+    if !dependents.is_empty() {
+        let dependent_ids = dependents
+            .iter()
+            .map(|d| format!("{} ({})", d.dependent_id, d.environment))
+            .collect::<Vec<String>>()
+            .join(", ");
+        warn!(
+            "Not destroying expired deployment {} in {} since it has dependents: {}",
+            deployment.deployment_id, deployment.environment, dependent_ids
+        );
+        let events = handler
+            .get_events(&deployment.deployment_id, &deployment.environment)
+            .await?;
+        if !has_expiry_event(&events, EXPIRY_BLOCKED_EVENT, expires_at_epoch) {
+            insert_expiry_event(
+                handler,
+                deployment,
+                EXPIRY_BLOCKED_EVENT,
+                format!(
+                    "The deployment has expired but is not destroyed since other deployments depend on it: {}",
+                    dependent_ids
+                ),
+                &[("dependents", json!(dependents))],
+            )
+            .await;
+        }
+        return Ok(Some(ExpiryResult {
+            deployment_id: deployment.deployment_id.clone(),
+            environment: deployment.environment.clone(),
+            expires_at_epoch,
+            action: ExpiryAction::Blocked,
+            job_id: None,
+            error: Some(format!("Has dependents: {}", dependent_ids)),
+        }));
+    }
+
+    info!(
+        "Destroying expired deployment {} in {}",
+        deployment.deployment_id, deployment.environment
+    );
+    let job_id = destroy_infra(
+        handler,
+        &deployment.deployment_id,
+        &deployment.environment,
+        ExtraData::None,
+        None,
+    )
+    .await?;
+    insert_expiry_event(
+        handler,
+        deployment,
+        EXPIRED_EVENT,
+        "The deployment has expired and is being destroyed".to_string(),
+        &[("destroy_job_id", json!(job_id))],
+    )
+    .await;
+    Ok(Some(ExpiryResult {
+        deployment_id: deployment.deployment_id.clone(),
+        environment: deployment.environment.clone(),
+        expires_at_epoch,
+        action: ExpiryAction::Destroyed,
+        job_id: Some(job_id),
+        error: None,
+    }))
+}
+
+async fn insert_expiry_event(
+    handler: &GenericCloudHandler,
+    deployment: &DeploymentResp,
+    event: &str,
+    message: String,
+    metadata: &[(&str, serde_json::Value)],
+) {
+    let mut status_handler = get_deployment_status_handler(event, deployment, message);
+    status_handler.set_metadata_field("expires_at_epoch", json!(deployment.expires_at_epoch));
+    for (field, value) in metadata {
+        status_handler.set_metadata_field(field, value.clone());
+    }
+    status_handler.send_event(handler).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const HOUR: u128 = 60 * 60 * 1000;
+
+    fn spec(ttl: Option<&str>, expires_at: Option<&str>) -> DeploymentSpec {
+        DeploymentSpec {
+            module_version: Some("1.0.0".to_string()),
+            stack_version: None,
+            region: "us-west-2".to_string(),
+            reference: None,
+            variables: serde_yaml::Mapping::new(),
+            dependencies: None,
+            drift_detection: None,
+            ttl: ttl.map(str::to_string),
+            expires_at: expires_at.map(str::to_string),
+            imports: None,
+        }
+    }
+
+    fn event(event: &str, metadata: serde_json::Value) -> EventData {
+        serde_json::from_value(json!({
+            "deployment_id": "s3bucket/bucket",
+            "event": event,
+            "epoch": 0,
+            "error_text": "",
+            "job_id": "job",
+            "metadata": metadata,
+            "module": "s3bucket",
+            "name": "bucket",
+            "environment": "dev",
+            "project_id": "p",
+            "region": "r",
+            "status": "successful",
+            "timestamp": "",
+            "id": "id",
+            "output": {},
+            "policy_results": [],
+            "initiated_by": "user",
+            "drift_detection": {},
+            "next_drift_check_epoch": -1,
+            "has_drifted": false,
+            "event_duration": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_expires_at_epoch() {
+        // 2025-01-01T00:00:00Z
+        let now = 1_735_689_600_000;
+        assert_eq!(get_expires_at_epoch(&spec(None, None), now).unwrap(), None);
+        assert_eq!(
+            get_expires_at_epoch(&spec(Some("8h"), None), now).unwrap(),
+            Some(now + 8 * HOUR)
+        );
+        assert_eq!(
+            get_expires_at_epoch(&spec(None, Some("2025-01-02T00:00:00Z")), now).unwrap(),
+            Some(now + 24 * HOUR)
+        );
+    }
+
+    #[test]
+    fn test_get_expires_at_epoch_invalid() {
+        let now = 1_735_689_600_000;
+        assert!(
+            get_expires_at_epoch(&spec(Some("8h"), Some("2025-01-02T00:00:00Z")), now).is_err()
+        );
+        assert!(get_expires_at_epoch(&spec(Some("eight hours"), None), now).is_err());
+        assert!(get_expires_at_epoch(&spec(None, Some("tomorrow")), now).is_err());
+        assert!(get_expires_at_epoch(&spec(None, Some("2024-12-31T00:00:00Z")), now).is_err());
+    }
+
+    #[test]
+    fn test_get_expiry_state() {
+        let now = 10 * HOUR;
+        assert_eq!(get_expiry_state(None, now), ExpiryState::NotExpiring);
+        assert_eq!(
+            get_expiry_state(Some(now + 2 * HOUR), now),
+            ExpiryState::Active
+        );
+        assert_eq!(
+            get_expiry_state(Some(now + HOUR), now),
+            ExpiryState::ExpiringSoon
+        );
+        assert_eq!(get_expiry_state(Some(now), now), ExpiryState::Expired);
+        assert_eq!(
+            get_expiry_state(Some(now - HOUR), now),
+            ExpiryState::Expired
+        );
+    }
+
+    #[test]
+    fn test_has_expiry_event() {
+        let events = vec![
+            event(EXPIRY_WARNING_EVENT, json!({"expires_at_epoch": 1000})),
+            event("apply", json!({})),
+        ];
+        assert_eq!(has_expiry_event(&events, EXPIRY_WARNING_EVENT, 1000), true);
+        // The expiry time was changed by a new apply
+        assert_eq!(has_expiry_event(&events, EXPIRY_WARNING_EVENT, 2000), false);
+        assert_eq!(has_expiry_event(&events, EXPIRY_BLOCKED_EVENT, 1000), false);
+    }
+}
@@ -1,15 +1,20 @@
 use env_defs::{
-    ApiInfraPayload, ApiInfraPayloadWithVariables, CloudHandlerError, CloudProvider, Dependency,
-    DeploymentManifest, DeploymentResp, DriftDetection, ExtraData, GenericFunctionResponse,
+    ApiInfraPayload, ApiInfraPayloadWithVariables, CascadeTrigger, CloudHandlerError,
+    CloudProvider, Dependency, DeploymentManifest, DeploymentResp, DeploymentStatus,
+    DriftDetection, ExtraData, GenericFunctionResponse, ModuleResp, ModuleUpgradeWarning,
     Webhook,
 };
 use env_utils::{
     convert_first_level_keys_to_snake_case, flatten_and_convert_first_level_keys_to_snake_case,
-    get_version_track, verify_required_variables_are_set, verify_variable_claim_casing,
-    verify_variable_existence_and_type,
+    get_epoch, get_module_upgrade_warnings, get_version_track, validate_drift_detection,
+    verify_required_variables_are_set, verify_variable_claim_casing,
+    verify_variable_existence_and_type, verify_variable_validations,
 };
 use log::{debug, error, info, warn};
+use std::collections::BTreeMap;
 
+use super::api_expiry::get_expires_at_epoch;
+use super::tf_root_module::import_blocks;
 use crate::{interface::GenericCloudHandler, DeploymentStatusHandler};
 
 pub async fn mutate_infra(
@@ -46,6 +51,16 @@ pub fn get_deployment_details(
     Ok((region, environment, deployment_id, module, name))
 }
 
+/// Name of the module block in the root module that the addresses to import are relative to,
+/// `None` for stacks where they are relative to the root module itself
+pub fn get_import_module_name(module: &ModuleResp) -> Option<&str> {
+    if module.module_type == "stack" {
+        None
+    } else {
+        Some(&module.manifest.metadata.name)
+    }
+}
+
 /// Validates and prepares a claim payload for deployment
 /// This function performs all validation and constructs the payload without submitting it
 pub async fn validate_and_prepare_claim(
@@ -97,8 +112,25 @@ pub async fn validate_and_prepare_claim(
             enabled: drift_detection_enabled,
             auto_remediate: drift_detection_auto_remediate,
             webhooks: drift_detection_webhooks,
+            jitter: deployment_manifest
+                .spec
+                .drift_detection
+                .as_ref()
+                .and_then(|d| d.jitter.clone()),
+            quiet_hours: deployment_manifest
+                .spec
+                .drift_detection
+                .as_ref()
+                .map(|d| d.quiet_hours.clone())
+                .unwrap_or_default(),
         }
     };
+    if drift_detection.enabled {
+        validate_drift_detection(&drift_detection)?;
+    }
+
+    let expires_at_epoch = get_expires_at_epoch(&deployment_manifest.spec, get_epoch())?;
+    let imports = deployment_manifest.spec.imports.clone().unwrap_or_default();
 
     let deployment_variables: serde_yaml::Mapping = deployment_manifest.spec.variables;
     let provided_variables: serde_json::Value = if deployment_variables.is_empty() {
@@ -218,15 +250,40 @@ pub async fn validate_and_prepare_claim(
         convert_first_level_keys_to_snake_case(&provided_variables)
     };
 
+    // Report what changes for the claim if the deployment moves to another module version,
+    // before the checks below fail on the same variables with less context
+    let upgrade_warnings = match handler
+        .get_deployment(&deployment_id, &environment, false)
+        .await
+    {
+        Ok(Some(deployment)) => {
+            check_module_upgrade(handler, &deployment, &module_resp, &variables).await?
+        }
+        Ok(None) => vec![],
+        Err(e) => {
+            warn!(
+                "Failed to get deployment {} in {}, skipping upgrade checks: {}",
+                deployment_id, environment, e
+            );
+            vec![]
+        }
+    };
+
     // Validate input according to module schema
     verify_variable_existence_and_type(&module_resp, &variables)?;
 
     // Verify that all required variables are set
     verify_required_variables_are_set(&module_resp, &variables)?;
 
+    // Evaluate the validation blocks of the module variables that can be checked before apply
+    verify_variable_validations(&module_resp, &variables)?;
+
     // Verify that all provided claim variables are in camelCase and not in snake_case
     verify_variable_claim_casing(&claim, &provided_variables)?;
 
+    // Verify the resource addresses to import, the runner generates the import blocks from them
+    import_blocks(&imports, get_import_module_name(&module_resp))?;
+
     info!("Validated claim for environment: {}", environment);
     info!("command: {}", command);
     info!("module: {}", module);
@@ -258,6 +315,12 @@ pub async fn validate_and_prepare_claim(
         memory: module_resp.memory.clone(),
         reference: reference.clone(),
         extra_data,
+        triggered_by: None,
+        expires_at_epoch,
+        from_plan: None,
+        imports,
+        upgrade_warnings,
+        labels: get_claim_labels(&deployment_manifest.metadata.labels),
     };
 
     let payload_with_variables = ApiInfraPayloadWithVariables {
@@ -293,7 +356,105 @@ pub async fn run_claim(
     Ok((job_id, deployment_id, payload_with_variables))
 }
 
-fn validate_name(name: &str) -> Result<(), anyhow::Error> {
+/// Applies the plan file saved by the plan job `plan_job_id` instead of planning again, so exactly
+/// what was planned (and reviewed) is applied. The claim must match the claim that was planned,
+/// the runner refuses the job if the state or module version changed since the plan.
+pub async fn run_claim_from_plan(
+    handler: &GenericCloudHandler,
+    yaml: &serde_yaml::Value,
+    environment: &str,
+    plan_job_id: &str,
+    extra_data: ExtraData,
+    reference_fallback: &str,
+) -> Result<(String, String, ApiInfraPayloadWithVariables), anyhow::Error> {
+    let (deployment_id, mut payload_with_variables) = validate_and_prepare_claim(
+        handler,
+        yaml,
+        environment,
+        "apply",
+        vec![],
+        extra_data,
+        reference_fallback,
+    )
+    .await?;
+
+    use_saved_plan(handler, &mut payload_with_variables, plan_job_id).await?;
+    let job_id = submit_claim_job(handler, &payload_with_variables).await?;
+
+    Ok((job_id, deployment_id, payload_with_variables))
+}
+
+/// Makes the job apply the plan file saved by the plan job `plan_job_id`, after verifying that the
+/// plan succeeded for the same module version and variables and that its plan file was stored
+async fn use_saved_plan(
+    handler: &GenericCloudHandler,
+    payload_with_variables: &mut ApiInfraPayloadWithVariables,
+    plan_job_id: &str,
+) -> Result<(), anyhow::Error> {
+    let payload = &payload_with_variables.payload;
+    let plan = handler
+        .get_plan_deployment(&payload.deployment_id, &payload.environment, plan_job_id)
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No plan with job id {} found for deployment {} in {}",
+                plan_job_id,
+                payload.deployment_id,
+                payload.environment
+            )
+        })?;
+    verify_plan_matches_claim(&plan, payload_with_variables)?;
+    let change_record = handler
+        .get_change_record(
+            &payload.environment,
+            &payload.deployment_id,
+            plan_job_id,
+            "PLAN",
+        )
+        .await?;
+    if change_record.plan_file_key.is_none() {
+        return Err(anyhow::anyhow!(
+            "No plan file was stored for job {}, run a new plan to apply it (plan files of claims using secrets are never stored)",
+            plan_job_id
+        ));
+    }
+
+    payload_with_variables.payload.from_plan = Some(plan_job_id.to_string());
+    Ok(())
+}
+
+/// Verifies that a plan succeeded for the same module version and variables as the claim
+fn verify_plan_matches_claim(
+    plan: &DeploymentResp,
+    payload_with_variables: &ApiInfraPayloadWithVariables,
+) -> Result<(), anyhow::Error> {
+    let payload = &payload_with_variables.payload;
+    if plan.status != DeploymentStatus::Successful {
+        return Err(anyhow::anyhow!(
+            "Plan {} has status {}, only successful plans can be applied",
+            plan.job_id,
+            plan.status
+        ));
+    }
+    if plan.module_version != payload.module_version {
+        return Err(anyhow::anyhow!(
+            "Plan {} was made for version {} of {}, but the claim uses version {}",
+            plan.job_id,
+            plan.module_version,
+            payload.module,
+            payload.module_version
+        ));
+    }
+    if plan.variables != payload_with_variables.variables {
+        return Err(anyhow::anyhow!(
+            "The variables of the claim differ from the variables of plan {}",
+            plan.job_id
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_name(name: &str) -> Result<(), anyhow::Error> {
     // Only a-z, 0-9, and -
     // Starts/ends with alphanumeric
     // Length between 1 and 63 characters
@@ -307,6 +468,23 @@ fn validate_name(name: &str) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// Labels of the claim as strings, labels whose value is not a string, number or boolean are skipped
+fn get_claim_labels(labels: &Option<serde_yaml::Mapping>) -> BTreeMap<String, String> {
+    labels
+        .iter()
+        .flatten()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                serde_yaml::Value::String(value) => value.clone(),
+                serde_yaml::Value::Number(value) => value.to_string(),
+                serde_yaml::Value::Bool(value) => value.to_string(),
+                _ => return None,
+            };
+            Some((key.as_str()?.to_string(), value))
+        })
+        .collect()
+}
+
 pub async fn destroy_infra(
     handler: &GenericCloudHandler,
     deployment_id: &str,
@@ -370,6 +548,12 @@ pub async fn destroy_infra(
                     memory: deployment.memory,
                     reference: deployment.reference,
                     extra_data,
+                    triggered_by: None,
+                    expires_at_epoch: deployment.expires_at_epoch,
+                    labels: deployment.labels.clone(),
+                    from_plan: None,
+                    imports: BTreeMap::new(),
+                    upgrade_warnings: vec![],
                 };
 
                 let payload_with_variables = ApiInfraPayloadWithVariables {
@@ -415,6 +599,43 @@ pub async fn driftcheck_infra(
     environment: &str,
     remediate: bool,
     extra_data: ExtraData,
+) -> Result<String, anyhow::Error> {
+    _driftcheck_infra(
+        handler,
+        deployment_id,
+        environment,
+        remediate,
+        extra_data,
+        None,
+    )
+    .await
+}
+
+/// Re-applies a deployment with its current variables since the outputs of a deployment it depends on changed
+pub async fn reapply_dependent_infra(
+    handler: &GenericCloudHandler,
+    deployment_id: &str,
+    environment: &str,
+    triggered_by: CascadeTrigger,
+) -> Result<String, anyhow::Error> {
+    _driftcheck_infra(
+        handler,
+        deployment_id,
+        environment,
+        true,
+        ExtraData::None,
+        Some(triggered_by),
+    )
+    .await
+}
+
+async fn _driftcheck_infra(
+    handler: &GenericCloudHandler,
+    deployment_id: &str,
+    environment: &str,
+    remediate: bool,
+    extra_data: ExtraData,
+    triggered_by: Option<CascadeTrigger>,
 ) -> Result<String, anyhow::Error> {
     let name = "".to_string();
     match handler
@@ -475,6 +696,12 @@ pub async fn driftcheck_infra(
                     memory: deployment.memory.clone(),
                     reference: deployment.reference.clone(),
                     extra_data,
+                    triggered_by,
+                    expires_at_epoch: deployment.expires_at_epoch,
+                    labels: deployment.labels.clone(),
+                    from_plan: None,
+                    imports: BTreeMap::new(),
+                    upgrade_warnings: vec![],
                 };
 
                 let payload_with_variables = ApiInfraPayloadWithVariables {
@@ -498,7 +725,7 @@ pub async fn submit_claim_job(
     payload_with_variables: &ApiInfraPayloadWithVariables,
 ) -> Result<String, anyhow::Error> {
     let payload = &payload_with_variables.payload;
-    let (in_progress, job_id, _, _) = is_deployment_in_progress(
+    let (in_progress, job_id, _, existing_deployment) = is_deployment_in_progress(
         handler,
         &payload.deployment_id,
         &payload.environment,
@@ -522,7 +749,12 @@ pub async fn submit_claim_job(
         }
     };
 
-    insert_request_event(handler, payload_with_variables, &job_id).await?;
+    // Keep the outputs of the deployment until the new job has replaced them, they are
+    // used to detect if the outputs changed when the job completes
+    let output = existing_deployment
+        .map(|deployment| deployment.output)
+        .unwrap_or(serde_json::Value::Null);
+    insert_request_event(handler, payload_with_variables, &job_id, output).await?;
 
     Ok(job_id)
 }
@@ -531,15 +763,16 @@ async fn insert_request_event(
     handler: &GenericCloudHandler,
     payload_with_variables: &ApiInfraPayloadWithVariables,
     job_id: &str,
+    output: serde_json::Value,
 ) -> Result<(), anyhow::Error> {
     let payload = &payload_with_variables.payload;
-    let status_handler = DeploymentStatusHandler::new(
+    let mut status_handler = DeploymentStatusHandler::new(
         &payload.command,
         &payload.module,
         &payload.module_version,
         &payload.module_type,
         &payload.module_track,
-        "requested".to_string(),
+        DeploymentStatus::Requested,
         &payload.environment,
         &payload.deployment_id,
         &payload.project_id,
@@ -551,27 +784,350 @@ async fn insert_request_event(
         payload.drift_detection.clone(),
         payload.next_drift_check_epoch,
         payload.dependencies.clone(),
-        serde_json::Value::Null,
+        output,
         vec![],
         payload.initiated_by.as_str(),
         payload.cpu.clone(),
         payload.memory.clone(),
         payload.reference.clone(),
     );
+    status_handler.set_expires_at_epoch(payload.expires_at_epoch);
+    status_handler.set_labels(payload.labels.clone());
+    if let Some(triggered_by) = &payload.triggered_by {
+        status_handler.set_triggered_by(triggered_by);
+    }
     status_handler.send_event(handler).await;
     status_handler.send_deployment(handler).await?;
     Ok(())
 }
 
+/// Builds the payload to restore the module version and variables recorded by a previous apply of
+/// the deployment, `command` is `plan` to preview the rollback and `apply` to perform it
+pub async fn prepare_rollback(
+    handler: &GenericCloudHandler,
+    deployment_id: &str,
+    environment: &str,
+    job_id: &str,
+    command: &str,
+) -> Result<ApiInfraPayloadWithVariables, anyhow::Error> {
+    let deployment = match handler
+        .get_deployment(deployment_id, environment, false)
+        .await?
+    {
+        Some(deployment) => deployment,
+        None => {
+            return Err(anyhow::anyhow!(
+                "Deployment {} not found in {}",
+                deployment_id,
+                environment
+            ))
+        }
+    };
+    let change_record = handler
+        .get_change_record(environment, deployment_id, job_id, "APPLY")
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "No apply with job id {} found for deployment {}: {}",
+                job_id,
+                deployment_id,
+                e
+            )
+        })?;
+    if change_record.variables.is_null() {
+        return Err(anyhow::anyhow!(
+            "The change record of job {} has no variables (it was recorded by an older version), cannot roll back to it",
+            job_id
+        ));
+    }
+    verify_module_version(handler, &deployment.module, &change_record.module_version).await?;
+
+    info!(
+        "Rolling back deployment {} to job {}: version {} -> {}",
+        deployment_id, job_id, deployment.module_version, change_record.module_version
+    );
+
+    let payload =
+        get_version_change_payload(handler, &deployment, &change_record.module_version, command)
+            .await?;
+
+    Ok(ApiInfraPayloadWithVariables {
+        payload,
+        variables: change_record.variables,
+    })
+}
+
+/// Builds the payload to move a deployment to another version of its module (or stack) with its
+/// current variables, `command` is `plan` to preview the upgrade and `apply` to perform it.
+/// The variables are verified against the new version before anything is submitted.
+pub async fn prepare_upgrade(
+    handler: &GenericCloudHandler,
+    deployment_id: &str,
+    environment: &str,
+    module_version: &str,
+    command: &str,
+) -> Result<ApiInfraPayloadWithVariables, anyhow::Error> {
+    let deployment = match handler
+        .get_deployment(deployment_id, environment, false)
+        .await?
+    {
+        Some(deployment) => deployment,
+        None => {
+            return Err(anyhow::anyhow!(
+                "Deployment {} not found in {}",
+                deployment_id,
+                environment
+            ))
+        }
+    };
+    let is_stack = deployment.module_type == "stack";
+    let track = get_version_track(module_version)
+        .map_err(|e| anyhow::anyhow!("Failed to get track from version: {}", e))?;
+    let module_resp = if is_stack {
+        handler
+            .get_stack_version(&deployment.module, &track, module_version)
+            .await?
+    } else {
+        handler
+            .get_module_version(&deployment.module, &track, module_version)
+            .await?
+    }
+    .ok_or_else(|| {
+        anyhow::anyhow!(
+            "{} version does not exist: {}",
+            if is_stack { "Stack" } else { "Module" },
+            module_version
+        )
+    })?;
+
+    let upgrade_warnings =
+        check_module_upgrade(handler, &deployment, &module_resp, &deployment.variables).await?;
+    verify_variable_existence_and_type(&module_resp, &deployment.variables)?;
+    verify_required_variables_are_set(&module_resp, &deployment.variables)?;
+    verify_variable_validations(&module_resp, &deployment.variables)?;
+
+    info!(
+        "Upgrading deployment {}: version {} -> {}",
+        deployment_id, deployment.module_version, module_version
+    );
+
+    let payload = ApiInfraPayload {
+        upgrade_warnings,
+        ..get_version_change_payload(handler, &deployment, module_version, command).await?
+    };
+
+    Ok(ApiInfraPayloadWithVariables {
+        payload,
+        variables: deployment.variables,
+    })
+}
+
+/// Submits a job moving the deployment to another version of its module with its current variables.
+/// With `from_plan` the job applies the plan file of that upgrade plan instead of planning again
+pub async fn upgrade_infra(
+    handler: &GenericCloudHandler,
+    deployment_id: &str,
+    environment: &str,
+    module_version: &str,
+    command: &str,
+    from_plan: Option<&str>,
+) -> Result<String, anyhow::Error> {
+    let mut payload_with_variables =
+        prepare_upgrade(handler, deployment_id, environment, module_version, command).await?;
+    if let Some(plan_job_id) = from_plan {
+        use_saved_plan(handler, &mut payload_with_variables, plan_job_id).await?;
+    }
+    submit_claim_job(handler, &payload_with_variables).await
+}
+
+async fn get_version_change_payload(
+    handler: &GenericCloudHandler,
+    deployment: &DeploymentResp,
+    module_version: &str,
+    command: &str,
+) -> Result<ApiInfraPayload, anyhow::Error> {
+    let module_track = get_version_track(module_version)
+        .map_err(|e| anyhow::anyhow!("Failed to get track from version: {}", e))?;
+    Ok(ApiInfraPayload {
+        command: command.to_string(),
+        flags: vec![],
+        module: deployment.module.to_lowercase(),
+        module_version: module_version.to_string(),
+        module_type: deployment.module_type.clone(),
+        module_track,
+        name: "".to_string(),
+        environment: deployment.environment.clone(),
+        deployment_id: deployment.deployment_id.clone(),
+        project_id: deployment.project_id.clone(),
+        region: deployment.region.clone(),
+        drift_detection: deployment.drift_detection.clone(),
+        next_drift_check_epoch: -1, // Prevent reconciler from finding this deployment since it is in progress
+        annotations: serde_json::json!({}),
+        dependencies: deployment.dependencies.clone(),
+        initiated_by: handler.get_user_id().await?,
+        cpu: deployment.cpu.clone(),
+        memory: deployment.memory.clone(),
+        reference: deployment.reference.clone(),
+        extra_data: ExtraData::None,
+        triggered_by: None,
+        expires_at_epoch: deployment.expires_at_epoch,
+        from_plan: None,
+        imports: BTreeMap::new(),
+        upgrade_warnings: vec![],
+        labels: deployment.labels.clone(),
+    })
+}
+
+/// Submits a job restoring the module version and variables recorded by a previous apply of the deployment.
+/// With `from_plan` the job applies the plan file of that rollback plan instead of planning again
+pub async fn rollback_infra(
+    handler: &GenericCloudHandler,
+    deployment_id: &str,
+    environment: &str,
+    job_id: &str,
+    command: &str,
+    from_plan: Option<&str>,
+) -> Result<String, anyhow::Error> {
+    let mut payload_with_variables =
+        prepare_rollback(handler, deployment_id, environment, job_id, command).await?;
+    if let Some(plan_job_id) = from_plan {
+        use_saved_plan(handler, &mut payload_with_variables, plan_job_id).await?;
+    }
+    submit_claim_job(handler, &payload_with_variables).await
+}
+
+/// How long to wait for the runner of a cancelled job to stop and record the cancellation
+const CANCEL_TIMEOUT_SECONDS: u64 = 120;
+const CANCEL_POLL_INTERVAL_SECONDS: u64 = 5;
+
+/// Cancels the job in progress for the deployment and returns its job id.
+///
+/// The runner interrupts terraform (which releases the state lock) and records the cancellation.
+/// If the runner has not recorded it when the job has stopped, e.g. since the job never started,
+/// the cancellation is recorded here so the deployment is not blocked by the job.
+pub async fn cancel_deployment_job(
+    handler: &GenericCloudHandler,
+    deployment_id: &str,
+    environment: &str,
+) -> Result<String, anyhow::Error> {
+    let deployment = match handler
+        .get_deployment(deployment_id, environment, false)
+        .await?
+    {
+        Some(deployment) => deployment,
+        None => {
+            return Err(anyhow::anyhow!(
+                "Deployment {} not found in {}",
+                deployment_id,
+                environment
+            ))
+        }
+    };
+    if !deployment.status.is_in_progress() {
+        return Err(anyhow::anyhow!(
+            "No job is in progress for deployment {} (status: {})",
+            deployment_id,
+            deployment.status
+        ));
+    }
+    let job_id = deployment.job_id.clone();
+
+    info!("Cancelling job {} for deployment {}", job_id, deployment_id);
+    handler.cancel_job(&job_id).await?;
+
+    let mut waited_seconds = 0;
+    while waited_seconds < CANCEL_TIMEOUT_SECONDS {
+        match handler.get_job_status(&job_id).await {
+            Ok(Some(job_status)) if job_status.is_running => {}
+            Ok(_) => break,
+            Err(e) => warn!("Failed to get job status for {}: {}", job_id, e),
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(CANCEL_POLL_INTERVAL_SECONDS)).await;
+        waited_seconds += CANCEL_POLL_INTERVAL_SECONDS;
+    }
+
+    match handler
+        .get_deployment(deployment_id, environment, false)
+        .await?
+    {
+        Some(deployment) if deployment.job_id == job_id && deployment.status.is_in_progress() => {
+            warn!(
+                "Job {} did not record the cancellation, recording it for deployment {}",
+                job_id, deployment_id
+            );
+            insert_cancelled_event(handler, &deployment).await?;
+        }
+        _ => {
+            info!("Job {} was cancelled", job_id);
+        }
+    }
+    Ok(job_id)
+}
+
+async fn insert_cancelled_event(
+    handler: &GenericCloudHandler,
+    deployment: &DeploymentResp,
+) -> Result<(), anyhow::Error> {
+    let mut status_handler = get_deployment_status_handler(
+        "cancel",
+        deployment,
+        "The job was cancelled before it recorded any progress".to_string(),
+    );
+    status_handler.set_status(DeploymentStatus::Cancelled)?;
+    status_handler.send_event(handler).await;
+    status_handler.send_deployment(handler).await
+}
+
+/// Status handler for recording events about a deployment outside of a job, it keeps the
+/// current state of the deployment
+pub(crate) fn get_deployment_status_handler<'a>(
+    command: &'a str,
+    deployment: &'a DeploymentResp,
+    error_text: String,
+) -> DeploymentStatusHandler<'a> {
+    let name = deployment
+        .deployment_id
+        .split('/')
+        .next_back()
+        .unwrap_or_default();
+    let mut status_handler = DeploymentStatusHandler::new(
+        command,
+        &deployment.module,
+        &deployment.module_version,
+        &deployment.module_type,
+        &deployment.module_track,
+        deployment.status.clone(),
+        &deployment.environment,
+        &deployment.deployment_id,
+        &deployment.project_id,
+        &deployment.region,
+        error_text,
+        deployment.job_id.clone(),
+        name,
+        deployment.variables.clone(),
+        deployment.drift_detection.clone(),
+        deployment.next_drift_check_epoch,
+        deployment.dependencies.clone(),
+        deployment.output.clone(),
+        deployment.policy_results.clone(),
+        &deployment.initiated_by,
+        deployment.cpu.clone(),
+        deployment.memory.clone(),
+        deployment.reference.clone(),
+    );
+    status_handler.set_resources(deployment.tf_resources.clone());
+    status_handler.set_expires_at_epoch(deployment.expires_at_epoch);
+    status_handler.set_labels(deployment.labels.clone());
+    status_handler
+}
+
 pub async fn is_deployment_in_progress(
     handler: &GenericCloudHandler,
     deployment_id: &str,
     environment: &str,
     job_check: bool, // Ensure that the job is actually running even if deployment status is in progress
     include_deleted: bool,
-) -> (bool, String, String, Option<DeploymentResp>) {
-    let busy_statuses = ["requested", "initiated"]; // TODO: use enums
-
+) -> (bool, String, Option<DeploymentStatus>, Option<DeploymentResp>) {
     let deployment = match handler
         .get_deployment(deployment_id, environment, include_deleted)
         .await
@@ -583,16 +1139,16 @@ pub async fn is_deployment_in_progress(
                     "No existing deployment was not found for {}, {}",
                     deployment_id, environment
                 );
-                return (false, "".to_string(), "".to_string(), None);
+                return (false, "".to_string(), None, None);
             }
         },
         Err(e) => {
             error!("Failed to describe deployment: {}", e);
-            return (false, "".to_string(), "".to_string(), None);
+            return (false, "".to_string(), None, None);
         }
     };
 
-    if busy_statuses.contains(&deployment.status.as_str()) {
+    if deployment.status.is_in_progress() {
         if job_check {
             warn!(
                 "Deployment is currently in process according to deployment: {}",
@@ -611,7 +1167,7 @@ pub async fn is_deployment_in_progress(
                         return (
                             false,
                             "".to_string(),
-                            deployment.status.to_string(),
+                            Some(deployment.status.clone()),
                             Some(deployment.clone()),
                         );
                     }
@@ -630,7 +1186,7 @@ pub async fn is_deployment_in_progress(
         return (
             true,
             deployment.job_id.clone(),
-            deployment.status.to_string(),
+            Some(deployment.status.clone()),
             Some(deployment.clone()),
         );
     }
@@ -638,7 +1194,7 @@ pub async fn is_deployment_in_progress(
     (
         false,
         "".to_string(),
-        deployment.status.to_string(),
+        Some(deployment.status.clone()),
         Some(deployment.clone()),
     )
 }
@@ -649,8 +1205,6 @@ pub async fn is_deployment_plan_in_progress(
     environment: &str,
     job_id: &str,
 ) -> (bool, String, Option<DeploymentResp>) {
-    let busy_statuses = ["requested", "initiated"]; // TODO: use enums
-
     let deployment = match handler
         .get_plan_deployment(deployment_id, environment, job_id)
         .await
@@ -665,7 +1219,7 @@ pub async fn is_deployment_plan_in_progress(
         }
     };
 
-    let in_progress = busy_statuses.contains(&deployment.status.as_str());
+    let in_progress = deployment.status.is_in_progress();
     let job_id = deployment.job_id.clone();
 
     (in_progress, job_id, Some(deployment.clone()))
@@ -732,6 +1286,63 @@ pub async fn check_module_deprecation(
     }
 }
 
+/// Compares the module version the deployment uses with the one it moves to and fails if the
+/// variables no longer fit, the remaining warnings are returned to be shown with the plan
+pub async fn check_module_upgrade(
+    handler: &GenericCloudHandler,
+    deployment: &DeploymentResp,
+    module_resp: &ModuleResp,
+    variables: &serde_json::Value,
+) -> Result<Vec<ModuleUpgradeWarning>, anyhow::Error> {
+    if deployment.deleted || deployment.module_version == module_resp.version {
+        return Ok(vec![]);
+    }
+    let is_stack = deployment.module_type == "stack";
+    let track = get_version_track(&deployment.module_version)
+        .map_err(|e| anyhow::anyhow!("Failed to get track from version: {}", e))?;
+    let current = if is_stack {
+        handler
+            .get_stack_version(&deployment.module, &track, &deployment.module_version)
+            .await
+    } else {
+        handler
+            .get_module_version(&deployment.module, &track, &deployment.module_version)
+            .await
+    };
+    let current = match current {
+        Ok(Some(current)) => current,
+        Ok(None) | Err(_) => {
+            warn!(
+                "Version {} of {} is not found, skipping upgrade checks of {}",
+                deployment.module_version, deployment.module, deployment.deployment_id
+            );
+            return Ok(vec![]);
+        }
+    };
+
+    let (errors, warnings): (Vec<ModuleUpgradeWarning>, Vec<ModuleUpgradeWarning>) =
+        get_module_upgrade_warnings(&current, module_resp, variables)
+            .into_iter()
+            .partition(|w| w.is_error());
+    if !errors.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Upgrading {} from {} to {} requires changes to the claim: {}",
+            deployment.deployment_id,
+            deployment.module_version,
+            module_resp.version,
+            errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<String>>()
+                .join("; ")
+        ));
+    }
+    for warning in warnings.iter() {
+        warn!("{}", warning);
+    }
+    Ok(warnings)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -755,6 +1366,97 @@ mod tests {
         assert_eq!(deployment.is_ok(), true);
     }
 
+    fn plan(status: &str, module_version: &str, variables: serde_json::Value) -> DeploymentResp {
+        serde_json::from_value(serde_json::json!({
+            "epoch": 0,
+            "deployment_id": "s3bucket/bucket1a",
+            "status": status,
+            "job_id": "plan-job",
+            "environment": "cli/default",
+            "project_id": "p",
+            "region": "eu-west-1",
+            "module": "s3bucket",
+            "module_version": module_version,
+            "module_type": "module",
+            "module_track": "stable",
+            "drift_detection": {},
+            "next_drift_check_epoch": -1,
+            "has_drifted": false,
+            "variables": variables,
+            "output": {},
+            "policy_results": [],
+            "error_text": "",
+            "deleted": false,
+            "dependencies": [],
+            "initiated_by": "user",
+            "cpu": "1024",
+            "memory": "2048",
+            "reference": "",
+            "tf_resources": null,
+        }))
+        .unwrap()
+    }
+
+    fn claim_payload(module_version: &str) -> ApiInfraPayloadWithVariables {
+        ApiInfraPayloadWithVariables {
+            payload: ApiInfraPayload {
+                command: "apply".to_string(),
+                flags: vec![],
+                module: "s3bucket".to_string(),
+                module_version: module_version.to_string(),
+                module_type: "module".to_string(),
+                module_track: "stable".to_string(),
+                name: "bucket1a".to_string(),
+                environment: "cli/default".to_string(),
+                deployment_id: "s3bucket/bucket1a".to_string(),
+                project_id: "p".to_string(),
+                region: "eu-west-1".to_string(),
+                drift_detection: serde_json::from_value(serde_json::json!({})).unwrap(),
+                next_drift_check_epoch: -1,
+                annotations: serde_json::json!({}),
+                dependencies: vec![],
+                initiated_by: "user".to_string(),
+                cpu: "1024".to_string(),
+                memory: "2048".to_string(),
+                reference: "".to_string(),
+                extra_data: ExtraData::None,
+                triggered_by: None,
+                expires_at_epoch: None,
+                from_plan: None,
+                imports: BTreeMap::new(),
+                upgrade_warnings: vec![],
+                labels: BTreeMap::new(),
+            },
+            variables: serde_json::json!({"bucket_name": "my-bucket"}),
+        }
+    }
+
+    #[test]
+    fn test_verify_plan_matches_claim() {
+        let variables = serde_json::json!({"bucket_name": "my-bucket"});
+        let claim = claim_payload("0.0.21");
+
+        let result =
+            verify_plan_matches_claim(&plan("successful", "0.0.21", variables.clone()), &claim);
+        assert_eq!(result.is_ok(), true);
+
+        let result =
+            verify_plan_matches_claim(&plan("failed_plan", "0.0.21", variables.clone()), &claim);
+        assert_eq!(result.is_ok(), false);
+
+        let result = verify_plan_matches_claim(&plan("successful", "0.0.20", variables), &claim);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Plan plan-job was made for version 0.0.20 of s3bucket, but the claim uses version 0.0.21"
+        );
+
+        let result = verify_plan_matches_claim(
+            &plan("successful", "0.0.21", serde_json::json!({"bucket_name": "other"})),
+            &claim,
+        );
+        assert_eq!(result.is_ok(), false);
+    }
+
     #[test]
     fn test_claim_missing_region() {
         let yaml_manifest = r#"
@@ -771,4 +1473,26 @@ mod tests {
             serde_yaml::from_str(yaml_manifest);
         assert_eq!(deployment.is_ok(), false);
     }
+
+    #[test]
+    fn test_get_claim_labels() {
+        let labels: serde_yaml::Mapping = serde_yaml::from_str(
+            r#"
+    team: payments
+    tier: 1
+    public: false
+    owners: [alice]
+    "#,
+        )
+        .unwrap();
+        assert_eq!(
+            get_claim_labels(&Some(labels)),
+            BTreeMap::from([
+                ("public".to_string(), "false".to_string()),
+                ("team".to_string(), "payments".to_string()),
+                ("tier".to_string(), "1".to_string()),
+            ])
+        );
+        assert!(get_claim_labels(&None).is_empty());
+    }
 }
@@ -166,6 +166,9 @@ pub async fn publish_module(
             variables: serde_yaml::Mapping::with_capacity(0),
             dependencies: None,
             drift_detection: None,
+            ttl: None,
+            expires_at: None,
+            imports: None,
         },
     };
     let module_call_builder = Body::builder()
@@ -1085,6 +1088,7 @@ mod tests {
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
             TfVariable {
                 name: "tags".to_string(),
@@ -1093,6 +1097,7 @@ mod tests {
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("map".to_string()),
+                validations: vec![],
             },
             TfVariable {
                 name: "port_mapping".to_string(),
@@ -1101,6 +1106,7 @@ mod tests {
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("list".to_string()),
+                validations: vec![],
             },
         ];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
@@ -1130,6 +1136,7 @@ port_mapping:
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
             TfVariable {
                 name: "bucket_name".to_string(),
@@ -1138,6 +1145,7 @@ port_mapping:
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
         ];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
@@ -1161,6 +1169,7 @@ bucket_name: some-bucket-name
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
             TfVariable {
                 name: "bucket_name".to_string(),
@@ -1169,6 +1178,7 @@ bucket_name: some-bucket-name
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
         ];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
@@ -1192,6 +1202,7 @@ bucket_name: some-bucket-name
                 sensitive: false,
                 nullable: true,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
             TfVariable {
                 name: "bucket_name".to_string(),
@@ -1200,6 +1211,7 @@ bucket_name: some-bucket-name
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
         ];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
@@ -1222,6 +1234,7 @@ bucket_name: some-bucket-name
             sensitive: false,
             nullable: false,
             _type: serde_json::Value::String("string".to_string()),
+            validations: vec![],
         }];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
             r#"
@@ -1248,6 +1261,7 @@ port_mapping:
             sensitive: false,
             nullable: false,
             _type: serde_json::Value::String("string".to_string()),
+            validations: vec![],
         }];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
             r#"
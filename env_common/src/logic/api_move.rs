@@ -0,0 +1,510 @@
+use std::collections::BTreeSet;
+
+use env_defs::{get_deployment_identifier, CloudProvider, Dependency, DeploymentResp, EventData};
+use env_utils::{get_epoch, get_timestamp, is_change_record_not_found};
+use log::{info, warn};
+
+use crate::interface::GenericCloudHandler;
+
+use super::api_change_record::put_infra_change_record;
+use super::api_deployment::set_deployment;
+use super::api_event::{copy_event, insert_event};
+use super::api_infra::{is_deployment_in_progress, validate_name};
+
+/// Change record types as used in the change record keys
+const CHANGE_RECORD_TYPES: [&str; 3] = ["PLAN", "APPLY", "DESTROY"];
+
+/// Checks that `deployment` can be moved to `new_deployment_id` in `new_environment`.
+/// The module cannot change and the new name and namespace follow the same rules as a claim.
+pub fn validate_move(
+    deployment: &DeploymentResp,
+    new_deployment_id: &str,
+    new_environment: &str,
+) -> Result<(), anyhow::Error> {
+    if deployment.deleted {
+        return Err(anyhow::anyhow!(
+            "Deployment {} in {} is deleted and cannot be moved",
+            deployment.deployment_id,
+            deployment.environment
+        ));
+    }
+    if deployment.deployment_id == new_deployment_id && deployment.environment == new_environment {
+        return Err(anyhow::anyhow!(
+            "Deployment {} is already in {}",
+            new_deployment_id,
+            new_environment
+        ));
+    }
+    let (module, _) = deployment
+        .deployment_id
+        .split_once('/')
+        .unwrap_or((deployment.deployment_id.as_str(), ""));
+    let new_name = match new_deployment_id.split_once('/') {
+        Some((new_module, new_name)) if new_module == module => new_name,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Deployment {} can only be moved to another {}/<name>, not {}",
+                deployment.deployment_id,
+                module,
+                new_deployment_id
+            ))
+        }
+    };
+    validate_name(new_name)?;
+    // The environment is <launcher>/<namespace>, see get_deployment_details
+    let namespace = new_environment
+        .split('/')
+        .skip(1)
+        .collect::<Vec<&str>>()
+        .join("/");
+    validate_name(&namespace)?;
+    Ok(())
+}
+
+/// Replaces the dependency on `from` with `to`, other dependencies are kept as they are
+pub fn rewrite_dependencies(
+    dependencies: &[Dependency],
+    from: &Dependency,
+    to: &Dependency,
+) -> Vec<Dependency> {
+    dependencies
+        .iter()
+        .map(|d| {
+            if d.project_id == from.project_id
+                && d.region == from.region
+                && d.deployment_id == from.deployment_id
+                && d.environment == from.environment
+            {
+                to.clone()
+            } else {
+                d.clone()
+            }
+        })
+        .collect()
+}
+
+fn get_name(deployment_id: &str) -> &str {
+    deployment_id
+        .split_once('/')
+        .map(|(_, name)| name)
+        .unwrap_or(deployment_id)
+}
+
+fn moved_event(event: &EventData, new_deployment_id: &str, new_environment: &str) -> EventData {
+    EventData {
+        deployment_id: new_deployment_id.to_string(),
+        environment: new_environment.to_string(),
+        name: get_name(new_deployment_id).to_string(),
+        ..event.clone()
+    }
+}
+
+fn move_event(deployment: &DeploymentResp, field: &str, other: &DeploymentResp) -> EventData {
+    let epoch = get_epoch();
+    EventData {
+        deployment_id: deployment.deployment_id.clone(),
+        project_id: deployment.project_id.clone(),
+        region: deployment.region.clone(),
+        environment: deployment.environment.clone(),
+        event: "move".to_string(),
+        epoch,
+        error_text: "".to_string(),
+        id: format!(
+            "{}-{}-{}-move-{}",
+            deployment.module, deployment.deployment_id, epoch, deployment.status
+        ),
+        job_id: deployment.job_id.clone(),
+        metadata: serde_json::json!({
+            field: {
+                "deployment_id": other.deployment_id,
+                "environment": other.environment,
+            }
+        }),
+        drift_detection: deployment.drift_detection.clone(),
+        next_drift_check_epoch: deployment.next_drift_check_epoch,
+        has_drifted: deployment.has_drifted,
+        module: deployment.module.clone(),
+        name: get_name(&deployment.deployment_id).to_string(),
+        status: deployment.status.clone(),
+        timestamp: get_timestamp(),
+        output: deployment.output.clone(),
+        policy_results: deployment.policy_results.clone(),
+        initiated_by: deployment.initiated_by.clone(),
+        event_duration: 0,
+    }
+}
+
+/// Moves a deployment to a new deployment id and/or environment without recreating its resources.
+///
+/// Events and change records are copied first, then the Terraform state is moved, the deployment
+/// is stored under the new identifier and deployments depending on it are pointed to it. If storing
+/// the deployment fails the state is moved back. Events and change records under the old identifier
+/// are kept as history together with a `move` event.
+pub async fn move_deployment(
+    handler: &GenericCloudHandler,
+    deployment_id: &str,
+    environment: &str,
+    new_deployment_id: &str,
+    new_environment: &str,
+) -> Result<DeploymentResp, anyhow::Error> {
+    let (in_progress, job_id, _, deployment) =
+        is_deployment_in_progress(handler, deployment_id, environment, false, false).await;
+    if in_progress {
+        return Err(anyhow::anyhow!(
+            "Deployment {} in {} has a job in progress ({}), wait for it to finish before moving it",
+            deployment_id,
+            environment,
+            job_id
+        ));
+    }
+    let deployment = match deployment {
+        Some(deployment) => deployment,
+        None => {
+            return Err(anyhow::anyhow!(
+                "Deployment {} not found in {}",
+                deployment_id,
+                environment
+            ))
+        }
+    };
+    validate_move(&deployment, new_deployment_id, new_environment)?;
+    if handler
+        .get_deployment(new_deployment_id, new_environment, false)
+        .await?
+        .is_some()
+    {
+        return Err(anyhow::anyhow!(
+            "Deployment {} already exists in {}",
+            new_deployment_id,
+            new_environment
+        ));
+    }
+
+    let events = handler.get_events(deployment_id, environment).await?;
+    for event in events.iter() {
+        copy_event(
+            handler,
+            &moved_event(event, new_deployment_id, new_environment),
+        )
+        .await?;
+    }
+
+    let job_ids: BTreeSet<&str> = events.iter().map(|e| e.job_id.as_str()).collect();
+    for job_id in job_ids {
+        for change_type in CHANGE_RECORD_TYPES {
+            match handler
+                .get_change_record(environment, deployment_id, job_id, change_type)
+                .await
+            {
+                Ok(mut change_record) => {
+                    change_record.deployment_id = new_deployment_id.to_string();
+                    change_record.environment = new_environment.to_string();
+                    put_infra_change_record(handler, &change_record).await?;
+                }
+                // Most jobs only have one type of change record, so missing ones are expected
+                Err(e) if is_change_record_not_found(&e) => {}
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "Failed to get the {} change record of job {}: {}",
+                        change_type,
+                        job_id,
+                        e
+                    ))
+                }
+            }
+        }
+    }
+
+    handler
+        .move_state_file(
+            environment,
+            deployment_id,
+            new_environment,
+            new_deployment_id,
+        )
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to move the state of {}: {}", deployment_id, e))?;
+    info!(
+        "Moved state of {} in {} to {} in {}",
+        deployment_id, environment, new_deployment_id, new_environment
+    );
+
+    let moved = DeploymentResp {
+        deployment_id: new_deployment_id.to_string(),
+        environment: new_environment.to_string(),
+        ..deployment.clone()
+    };
+    if let Err(e) = store_moved_deployment(handler, &deployment, &moved).await {
+        // Put the state back so the deployment keeps working where it was
+        return match handler
+            .move_state_file(
+                new_environment,
+                new_deployment_id,
+                environment,
+                deployment_id,
+            )
+            .await
+        {
+            Ok(_) => Err(e),
+            Err(undo_error) => Err(anyhow::anyhow!(
+                "{}, and failed to move the state back to {} in {}: {}",
+                e,
+                deployment_id,
+                environment,
+                undo_error
+            )),
+        };
+    }
+
+    insert_event(handler, move_event(&deployment, "moved_to", &moved)).await?;
+    insert_event(handler, move_event(&moved, "moved_from", &deployment)).await?;
+
+    Ok(moved)
+}
+
+/// Stores `moved` in place of `deployment`, points the dependents to it and removes `deployment`
+/// together with the links to it from its dependencies
+async fn store_moved_deployment(
+    handler: &GenericCloudHandler,
+    deployment: &DeploymentResp,
+    moved: &DeploymentResp,
+) -> Result<(), anyhow::Error> {
+    set_deployment(handler, moved, false).await?;
+
+    let from = Dependency {
+        project_id: deployment.project_id.clone(),
+        region: deployment.region.clone(),
+        deployment_id: deployment.deployment_id.clone(),
+        environment: deployment.environment.clone(),
+    };
+    let to = Dependency {
+        deployment_id: moved.deployment_id.clone(),
+        environment: moved.environment.clone(),
+        ..from.clone()
+    };
+    for dependent in handler
+        .get_dependents(&deployment.deployment_id, &deployment.environment)
+        .await?
+    {
+        match handler
+            .get_deployment(&dependent.dependent_id, &dependent.environment, false)
+            .await?
+        {
+            Some(mut dependent_deployment) => {
+                dependent_deployment.dependencies =
+                    rewrite_dependencies(&dependent_deployment.dependencies, &from, &to);
+                set_deployment(handler, &dependent_deployment, false).await?;
+            }
+            None => warn!(
+                "Dependent {} in {} not found, skipping it",
+                dependent.dependent_id, dependent.environment
+            ),
+        }
+    }
+
+    // Remove the old deployment and the links to it from its dependencies
+    let deployment_table_placeholder = "deployments";
+    let old_identifier = get_deployment_identifier(
+        &deployment.project_id,
+        &deployment.region,
+        &deployment.deployment_id,
+        &deployment.environment,
+    );
+    let mut transaction_items = vec![serde_json::json!({
+        "Delete": {
+            "TableName": deployment_table_placeholder,
+            "Key": {
+                "PK": format!("DEPLOYMENT#{}", old_identifier),
+                "SK": "METADATA",
+            }
+        }
+    })];
+    for dependency in deployment.dependencies.iter() {
+        transaction_items.push(serde_json::json!({
+            "Delete": {
+                "TableName": deployment_table_placeholder,
+                "Key": {
+                    "PK": format!(
+                        "DEPLOYMENT#{}",
+                        get_deployment_identifier(
+                            &dependency.project_id,
+                            &dependency.region,
+                            &dependency.deployment_id,
+                            &dependency.environment
+                        )
+                    ),
+                    "SK": format!("DEPENDENT#{}", old_identifier),
+                }
+            }
+        }));
+    }
+    let payload = serde_json::json!({
+        "event": "transact_write",
+        "items": transaction_items,
+    });
+    handler
+        .run_function(&payload)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to remove the old deployment: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn deployment(deployment_id: &str, environment: &str, deleted: bool) -> DeploymentResp {
+        serde_json::from_value(serde_json::json!({
+            "epoch": 0,
+            "deployment_id": deployment_id,
+            "status": "successful",
+            "job_id": "job",
+            "environment": environment,
+            "project_id": "p",
+            "region": "r",
+            "module": "s3bucket",
+            "module_version": "1.0.0",
+            "module_type": "module",
+            "module_track": "stable",
+            "drift_detection": {},
+            "next_drift_check_epoch": -1,
+            "has_drifted": false,
+            "variables": {},
+            "output": {},
+            "policy_results": [],
+            "error_text": "",
+            "deleted": deleted,
+            "dependencies": [],
+            "initiated_by": "user",
+            "cpu": "1024",
+            "memory": "2048",
+            "reference": "",
+            "tf_resources": null,
+        }))
+        .unwrap()
+    }
+
+    fn dependency(deployment_id: &str, environment: &str) -> Dependency {
+        Dependency {
+            project_id: "p".to_string(),
+            region: "r".to_string(),
+            deployment_id: deployment_id.to_string(),
+            environment: environment.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_move() {
+        let bucket = deployment("s3bucket/bucket", "cli/dev", false);
+        assert!(validate_move(&bucket, "s3bucket/new-bucket", "cli/dev").is_ok());
+        assert!(validate_move(&bucket, "s3bucket/bucket", "cli/prod").is_ok());
+
+        assert!(validate_move(&bucket, "s3bucket/bucket", "cli/dev").is_err());
+        assert!(validate_move(&bucket, "vpc/bucket", "cli/dev").is_err());
+        assert!(validate_move(&bucket, "s3bucket/New_Bucket", "cli/dev").is_err());
+        assert!(validate_move(&bucket, "s3bucket/bucket", "cli").is_err());
+
+        let deleted = deployment("s3bucket/bucket", "cli/dev", true);
+        assert!(validate_move(&deleted, "s3bucket/new-bucket", "cli/dev").is_err());
+    }
+
+    #[test]
+    fn test_rewrite_dependencies() {
+        let dependencies = vec![
+            dependency("s3bucket/bucket", "cli/dev"),
+            dependency("s3bucket/bucket", "cli/prod"),
+            dependency("vpc/network", "cli/dev"),
+        ];
+        let rewritten = rewrite_dependencies(
+            &dependencies,
+            &dependency("s3bucket/bucket", "cli/dev"),
+            &dependency("s3bucket/new-bucket", "cli/test"),
+        );
+        let ids: Vec<(&str, &str)> = rewritten
+            .iter()
+            .map(|d| (d.deployment_id.as_str(), d.environment.as_str()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                ("s3bucket/new-bucket", "cli/test"),
+                ("s3bucket/bucket", "cli/prod"),
+                ("vpc/network", "cli/dev"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_deployment_moves_change_records() {
+        let dir = tempfile::tempdir().unwrap();
+        // SAFETY: no other test in this crate reads these variables
+        unsafe {
+            std::env::set_var("PROVIDER", "local");
+            std::env::set_var("INFRAWEAVE_LOCAL_DIR", dir.path());
+        }
+        let handler = GenericCloudHandler::workload("p", "r").await;
+
+        let bucket = deployment("s3bucket/bucket", "cli/dev", false);
+        set_deployment(&handler, &bucket, false).await.unwrap();
+        insert_event(&handler, move_event(&bucket, "created", &bucket))
+            .await
+            .unwrap();
+        let change_record: env_defs::InfraChangeRecord =
+            serde_json::from_value(serde_json::json!({
+                "deployment_id": "s3bucket/bucket",
+                "project_id": "p",
+                "region": "r",
+                "job_id": "job",
+                "module": "s3bucket",
+                "environment": "cli/dev",
+                "change_type": "plan",
+                "module_version": "1.0.0",
+                "epoch": 0,
+                "timestamp": "",
+                "plan_std_output": "",
+                "plan_raw_json_key": "",
+            }))
+            .unwrap();
+        put_infra_change_record(&handler, &change_record)
+            .await
+            .unwrap();
+        let state = env_local::get_state_path(
+            &handler.get_storage_basepath(),
+            "cli/dev",
+            "s3bucket/bucket",
+        );
+        std::fs::create_dir_all(state.parent().unwrap()).unwrap();
+        std::fs::write(&state, "{}").unwrap();
+
+        move_deployment(
+            &handler,
+            "s3bucket/bucket",
+            "cli/dev",
+            "s3bucket/new-bucket",
+            "cli/test",
+        )
+        .await
+        .unwrap();
+
+        let moved = handler
+            .get_change_record("cli/test", "s3bucket/new-bucket", "job", "PLAN")
+            .await
+            .unwrap();
+        assert_eq!(
+            (moved.deployment_id.as_str(), moved.environment.as_str()),
+            ("s3bucket/new-bucket", "cli/test")
+        );
+        assert!(handler
+            .get_deployment("s3bucket/new-bucket", "cli/test", false)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(handler
+            .get_deployment("s3bucket/bucket", "cli/dev", false)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!state.exists());
+    }
+}
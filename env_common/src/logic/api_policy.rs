@@ -6,42 +6,56 @@ use env_defs::{
     get_policy_identifier, CloudProvider, GenericFunctionResponse, PolicyManifest, PolicyResp,
 };
 use env_utils::{
-    get_timestamp, merge_json_dicts, semver_parse, validate_policy_schema, zero_pad_semver,
+    get_epoch, get_timestamp, merge_json_dicts, read_rego_files, semver_parse,
+    validate_policy_schema, validate_policy_waivers, validate_rego_files, zero_pad_semver,
 };
 
 use crate::interface::GenericCloudHandler;
 
+/// Publishes the policy in `manifest_path`, its `policy.yaml` and `.rego` files, to `environment`.
+/// `reference` and `description` override the ones in the manifest
 pub async fn publish_policy(
     handler: &GenericCloudHandler,
     manifest_path: &str,
     environment: &str,
-) -> anyhow::Result<(), anyhow::Error> {
+    reference: Option<&str>,
+    description: Option<&str>,
+) -> anyhow::Result<PolicyResp, anyhow::Error> {
     let policy_yaml_path = Path::new(&manifest_path).join("policy.yaml");
-    let manifest =
-        std::fs::read_to_string(&policy_yaml_path).expect("Failed to read policy manifest file");
+    let manifest = std::fs::read_to_string(&policy_yaml_path).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read policy manifest {}: {}",
+            policy_yaml_path.display(),
+            e
+        )
+    })?;
 
-    let policy_yaml =
-        serde_yaml::from_str::<PolicyManifest>(&manifest).expect("Failed to parse policy manifest");
+    let mut policy_yaml = serde_yaml::from_str::<PolicyManifest>(&manifest)
+        .map_err(|e| anyhow::anyhow!("Failed to parse policy manifest: {}", e))?;
+    validate_policy_schema(&manifest)
+        .map_err(|e| anyhow::anyhow!("Invalid policy manifest: {}", e))?;
+    if !policy_yaml.spec.waivers.is_empty() {
+        let published_by = handler.get_user_id().await?;
+        validate_policy_waivers(&policy_yaml.spec.waivers, &published_by, get_epoch())?;
+    }
+    if let Some(reference) = reference {
+        policy_yaml.spec.reference = reference.to_string();
+    }
+    if let Some(description) = description {
+        policy_yaml.spec.description = description.to_string();
+    }
+
+    let rego_files = read_rego_files(Path::new(manifest_path))?;
+    let packages = validate_rego_files(&rego_files)?;
+    println!("Found policy packages: {}", packages.join(", "));
 
     let zip_file = env_utils::get_zip_file(Path::new(manifest_path), &policy_yaml_path).await?;
     // Encode the zip file content to Base64
     let zip_base64 = base64.encode(&zip_file);
 
-    match validate_policy_schema(&manifest) {
-        std::result::Result::Ok(_) => (),
-        Err(error) => {
-            println!("{}", error);
-            std::process::exit(1);
-        }
-    }
-
     let policy = PolicyResp {
         environment: environment.to_string(),
-        environment_version: format!(
-            "{}#{}",
-            environment,
-            zero_pad_semver(policy_yaml.spec.version.as_str(), 3).unwrap()
-        ),
+        environment_version: get_environment_version(environment, &policy_yaml.spec.version)?,
         version: policy_yaml.spec.version.clone(),
         timestamp: get_timestamp(),
         policy: policy_yaml.metadata.name.clone(),
@@ -56,73 +70,126 @@ pub async fn publish_policy(
         ), // s3_key -> "{policy}/{policy}-{version}.zip"
     };
 
-    if let Ok(latest_policy) = handler
-        .get_newest_policy_version(&policy.policy, environment)
+    verify_newest_policy_version(handler, &policy).await?;
+
+    upload_file_base64(handler, &policy.s3_key, &zip_base64).await?;
+    println!("Successfully uploaded policy zip file to S3");
+
+    insert_policy(handler, &policy).await?;
+    println!(
+        "Published version {} of policy {} to environment {}",
+        policy.version, policy.policy, environment
+    );
+
+    Ok(policy)
+}
+
+/// Publishes `version` of `policy` from `from_environment` to `to_environment`, using the zip file
+/// that was uploaded when the version was published
+pub async fn promote_policy(
+    handler: &GenericCloudHandler,
+    policy: &str,
+    version: &str,
+    from_environment: &str,
+    to_environment: &str,
+) -> anyhow::Result<PolicyResp, anyhow::Error> {
+    let source = handler
+        .get_policy(policy, from_environment, version)
         .await
-    {
-        let manifest_version = semver_parse(&policy.version).unwrap();
-        let latest_version = semver_parse(&latest_policy.version).unwrap();
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Version {} of policy {} is not published in environment {}: {}",
+                version,
+                policy,
+                from_environment,
+                e
+            )
+        })?;
 
-        if manifest_version == latest_version {
-            println!(
-                "Policy version {} already exists in environment {}",
-                manifest_version, environment
-            );
-            return Err(anyhow::anyhow!(
-                "Policy version {} already exists in environment {}",
-                manifest_version,
-                environment
-            ));
-        } else if manifest_version <= latest_version {
-            println!(
-                "Policy version {} is older than the latest version {} in environment {}",
-                manifest_version, latest_version, environment
-            );
-            return Err(anyhow::anyhow!(
-                "Policy version {} is older than the latest version {} in environment {}",
-                manifest_version,
-                latest_version,
-                environment
-            ));
-        } else {
-            println!(
-                "Policy version {} is confirmed to be the newest version",
-                manifest_version
-            );
-        }
-    } else {
-        println!(
-            "No policy found with policy: {} and environment: {}",
-            &policy.policy, &environment
-        );
-        println!("Creating new policy version");
-    }
+    let promoted = PolicyResp {
+        environment: to_environment.to_string(),
+        environment_version: get_environment_version(to_environment, &source.version)?,
+        timestamp: get_timestamp(),
+        ..source
+    };
 
-    match upload_file_base64(handler, &policy.s3_key, &zip_base64).await {
-        Ok(_) => {
-            println!("Successfully uploaded policy zip file to S3");
-        }
-        Err(error) => {
-            println!("{}", error);
-            std::process::exit(1);
-        }
+    verify_newest_policy_version(handler, &promoted).await?;
+
+    insert_policy(handler, &promoted).await?;
+    println!(
+        "Promoted version {} of policy {} from environment {} to {}",
+        promoted.version, promoted.policy, from_environment, to_environment
+    );
+
+    Ok(promoted)
+}
+
+fn get_environment_version(environment: &str, version: &str) -> anyhow::Result<String> {
+    let padded_version = zero_pad_semver(version, 3)
+        .map_err(|e| anyhow::anyhow!("Invalid policy version {}: {}", version, e))?;
+    Ok(format!("{}#{}", environment, padded_version))
+}
+
+/// Fails unless `version` is newer than `latest_version`, the newest version in `environment`
+fn verify_newer_version(
+    version: &str,
+    latest_version: Option<&str>,
+    environment: &str,
+) -> anyhow::Result<()> {
+    let version = semver_parse(version)
+        .map_err(|e| anyhow::anyhow!("Invalid policy version {}: {}", version, e))?;
+    let Some(latest_version) = latest_version else {
+        return Ok(());
+    };
+    let latest_version = semver_parse(latest_version)
+        .map_err(|e| anyhow::anyhow!("Invalid policy version {}: {}", latest_version, e))?;
+
+    if version == latest_version {
+        Err(anyhow::anyhow!(
+            "Policy version {} already exists in environment {}",
+            version,
+            environment
+        ))
+    } else if version < latest_version {
+        Err(anyhow::anyhow!(
+            "Policy version {} is older than the latest version {} in environment {}",
+            version,
+            latest_version,
+            environment
+        ))
+    } else {
+        Ok(())
     }
+}
 
-    match insert_policy(handler, &policy).await {
-        Ok(_) => {
-            println!("Successfully published policy {}", policy.policy);
+async fn verify_newest_policy_version(
+    handler: &GenericCloudHandler,
+    policy: &PolicyResp,
+) -> anyhow::Result<()> {
+    match handler
+        .get_newest_policy_version(&policy.policy, &policy.environment)
+        .await
+    {
+        Ok(latest_policy) => {
+            verify_newer_version(
+                &policy.version,
+                Some(&latest_policy.version),
+                &policy.environment,
+            )?;
+            println!(
+                "Policy version {} is confirmed to be the newest version",
+                policy.version
+            );
         }
-        Err(error) => {
-            println!("{}", error);
-            std::process::exit(1);
+        Err(_) => {
+            verify_newer_version(&policy.version, None, &policy.environment)?;
+            println!(
+                "No policy found with policy: {} and environment: {}",
+                &policy.policy, &policy.environment
+            );
+            println!("Creating new policy version");
         }
     }
-
-    println!(
-        "Publishing version {} of policy {}",
-        policy.version, policy.policy
-    );
-
     Ok(())
 }
 
@@ -212,3 +279,37 @@ async fn insert_policy<T: CloudProvider>(
         Err(e) => Err(anyhow::anyhow!("Failed to insert policy: {}", e)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_verify_newer_version() {
+        assert!(verify_newer_version("0.1.0", None, "cli/default").is_ok());
+        assert!(verify_newer_version("0.2.0", Some("0.1.9"), "cli/default").is_ok());
+        assert_eq!(
+            verify_newer_version("0.1.0", Some("0.1.0"), "cli/default")
+                .unwrap_err()
+                .to_string(),
+            "Policy version 0.1.0 already exists in environment cli/default"
+        );
+        assert_eq!(
+            verify_newer_version("0.1.0", Some("0.2.0"), "cli/prod")
+                .unwrap_err()
+                .to_string(),
+            "Policy version 0.1.0 is older than the latest version 0.2.0 in environment cli/prod"
+        );
+        assert!(verify_newer_version("latest", None, "cli/default").is_err());
+    }
+
+    #[test]
+    fn test_get_environment_version() {
+        assert_eq!(
+            get_environment_version("cli/default", "1.2.3").unwrap(),
+            "cli/default#001.002.003"
+        );
+        assert!(get_environment_version("cli/default", "1.2").is_err());
+    }
+}
@@ -0,0 +1,417 @@
+use env_defs::{get_rollout_identifier, CloudProvider, DeploymentResp};
+use env_utils::{get_timestamp, get_version_track, matches_pattern, semver_parse};
+use serde::{Deserialize, Serialize};
+
+use crate::interface::GenericCloudHandler;
+
+pub const DEFAULT_ROLLOUT_CANARY_SIZE: usize = 1;
+pub const DEFAULT_ROLLOUT_BATCH_SIZE: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutStepStatus {
+    /// Not planned on the new version yet
+    Pending,
+    /// Planned on the new version without destructive changes
+    Planned,
+    PlanFailed,
+    /// The plan deletes or replaces resources
+    Destructive,
+    Applied,
+    ApplyFailed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RolloutState {
+    InProgress,
+    /// The rollout cannot continue until the listed deployments are fixed (or destructive changes are allowed)
+    Stopped(Vec<String>),
+    Completed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RolloutDeployment {
+    pub deployment_id: String,
+    pub environment: String,
+    pub region: String,
+    pub from_version: String,
+    pub batch: usize,
+    pub status: RolloutStepStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    /// The plan on the new version that was checked for destructive changes, which is the plan
+    /// that is applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_job_id: Option<String>,
+    /// `<action>: <address>` of the resources deleted or replaced by the plan
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub destructive_changes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Upgrade of every deployment of a module to a new version, applied in batches with a canary
+/// batch first. Stored between runs so a stopped rollout can be resumed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rollout {
+    pub module: String,
+    pub to_version: String,
+    pub project_id: String,
+    pub region: String,
+    /// Environment patterns in the order they are rolled out, `*` matches any characters.
+    /// Deployments in environments matching none of them follow, ordered by environment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub environment_order: Vec<String>,
+    pub canary_size: usize,
+    pub batch_size: usize,
+    pub allow_destructive: bool,
+    pub started_at: String,
+    pub deployments: Vec<RolloutDeployment>,
+}
+
+/// Batch of the deployment at `position`, the first `canary_size` deployments form the first batch
+pub fn get_rollout_batch(position: usize, canary_size: usize, batch_size: usize) -> usize {
+    let batch_size = batch_size.max(1);
+    if position < canary_size {
+        0
+    } else {
+        let first_batch = if canary_size > 0 { 1 } else { 0 };
+        first_batch + (position - canary_size) / batch_size
+    }
+}
+
+/// Whether `version` is older than `to_version`, versions that cannot be parsed are not
+fn is_older_version(version: &str, to_version: &str) -> bool {
+    match (semver_parse(version), semver_parse(to_version)) {
+        (Ok(version), Ok(to_version)) => version < to_version,
+        _ => false,
+    }
+}
+
+/// Position of the first pattern in `environment_order` matching `environment`, environments
+/// matching none of them come last
+fn get_environment_position(environment_order: &[String], environment: &str) -> usize {
+    environment_order
+        .iter()
+        .position(|pattern| matches_pattern(pattern, environment))
+        .unwrap_or(environment_order.len())
+}
+
+impl Rollout {
+    /// Only deployments on the track of `to_version` with an older version are rolled out. They
+    /// are ordered by `environment_order`, then by environment and deployment id
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        module: &str,
+        to_version: &str,
+        project_id: &str,
+        region: &str,
+        deployments: &[DeploymentResp],
+        environment_order: &[String],
+        canary_size: usize,
+        batch_size: usize,
+        allow_destructive: bool,
+    ) -> Result<Self, anyhow::Error> {
+        let track = get_version_track(to_version)
+            .map_err(|e| anyhow::anyhow!("Invalid version {}: {}", to_version, e))?;
+        let mut deployments: Vec<&DeploymentResp> = deployments
+            .iter()
+            .filter(|d| d.module_track == track && is_older_version(&d.module_version, to_version))
+            .collect();
+        deployments.sort_by_key(|d| {
+            (
+                get_environment_position(environment_order, &d.environment),
+                d.environment.clone(),
+                d.deployment_id.clone(),
+            )
+        });
+        Ok(Rollout {
+            module: module.to_string(),
+            to_version: to_version.to_string(),
+            project_id: project_id.to_string(),
+            region: region.to_string(),
+            environment_order: environment_order.to_vec(),
+            canary_size,
+            batch_size,
+            allow_destructive,
+            started_at: get_timestamp(),
+            deployments: deployments
+                .iter()
+                .enumerate()
+                .map(|(position, d)| RolloutDeployment {
+                    deployment_id: d.deployment_id.clone(),
+                    environment: d.environment.clone(),
+                    region: d.region.clone(),
+                    from_version: d.module_version.clone(),
+                    batch: get_rollout_batch(position, canary_size, batch_size),
+                    status: RolloutStepStatus::Pending,
+                    job_id: None,
+                    plan_job_id: None,
+                    destructive_changes: vec![],
+                    error: None,
+                })
+                .collect(),
+        })
+    }
+
+    pub fn batch_count(&self) -> usize {
+        self.deployments
+            .iter()
+            .map(|d| d.batch + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_canary_batch(&self, batch: usize) -> bool {
+        batch == 0 && self.canary_size > 0
+    }
+
+    /// Deployments that need a (new) plan before they can be applied
+    pub fn needs_plan(&self, deployment: &RolloutDeployment) -> bool {
+        match deployment.status {
+            RolloutStepStatus::Pending
+            | RolloutStepStatus::PlanFailed
+            | RolloutStepStatus::ApplyFailed => true,
+            RolloutStepStatus::Destructive => !self.allow_destructive,
+            RolloutStepStatus::Planned | RolloutStepStatus::Applied => false,
+        }
+    }
+
+    pub fn is_ready_to_apply(&self, deployment: &RolloutDeployment) -> bool {
+        match deployment.status {
+            RolloutStepStatus::Planned => true,
+            RolloutStepStatus::Destructive => self.allow_destructive,
+            _ => false,
+        }
+    }
+
+    /// The first batch with deployments that are not applied yet
+    pub fn next_batch(&self) -> Option<usize> {
+        self.deployments
+            .iter()
+            .filter(|d| d.status != RolloutStepStatus::Applied)
+            .map(|d| d.batch)
+            .min()
+    }
+
+    pub fn state(&self) -> RolloutState {
+        let blocking: Vec<String> = self
+            .deployments
+            .iter()
+            .filter(|d| match d.status {
+                RolloutStepStatus::PlanFailed | RolloutStepStatus::ApplyFailed => true,
+                RolloutStepStatus::Destructive => !self.allow_destructive,
+                _ => false,
+            })
+            .map(|d| format!("{} ({})", d.deployment_id, d.environment))
+            .collect();
+        if !blocking.is_empty() {
+            RolloutState::Stopped(blocking)
+        } else if self.next_batch().is_none() {
+            RolloutState::Completed
+        } else {
+            RolloutState::InProgress
+        }
+    }
+}
+
+const ROLLOUT_SK: &str = "ROLLOUT";
+
+/// The latest rollout of `module` in the project and region of the handler
+pub async fn get_rollout(
+    handler: &GenericCloudHandler,
+    module: &str,
+) -> Result<Option<Rollout>, anyhow::Error> {
+    match handler.get_rollout(module).await? {
+        Some(item) => serde_json::from_value(item["data"].clone())
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("Failed to parse rollout of {}: {}", module, e)),
+        None => Ok(None),
+    }
+}
+
+/// Stores the rollout in place of the previous rollout of the module, so it can be followed and
+/// resumed from anywhere
+pub async fn save_rollout(
+    handler: &GenericCloudHandler,
+    rollout: &Rollout,
+) -> Result<(), anyhow::Error> {
+    let payload = serde_json::json!({
+        "event": "insert_db",
+        "table": "deployments",
+        "data": {
+            "PK": format!(
+                "ROLLOUT#{}",
+                get_rollout_identifier(&rollout.project_id, &rollout.region, &rollout.module)
+            ),
+            "SK": ROLLOUT_SK,
+            "data": rollout,
+        }
+    });
+    match handler.run_function(&payload).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow::anyhow!("Failed to store rollout: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn deployment(deployment_id: &str, environment: &str, module_version: &str) -> DeploymentResp {
+        let module_track = get_version_track(module_version).unwrap();
+        serde_json::from_value(serde_json::json!({
+            "epoch": 0,
+            "deployment_id": deployment_id,
+            "status": "successful",
+            "job_id": "job",
+            "environment": environment,
+            "project_id": "p",
+            "region": "r",
+            "module": "s3bucket",
+            "module_version": module_version,
+            "module_type": "module",
+            "module_track": module_track,
+            "drift_detection": {},
+            "next_drift_check_epoch": -1,
+            "has_drifted": false,
+            "variables": {},
+            "output": {},
+            "policy_results": [],
+            "error_text": "",
+            "deleted": false,
+            "dependencies": [],
+            "initiated_by": "user",
+            "cpu": "1024",
+            "memory": "2048",
+            "reference": "",
+            "tf_resources": null,
+        }))
+        .unwrap()
+    }
+
+    fn rollout_in_order(
+        environment_order: &[&str],
+        canary_size: usize,
+        batch_size: usize,
+    ) -> Rollout {
+        Rollout::new(
+            "s3bucket",
+            "1.1.0",
+            "p",
+            "r",
+            &[
+                deployment("s3bucket/c", "prod", "1.0.0"),
+                deployment("s3bucket/a", "dev", "1.0.0"),
+                deployment("s3bucket/b", "dev", "1.1.0"),
+                deployment("s3bucket/d", "prod", "0.9.0"),
+                deployment("s3bucket/e", "test", "1.0.0"),
+                deployment("s3bucket/f", "dev", "1.2.0"),
+                deployment("s3bucket/g", "dev", "1.0.0-beta"),
+            ],
+            &environment_order
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            canary_size,
+            batch_size,
+            false,
+        )
+        .unwrap()
+    }
+
+    fn rollout(canary_size: usize, batch_size: usize) -> Rollout {
+        rollout_in_order(&[], canary_size, batch_size)
+    }
+
+    fn batches(rollout: &Rollout) -> Vec<(&str, usize)> {
+        rollout
+            .deployments
+            .iter()
+            .map(|d| (d.deployment_id.as_str(), d.batch))
+            .collect()
+    }
+
+    #[test]
+    fn test_get_rollout_batch() {
+        let batches: Vec<usize> = (0..6).map(|p| get_rollout_batch(p, 1, 2)).collect();
+        assert_eq!(batches, vec![0, 1, 1, 2, 2, 3]);
+        let batches: Vec<usize> = (0..4).map(|p| get_rollout_batch(p, 0, 2)).collect();
+        assert_eq!(batches, vec![0, 0, 1, 1]);
+        let batches: Vec<usize> = (0..3).map(|p| get_rollout_batch(p, 2, 0)).collect();
+        assert_eq!(batches, vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_new_rollout_in_environment_order() {
+        let rollout = rollout_in_order(&["prod*", "dev"], 1, 2);
+        assert_eq!(
+            batches(&rollout),
+            vec![
+                ("s3bucket/c", 0),
+                ("s3bucket/d", 1),
+                ("s3bucket/a", 1),
+                ("s3bucket/e", 2),
+            ]
+        );
+        assert!(Rollout::new("s3bucket", "latest", "p", "r", &[], &[], 1, 2, false).is_err());
+    }
+
+    #[test]
+    fn test_new_rollout_only_upgrades_older_versions_on_track() {
+        let rollout = rollout(1, 2);
+        assert_eq!(
+            batches(&rollout),
+            vec![
+                ("s3bucket/a", 0),
+                ("s3bucket/c", 1),
+                ("s3bucket/d", 1),
+                ("s3bucket/e", 2),
+            ]
+        );
+        assert_eq!(rollout.batch_count(), 3);
+        assert_eq!(rollout.is_canary_batch(0), true);
+        assert_eq!(rollout.is_canary_batch(1), false);
+        assert_eq!(rollout.next_batch(), Some(0));
+        assert_eq!(rollout.state(), RolloutState::InProgress);
+    }
+
+    #[test]
+    fn test_rollout_progress() {
+        let mut rollout = rollout(1, 2);
+        rollout.deployments[0].status = RolloutStepStatus::Applied;
+        rollout.deployments[1].status = RolloutStepStatus::Planned;
+        assert_eq!(rollout.next_batch(), Some(1));
+
+        rollout.deployments[2].status = RolloutStepStatus::Destructive;
+        assert_eq!(
+            rollout.state(),
+            RolloutState::Stopped(vec!["s3bucket/d (prod)".to_string()])
+        );
+        assert_eq!(rollout.needs_plan(&rollout.deployments[2]), true);
+
+        rollout.allow_destructive = true;
+        assert_eq!(rollout.state(), RolloutState::InProgress);
+        assert_eq!(rollout.needs_plan(&rollout.deployments[2]), false);
+        assert_eq!(rollout.is_ready_to_apply(&rollout.deployments[2]), true);
+
+        for deployment in rollout.deployments.iter_mut() {
+            deployment.status = RolloutStepStatus::Applied;
+        }
+        assert_eq!(rollout.next_batch(), None);
+        assert_eq!(rollout.state(), RolloutState::Completed);
+    }
+
+    #[test]
+    fn test_rollout_stops_on_failure() {
+        let mut rollout = rollout(1, 2);
+        rollout.deployments[0].status = RolloutStepStatus::ApplyFailed;
+        assert_eq!(
+            rollout.state(),
+            RolloutState::Stopped(vec!["s3bucket/a (dev)".to_string()])
+        );
+        assert_eq!(rollout.needs_plan(&rollout.deployments[0]), true);
+        assert_eq!(rollout.is_ready_to_apply(&rollout.deployments[0]), false);
+    }
+}
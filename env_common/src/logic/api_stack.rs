@@ -1847,6 +1847,7 @@ mod tests {
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
             TfVariable {
                 name: "bucket1a__tags".to_string(),
@@ -1855,6 +1856,7 @@ mod tests {
                 sensitive: false,
                 nullable: true,
                 _type: serde_json::Value::String("map".to_string()),
+                validations: vec![],
             },
             TfVariable {
                 name: "bucket2__port_mapping".to_string(),
@@ -1863,6 +1865,7 @@ mod tests {
                 sensitive: false,
                 nullable: true,
                 _type: serde_json::Value::String("list".to_string()),
+                validations: vec![],
             },
         ];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
@@ -1890,6 +1893,7 @@ mod tests {
             sensitive: false,
             nullable: false,
             _type: serde_json::Value::String("string".to_string()),
+            validations: vec![],
         }];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
             r#"
@@ -1913,6 +1917,7 @@ mod tests {
                 sensitive: false,
                 nullable: false,
                 _type: serde_json::Value::String("string".to_string()),
+                validations: vec![],
             },
             TfVariable {
                 name: "bucket1a__tags".to_string(),
@@ -1921,6 +1926,7 @@ mod tests {
                 sensitive: false,
                 nullable: true,
                 _type: serde_json::Value::String("map".to_string()),
+                validations: vec![],
             },
         ];
         let example_variables = serde_yaml::from_str::<serde_yaml::Value>(
@@ -1968,6 +1974,7 @@ mod tests {
                         description: "Name of the S3 bucket".to_string(),
                         nullable: false,
                         sensitive: false,
+                        validations: vec![],
                     },
                 ),
                 (
@@ -1979,6 +1986,7 @@ mod tests {
                         description: "Some arbitrary input list".to_string(),
                         nullable: true,
                         sensitive: false,
+                        validations: vec![],
                     },
                 ),
                 (
@@ -1996,6 +2004,7 @@ mod tests {
                         description: "Tags to apply to the S3 bucket".to_string(),
                         nullable: true,
                         sensitive: false,
+                        validations: vec![],
                     },
                 ),
                 (
@@ -2007,6 +2016,7 @@ mod tests {
                         description: "Name of the S3 bucket".to_string(),
                         nullable: false,
                         sensitive: false,
+                        validations: vec![],
                     },
                 ),
                 (
@@ -2018,6 +2028,7 @@ mod tests {
                         description: "Some arbitrary input list".to_string(),
                         nullable: true,
                         sensitive: false,
+                        validations: vec![],
                     },
                 ),
                 (
@@ -2036,6 +2047,7 @@ mod tests {
                         description: "Tags to apply to the S3 bucket".to_string(),
                         nullable: true,
                         sensitive: false,
+                        validations: vec![],
                     },
                 ),
             ]);
@@ -2517,6 +2529,7 @@ output "bucket2__list_of_strings" {
                         _type: Value::String("string".to_string()),
                         nullable: false,
                         sensitive: false,
+                        validations: vec![],
                     },
                     TfVariable {
                         _type: Value::String("map(string)".to_string()),
@@ -2528,6 +2541,7 @@ output "bucket2__list_of_strings" {
                         .unwrap(),
                         nullable: true,
                         sensitive: false,
+                        validations: vec![],
                     },
                 ],
                 tf_extra_environment_variables: vec![],
@@ -2821,6 +2835,7 @@ output "bucket2__list_of_strings" {
                         _type: Value::String("string".to_string()),
                         nullable: false,
                         sensitive: false,
+                        validations: vec![],
                     },
                     TfVariable {
                         _type: Value::String("map(string)".to_string()),
@@ -2832,6 +2847,7 @@ output "bucket2__list_of_strings" {
                         .unwrap(),
                         nullable: true,
                         sensitive: false,
+                        validations: vec![],
                     },
                 ],
                 tf_extra_environment_variables: vec![],
@@ -2935,6 +2951,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     _type: Value::String("map(string)".to_string()),
@@ -2946,6 +2963,7 @@ output "bucket2__list_of_strings" {
                     .unwrap(),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -3051,6 +3069,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     _type: Value::String("map(string)".to_string()),
@@ -3062,6 +3081,7 @@ output "bucket2__list_of_strings" {
                     .unwrap(),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -3175,6 +3195,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     _type: Value::String("map(string)".to_string()),
@@ -3186,6 +3207,7 @@ output "bucket2__list_of_strings" {
                     .unwrap(),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -3303,6 +3325,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     _type: Value::String("map(string)".to_string()),
@@ -3314,6 +3337,7 @@ output "bucket2__list_of_strings" {
                     .unwrap(),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -3416,6 +3440,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     _type: Value::String("map(string)".to_string()),
@@ -3427,6 +3452,7 @@ output "bucket2__list_of_strings" {
                     .unwrap(),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -3572,6 +3598,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     _type: Value::String("map(string)".to_string()),
@@ -3583,6 +3610,7 @@ output "bucket2__list_of_strings" {
                     .unwrap(),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -3695,6 +3723,7 @@ output "bucket2__list_of_strings" {
                 _type: Value::String("string".to_string()),
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             }],
             tf_extra_environment_variables: vec![],
             stack_data: None,
@@ -3749,6 +3778,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     name: "vpc_id".to_string(),
@@ -3757,6 +3787,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -3840,6 +3871,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 }],
                 tf_extra_environment_variables: vec![],
                 stack_data: None,
@@ -3915,6 +3947,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 }],
                 tf_extra_environment_variables: vec![],
                 stack_data: None,
@@ -4042,6 +4075,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 // TfVariable { default: None, name: "enable_acl".to_string(), description: "Enable ACL for the S3 bucket".to_string()), _type: Value::Bool(false), nullable: Some(false), sensitive: false },
                 TfVariable {
@@ -4054,6 +4088,7 @@ output "bucket2__list_of_strings" {
                     .unwrap(),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     default: None,
@@ -4062,6 +4097,7 @@ output "bucket2__list_of_strings" {
                     _type: Value::String("list(string)".to_string()),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -1,13 +1,17 @@
+mod api_cascade;
 mod api_change_record;
 mod api_deployment;
 mod api_event;
+mod api_expiry;
 mod api_infra;
 mod api_log;
 mod api_module;
+mod api_move;
 mod api_notification;
 mod api_oci_registry;
 mod api_policy;
 mod api_provider;
+mod api_rollout;
 mod api_stack;
 mod common;
 mod tf_input_resolver;
@@ -22,26 +26,46 @@ pub use api_module::{
 
 pub use api_stack::{deprecate_stack, get_stack_preview, publish_stack};
 
+pub use api_cascade::{
+    order_dependents, outputs_changed, trigger_dependent_deployments, TriggeredDependent,
+};
+
 pub use api_deployment::set_deployment;
 
 pub use api_event::insert_event;
 
+pub use api_expiry::{
+    expire_deployments, get_expires_at_epoch, get_expiry_state, ExpiryAction, ExpiryResult,
+    ExpiryState, EXPIRY_WARNING_PERIOD,
+};
+
+pub use api_move::{move_deployment, rewrite_dependencies, validate_move};
+
 pub use api_notification::publish_notification;
 
 pub use api_infra::{
-    check_module_deprecation, destroy_infra, driftcheck_infra, get_deployment_details,
-    is_deployment_in_progress, is_deployment_plan_in_progress, mutate_infra, run_claim,
-    submit_claim_job, validate_and_prepare_claim,
+    cancel_deployment_job, check_module_deprecation, destroy_infra, driftcheck_infra,
+    get_deployment_details, get_import_module_name, is_deployment_in_progress,
+    is_deployment_plan_in_progress, mutate_infra, prepare_rollback, prepare_upgrade,
+    reapply_dependent_infra, rollback_infra, run_claim, run_claim_from_plan, submit_claim_job,
+    upgrade_infra, validate_and_prepare_claim,
 };
 
-pub use api_change_record::insert_infra_change_record;
+pub use api_change_record::{insert_infra_change_record, upload_change_record_file};
 
 pub use api_log::read_logs;
 
-pub use api_policy::publish_policy;
+pub use api_policy::{promote_policy, publish_policy};
+
+pub use api_rollout::{
+    get_rollout, get_rollout_batch, save_rollout, Rollout, RolloutDeployment, RolloutState,
+    RolloutStepStatus, DEFAULT_ROLLOUT_BATCH_SIZE, DEFAULT_ROLLOUT_CANARY_SIZE,
+};
 
 pub use common::{PROJECT_ID, REGION};
 
 pub use api_oci_registry::OCIRegistryProvider;
 
 pub use api_provider::{download_provider_to_vec, publish_provider};
+
+pub use tf_root_module::imports_file;
@@ -5,6 +5,7 @@ use hcl::{
     Attribute, Block, BlockLabel, Expression, Identifier, Object, ObjectKey,
 };
 use log::info;
+use std::collections::BTreeMap;
 
 use crate::logic::tf_input_resolver::TfInputResolver;
 
@@ -135,3 +136,142 @@ fn dependencies_attributes(dependencies: &[String]) -> Vec<Attribute> {
         ),
     )]
 }
+
+/// Import blocks adopting existing resources into the deployment. The addresses are relative to
+/// the module block `module_name`, or to the root module if it is `None` (stacks).
+pub fn import_blocks(
+    imports: &BTreeMap<String, String>,
+    module_name: Option<&str>,
+) -> Result<Vec<Block>, anyhow::Error> {
+    imports
+        .iter()
+        .map(|(address, id)| {
+            Ok(Block::builder("import")
+                .add_attribute(Attribute::new("to", import_address(address, module_name)?))
+                .add_attribute(Attribute::new("id", Expression::String(id.clone())))
+                .build())
+        })
+        .collect()
+}
+
+/// Content of the file with the import blocks, written next to the root module by the runner
+pub fn imports_file(
+    imports: &BTreeMap<String, String>,
+    module_name: Option<&str>,
+) -> Result<String, anyhow::Error> {
+    let blocks = import_blocks(imports, module_name)?;
+    Ok(hcl::format::to_string(
+        &hcl::Body::builder().add_blocks(blocks).build(),
+    )?)
+}
+
+fn import_address(address: &str, module_name: Option<&str>) -> Result<Expression, anyhow::Error> {
+    let invalid = || anyhow::anyhow!("Invalid resource address to import: {}", address);
+    let body = hcl::parse(&format!("to = {}", address)).map_err(|_| invalid())?;
+    if body.attributes().count() != 1 || body.blocks().count() != 0 {
+        return Err(invalid());
+    }
+    let traversal = match body.attributes().next().map(|attr| attr.expr()) {
+        Some(Expression::Traversal(traversal)) => (**traversal).clone(),
+        _ => return Err(invalid()),
+    };
+    let first = match &traversal.expr {
+        Expression::Variable(variable) => variable.as_str().to_string(),
+        _ => return Err(invalid()),
+    };
+    // A resource address has at least a type and a name, and data sources cannot be imported
+    if first == "data"
+        || !matches!(
+            traversal.operators.first(),
+            Some(TraversalOperator::GetAttr(_))
+        )
+    {
+        return Err(invalid());
+    }
+    match module_name {
+        None => Ok(Expression::from(traversal)),
+        Some(module_name) => {
+            let mut operators = vec![
+                TraversalOperator::GetAttr(Identifier::new(module_name)?),
+                TraversalOperator::GetAttr(Identifier::new(first)?),
+            ];
+            operators.extend(traversal.operators);
+            Ok(Expression::from(Traversal::new(
+                Variable::new("module")?,
+                operators,
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn imports(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(address, id)| (address.to_string(), id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_import_blocks_in_module() {
+        let tf = imports_file(
+            &imports(&[
+                ("aws_s3_bucket.bucket", "my-bucket"),
+                ("aws_iam_role.roles[\"reader\"]", "reader-role"),
+            ]),
+            Some("s3bucket"),
+        )
+        .unwrap();
+        assert_eq!(
+            tf,
+            r#"import {
+  to = module.s3bucket.aws_iam_role.roles["reader"]
+  id = "reader-role"
+}
+
+import {
+  to = module.s3bucket.aws_s3_bucket.bucket
+  id = "my-bucket"
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_import_blocks_in_stack() {
+        let tf = imports_file(
+            &imports(&[("module.bucket1.aws_s3_bucket.bucket", "my-bucket")]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            tf,
+            r#"import {
+  to = module.bucket1.aws_s3_bucket.bucket
+  id = "my-bucket"
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_import_blocks_invalid_address() {
+        for address in [
+            "aws_s3_bucket",
+            "data.aws_s3_bucket.bucket",
+            "\"aws_s3_bucket.bucket\"",
+            "aws_s3_bucket.bucket\nresource \"x\" \"y\" {}",
+            "aws_s3_bucket.bucket +",
+        ] {
+            let result = import_blocks(&imports(&[(address, "my-bucket")]), Some("s3bucket"));
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!("Invalid resource address to import: {}", address)
+            );
+        }
+    }
+}
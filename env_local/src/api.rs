@@ -0,0 +1,403 @@
+use std::{fs, io::Write, path::PathBuf};
+
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+use env_defs::{
+    get_change_record_identifier, get_deployment_identifier, get_event_identifier,
+    get_module_identifier, get_policy_identifier, get_rollout_identifier, GenericFunctionResponse,
+};
+use env_utils::{get_epoch, sanitize_payload_for_logging, zero_pad_semver};
+use log::info;
+use serde_json::{json, Value};
+
+use crate::{get_local_dir, store::write_atomic, LocalStore};
+
+pub fn get_store() -> LocalStore {
+    LocalStore::new(get_local_dir().join("db"))
+}
+
+pub fn get_blob_path(bucket: &str, key: &str) -> PathBuf {
+    get_local_dir().join("blobs").join(bucket).join(key)
+}
+
+pub fn get_log_path(job_id: &str) -> PathBuf {
+    get_local_dir().join("logs").join(format!("{}.log", job_id))
+}
+
+/// Handles the same events as the cloud api functions, but in-process against the local store
+pub async fn run_function(payload: &Value) -> Result<GenericFunctionResponse, anyhow::Error> {
+    let event = payload
+        .get("event")
+        .and_then(|e| e.as_str())
+        .ok_or_else(|| anyhow::anyhow!("No event specified in payload"))?;
+    info!(
+        "Running local function with payload: {}",
+        serde_json::to_string(&sanitize_payload_for_logging(payload.clone())).unwrap()
+    );
+
+    let store = get_store();
+    let data = &payload["data"];
+    let response = match event {
+        "insert_db" => {
+            let table = payload["table"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("No table specified for insert_db"))?;
+            store.put(table, data)?;
+            json!({})
+        }
+        "transact_write" => {
+            let items = payload["items"]
+                .as_array()
+                .ok_or_else(|| anyhow::anyhow!("No items specified for transact_write"))?;
+            store.transact_write(items)?;
+            json!({})
+        }
+        "read_db" => {
+            let table = payload["table"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("No table specified for read_db"))?;
+            json!({ "Items": store.query(table, &data["query"])? })
+        }
+        "upload_file_base64" => {
+            let path = get_blob_path(get_str(data, "bucket_name")?, get_str(data, "key")?);
+            let content = base64
+                .decode(get_str(data, "base64_content")?)
+                .context("Failed to decode base64 content")?;
+            write_atomic(&path, &content)?;
+            json!({})
+        }
+        "upload_file_url" => {
+            let path = get_blob_path(get_str(data, "bucket_name")?, get_str(data, "key")?);
+            if path.exists() {
+                json!({ "object_already_exists": true })
+            } else {
+                let url = get_str(data, "url")?;
+                let content = reqwest::get(url)
+                    .await
+                    .with_context(|| format!("request to {url} failed"))?
+                    .error_for_status()?
+                    .bytes()
+                    .await?;
+                write_atomic(&path, &content)?;
+                json!({ "object_already_exists": false })
+            }
+        }
+        "generate_presigned_url" => {
+            let path = get_blob_path(get_str(data, "bucket_name")?, get_str(data, "key")?);
+            json!({ "url": format!("file://{}", path.display()) })
+        }
+        "get_environment_variables" => {
+            json!({ "body": { "INFRAWEAVE_LOCAL_DIR": get_local_dir().display().to_string() } })
+        }
+        "publish_notification" => {
+            let path = get_local_dir().join("notifications.jsonl");
+            fs::create_dir_all(get_local_dir())?;
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            writeln!(file, "{}", serde_json::to_string(data)?)?;
+            json!({})
+        }
+        "start_runner" => {
+            let job_id = crate::executor::start_runner(data)?;
+            json!({ "job_id": job_id })
+        }
+        "get_job_status" => {
+            serde_json::to_value(crate::executor::get_job_status(get_str(data, "job_id")?)?)?
+        }
+        "cancel_job" => {
+            crate::executor::cancel_job(get_str(data, "job_id")?).await?;
+            json!({})
+        }
+        "read_logs" => {
+            let path = get_log_path(get_str(data, "job_id")?);
+            let events: Vec<Value> = match fs::read_to_string(&path) {
+                Ok(content) => content
+                    .lines()
+                    .map(|line| json!({ "message": line }))
+                    .collect(),
+                Err(_) => vec![],
+            };
+            json!({ "events": events })
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Event {} is not supported by the local provider",
+                event
+            ))
+        }
+    };
+    Ok(GenericFunctionResponse { payload: response })
+}
+
+fn get_str<'a>(data: &'a Value, field: &str) -> Result<&'a str, anyhow::Error> {
+    data[field]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing field {} in payload data", field))
+}
+
+pub fn get_latest_module_version_query(module: &str, track: &str) -> Value {
+    json!({
+        "pk": "LATEST_MODULE",
+        "sk": format!("MODULE#{}", get_module_identifier(module, track)),
+        "limit": 1,
+    })
+}
+
+pub fn get_latest_stack_version_query(stack: &str, track: &str) -> Value {
+    json!({
+        "pk": "LATEST_STACK",
+        "sk": format!("MODULE#{}", get_module_identifier(stack, track)),
+        "limit": 1,
+    })
+}
+
+pub fn get_latest_provider_version_query(provider: &str) -> Value {
+    json!({
+        "pk": "LATEST_PROVIDER",
+        "sk": format!("PROVIDER#{}", provider),
+        "limit": 1,
+    })
+}
+
+pub fn get_all_latest_modules_query(track: &str) -> Value {
+    _get_all_latest_modules_query("LATEST_MODULE", track)
+}
+
+pub fn get_all_latest_stacks_query(track: &str) -> Value {
+    _get_all_latest_modules_query("LATEST_STACK", track)
+}
+
+pub fn get_all_latest_providers_query() -> Value {
+    json!({ "pk": "LATEST_PROVIDER" })
+}
+
+fn _get_all_latest_modules_query(pk: &str, track: &str) -> Value {
+    if track.is_empty() {
+        json!({ "pk": pk })
+    } else {
+        json!({ "pk": pk, "sk_prefix": format!("MODULE#{}::", track) })
+    }
+}
+
+pub fn get_all_module_versions_query(module: &str, track: &str) -> Value {
+    json!({
+        "pk": format!("MODULE#{}", get_module_identifier(module, track)),
+        "sk_prefix": "VERSION#",
+        "descending": true,
+    })
+}
+
+pub fn get_all_stack_versions_query(stack: &str, track: &str) -> Value {
+    get_all_module_versions_query(stack, track)
+}
+
+pub fn get_module_version_query(
+    module: &str,
+    track: &str,
+    version: &str,
+) -> Result<Value, anyhow::Error> {
+    Ok(json!({
+        "pk": format!("MODULE#{}", get_module_identifier(module, track)),
+        "sk": format!("VERSION#{}", zero_pad_semver(version, 3)?),
+        "limit": 1,
+    }))
+}
+
+pub fn get_stack_version_query(
+    stack: &str,
+    track: &str,
+    version: &str,
+) -> Result<Value, anyhow::Error> {
+    get_module_version_query(stack, track, version)
+}
+
+pub fn get_all_deployments_query(
+    project_id: &str,
+    region: &str,
+    environment: &str,
+    include_deleted: bool,
+) -> Value {
+    let pk_prefix = format!(
+        "DEPLOYMENT#{}",
+        get_deployment_identifier(project_id, region, "", environment)
+    );
+    if include_deleted {
+        json!({ "pk_prefix": pk_prefix, "equals": {"SK": "METADATA"} })
+    } else {
+        json!({ "pk_prefix": pk_prefix, "equals": {"SK": "METADATA", "deleted": 0} })
+    }
+}
+
+pub fn get_deployment_and_dependents_query(
+    project_id: &str,
+    region: &str,
+    deployment_id: &str,
+    environment: &str,
+    include_deleted: bool,
+) -> Value {
+    let pk = format!(
+        "DEPLOYMENT#{}",
+        get_deployment_identifier(project_id, region, deployment_id, environment)
+    );
+    if include_deleted {
+        json!({ "pk": pk })
+    } else {
+        json!({ "pk": pk, "not_equals": {"deleted": 1} })
+    }
+}
+
+pub fn get_deployment_query(
+    project_id: &str,
+    region: &str,
+    deployment_id: &str,
+    environment: &str,
+    include_deleted: bool,
+) -> Value {
+    let pk = format!(
+        "DEPLOYMENT#{}",
+        get_deployment_identifier(project_id, region, deployment_id, environment)
+    );
+    if include_deleted {
+        json!({ "pk": pk, "sk": "METADATA" })
+    } else {
+        json!({ "pk": pk, "sk": "METADATA", "equals": {"deleted": 0} })
+    }
+}
+
+pub fn get_deployments_using_module_query(
+    project_id: &str,
+    region: &str,
+    module: &str,
+    environment: &str,
+    include_deleted: bool,
+) -> Value {
+    let mut query = json!({
+        "pk_prefix": format!("DEPLOYMENT#{}", get_deployment_identifier(project_id, region, "", environment)),
+        "equals": {
+            "SK": "METADATA",
+            "module_PK_base": format!("MODULE#{}#{}", get_deployment_identifier(project_id, region, "", ""), module),
+        },
+    });
+    if !include_deleted {
+        query["equals"]["deleted"] = json!(0);
+    }
+    query
+}
+
+pub fn get_plan_deployment_query(
+    project_id: &str,
+    region: &str,
+    deployment_id: &str,
+    environment: &str,
+    job_id: &str,
+) -> Value {
+    json!({
+        "pk": format!("PLAN#{}", get_deployment_identifier(project_id, region, deployment_id, environment)),
+        "sk": job_id,
+        "not_equals": {"deleted": 1},
+    })
+}
+
+pub fn get_dependents_query(
+    project_id: &str,
+    region: &str,
+    deployment_id: &str,
+    environment: &str,
+) -> Value {
+    // Dependent items carry no deleted flag, they are removed when the deployment is deleted
+    json!({
+        "pk": format!("DEPLOYMENT#{}", get_deployment_identifier(project_id, region, deployment_id, environment)),
+        "sk_prefix": "DEPENDENT#",
+        "not_equals": {"deleted": 1},
+    })
+}
+
+pub fn get_deployments_to_driftcheck_query(project_id: &str, region: &str) -> Value {
+    json!({
+        "equals": {
+            "deleted_SK_base": format!("0|METADATA#{}", get_deployment_identifier(project_id, region, "", "")),
+        },
+        "between": {
+            "field": "next_drift_check_epoch",
+            "start": 0,
+            "end": get_epoch(),
+        },
+    })
+}
+
+pub fn get_events_query(
+    project_id: &str,
+    region: &str,
+    deployment_id: &str,
+    environment: &str,
+) -> Value {
+    json!({
+        "pk": format!("EVENT#{}", get_event_identifier(project_id, region, deployment_id, environment)),
+    })
+}
+
+pub fn get_all_events_between_query(region: &str, start_epoch: u128, end_epoch: u128) -> Value {
+    json!({
+        "equals": {"PK_base_region": format!("EVENT#{}", region)},
+        "between": {
+            "field": "SK",
+            "start": start_epoch.to_string(),
+            "end": end_epoch.to_string(),
+        },
+    })
+}
+
+pub fn get_change_records_query(
+    project_id: &str,
+    region: &str,
+    environment: &str,
+    deployment_id: &str,
+    job_id: &str,
+    change_type: &str,
+) -> Value {
+    json!({
+        "pk": format!("{}#{}", change_type, get_change_record_identifier(project_id, region, deployment_id, environment)),
+        "sk": job_id,
+    })
+}
+
+pub fn get_rollout_query(project_id: &str, region: &str, module: &str) -> Value {
+    json!({
+        "pk": format!("ROLLOUT#{}", get_rollout_identifier(project_id, region, module)),
+        "sk": "ROLLOUT",
+    })
+}
+
+pub fn get_newest_policy_version_query(policy: &str, environment: &str) -> Value {
+    json!({
+        "pk": format!("POLICY#{}", get_policy_identifier(policy, environment)),
+        "descending": true,
+        "limit": 1,
+    })
+}
+
+pub fn get_all_policies_query(environment: &str) -> Value {
+    json!({
+        "pk": "CURRENT",
+        "sk_prefix": format!("POLICY#{}::", environment),
+    })
+}
+
+pub fn get_policy_query(
+    policy: &str,
+    environment: &str,
+    version: &str,
+) -> Result<Value, anyhow::Error> {
+    Ok(json!({
+        "pk": format!("POLICY#{}", get_policy_identifier(policy, environment)),
+        "sk": format!("VERSION#{}", zero_pad_semver(version, 3)?),
+        "limit": 1,
+    }))
+}
+
+pub fn get_project_map_query() -> Value {
+    json!({ "pk": "project_map", "limit": 1 })
+}
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use crate::get_local_dir;
+
+pub fn get_state_path(storage_basepath: &str, environment: &str, deployment_id: &str) -> PathBuf {
+    get_local_dir().join("state").join(format!(
+        "{}{}/{}/terraform.tfstate",
+        storage_basepath, environment, deployment_id
+    ))
+}
+
+pub async fn set_backend(
+    exec: &mut tokio::process::Command,
+    storage_basepath: &str,
+    deployment_id: &str,
+    environment: &str,
+) {
+    let path = get_state_path(storage_basepath, environment, deployment_id);
+    if let Some(parent) = path.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        eprintln!(
+            "Failed to create state directory {}: {}",
+            parent.display(),
+            e
+        );
+    }
+    exec.arg(format!("-backend-config=path={}", path.display()));
+}
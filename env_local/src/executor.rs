@@ -0,0 +1,228 @@
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use anyhow::Context;
+use env_defs::JobStatus;
+use env_utils::get_epoch;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{get_local_dir, get_log_path, store::write_atomic};
+
+static JOB_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// How long a cancelled job gets to stop before its process group is killed, the runner kills
+/// terraform itself well before this so it can record the cancellation and release the state lock
+const CANCEL_GRACE_PERIOD_SECONDS: u64 = 60;
+
+/// Record of a job started by the local executor, stored in `<local>/jobs/<job_id>/job.json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalJob {
+    pub job_id: String,
+    pub pid: u32,
+    pub started_at: u128,
+    pub working_directory: String,
+}
+
+pub fn get_job_dir(job_id: &str) -> PathBuf {
+    get_local_dir().join("jobs").join(job_id)
+}
+
+/// Starts a terraform_runner job as a child process with the same environment contract as
+/// the cloud runners (`PAYLOAD` etc.). The job runs in its own working directory and process
+/// group, so it keeps running if the process that submitted it exits.
+///
+/// The runner binary is `terraform_runner` from PATH unless `INFRAWEAVE_RUNNER_BINARY` is set.
+/// If `INFRAWEAVE_RUNNER_IMAGE` is set, the job is run with `docker run` using that image instead.
+pub fn start_runner(payload: &Value) -> Result<String, anyhow::Error> {
+    let job_id = format!(
+        "local-{}-{}-{}",
+        get_epoch(),
+        std::process::id(),
+        JOB_COUNTER.fetch_add(1, Ordering::SeqCst)
+    );
+    let job_dir = get_job_dir(&job_id);
+    let working_directory = job_dir.join("workdir");
+    fs::create_dir_all(&working_directory)
+        .with_context(|| format!("Failed to create {}", working_directory.display()))?;
+
+    let log_path = get_log_path(&job_id);
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let log_file = fs::File::create(&log_path)
+        .with_context(|| format!("Failed to create {}", log_path.display()))?;
+
+    let project_id = payload["project_id"].as_str().unwrap_or("local");
+    let region = payload["region"].as_str().unwrap_or("local");
+    let env_vars = [
+        ("PAYLOAD", serde_json::to_string(payload)?),
+        ("PROVIDER", "local".to_string()),
+        ("INFRAWEAVE_JOB_ID", job_id.clone()),
+        ("INFRAWEAVE_LOCAL_PROJECT", project_id.to_string()),
+        ("REGION", region.to_string()),
+    ];
+
+    let mut command = match std::env::var("INFRAWEAVE_RUNNER_IMAGE") {
+        Ok(image) => {
+            let mut command = Command::new("docker");
+            command
+                .arg("run")
+                .arg("--rm")
+                .arg("--name")
+                .arg(&job_id)
+                .arg("-v")
+                .arg(format!("{}:/infraweave", get_local_dir().display()))
+                .arg("-e")
+                .arg("INFRAWEAVE_LOCAL_DIR=/infraweave");
+            for (key, _) in env_vars.iter() {
+                command.arg("-e").arg(key);
+            }
+            command.arg(image);
+            command
+        }
+        Err(_) => {
+            let binary = std::env::var("INFRAWEAVE_RUNNER_BINARY")
+                .unwrap_or_else(|_| "terraform_runner".to_string());
+            let mut command = Command::new(binary);
+            command.env("INFRAWEAVE_LOCAL_DIR", get_local_dir());
+            command
+        }
+    };
+    command
+        .envs(env_vars)
+        .current_dir(&working_directory)
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start runner for job {}", job_id))?;
+
+    let job = LocalJob {
+        job_id: job_id.clone(),
+        pid: child.id(),
+        started_at: get_epoch(),
+        working_directory: working_directory.display().to_string(),
+    };
+    write_atomic(
+        &job_dir.join("job.json"),
+        serde_json::to_string(&job)?.as_bytes(),
+    )?;
+    info!("Started local job {} with pid {}", job_id, job.pid);
+
+    // Reap the child and record its exit code while this process is alive, if this process
+    // exits first the job is re-parented and its liveness is checked using the pid instead
+    let exit_code_path = job_dir.join("exit_code");
+    std::thread::spawn(move || {
+        if let Ok(status) = child.wait() {
+            let code = status.code().unwrap_or(-1);
+            let _ = write_atomic(&exit_code_path, code.to_string().as_bytes());
+        }
+    });
+
+    Ok(job_id)
+}
+
+pub fn read_job(job_id: &str) -> Result<Option<LocalJob>, anyhow::Error> {
+    let path = get_job_dir(job_id).join("job.json");
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(Some(serde_json::from_str(&content).with_context(|| {
+            format!("Failed to parse job record {}", path.display())
+        })?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+pub fn get_job_status(job_id: &str) -> Result<JobStatus, anyhow::Error> {
+    let is_running = match read_job(job_id)? {
+        Some(job) => !get_job_dir(job_id).join("exit_code").exists() && is_process_alive(job.pid),
+        None => false,
+    };
+    Ok(JobStatus {
+        job_id: job_id.to_string(),
+        is_running,
+    })
+}
+
+/// Interrupts the runner of the job, which stops terraform gracefully and records the
+/// cancellation. If the job is still running after the grace period its process group is killed.
+pub async fn cancel_job(job_id: &str) -> Result<(), anyhow::Error> {
+    let job = read_job(job_id)?.with_context(|| format!("Job {} not found", job_id))?;
+    if !get_job_status(job_id)?.is_running {
+        return Err(anyhow::anyhow!("Job {} is not running", job_id));
+    }
+
+    // Only the runner is interrupted, terraform exits immediately without releasing the state
+    // lock if it is interrupted twice (once from the process group and once by the runner)
+    info!("Interrupting local job {} with pid {}", job_id, job.pid);
+    send_signal(job.pid as i32, Signal::Interrupt)?;
+
+    let started = get_epoch();
+    while get_job_status(job_id)?.is_running {
+        if get_epoch() - started > CANCEL_GRACE_PERIOD_SECONDS as u128 * 1000 {
+            warn!(
+                "Local job {} did not stop within {} seconds, killing it",
+                job_id, CANCEL_GRACE_PERIOD_SECONDS
+            );
+            send_signal(-(job.pid as i32), Signal::Kill)?;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+    Ok(())
+}
+
+enum Signal {
+    Interrupt,
+    Kill,
+}
+
+/// Sends a signal to a process, or to a process group if `pid` is negative
+#[cfg(unix)]
+fn send_signal(pid: i32, signal: Signal) -> Result<(), anyhow::Error> {
+    let signal = match signal {
+        Signal::Interrupt => libc::SIGINT,
+        Signal::Kill => libc::SIGKILL,
+    };
+    if unsafe { libc::kill(pid, signal) } != 0 {
+        let error = std::io::Error::last_os_error();
+        // The job exited after it was checked
+        if error.raw_os_error() != Some(libc::ESRCH) {
+            return Err(error).with_context(|| format!("Failed to signal process {}", pid));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_signal(_pid: i32, _signal: Signal) -> Result<(), anyhow::Error> {
+    Err(anyhow::anyhow!(
+        "Cancelling local jobs is only supported on unix"
+    ))
+}
+
+#[cfg(unix)]
+pub(crate) fn is_process_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists and can be signalled
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_process_alive(_pid: u32) -> bool {
+    false
+}
@@ -0,0 +1,8 @@
+pub async fn get_current_job_id() -> Result<String, anyhow::Error> {
+    if std::env::var("TEST_MODE").is_ok() {
+        return Ok("running-test-job-id".to_string());
+    };
+
+    std::env::var("INFRAWEAVE_JOB_ID")
+        .map_err(|_| anyhow::anyhow!("INFRAWEAVE_JOB_ID not found in environment variables"))
+}
@@ -0,0 +1,48 @@
+mod api;
+mod backend;
+mod executor;
+mod job_id;
+mod provider;
+mod secret;
+mod store;
+mod utils;
+
+pub use api::{
+    // Alphabetical order and newlines between each function
+    get_all_deployments_query,
+    get_all_events_between_query,
+    get_all_latest_modules_query,
+    get_all_latest_providers_query,
+    get_all_latest_stacks_query,
+    get_all_module_versions_query,
+    get_all_policies_query,
+    get_all_stack_versions_query,
+    get_blob_path,
+    get_change_records_query,
+    get_dependents_query,
+    get_deployment_and_dependents_query,
+    get_deployment_query,
+    get_deployments_to_driftcheck_query,
+    get_deployments_using_module_query,
+    get_events_query,
+    get_latest_module_version_query,
+    get_latest_provider_version_query,
+    get_latest_stack_version_query,
+    get_log_path,
+    get_module_version_query,
+    get_newest_policy_version_query,
+    get_plan_deployment_query,
+    get_policy_query,
+    get_project_map_query,
+    get_rollout_query,
+    get_stack_version_query,
+    get_store,
+    run_function,
+};
+pub use backend::{get_state_path, set_backend};
+pub use executor::{cancel_job, get_job_dir, get_job_status, read_job, start_runner, LocalJob};
+pub use job_id::get_current_job_id;
+pub use provider::LocalCloudProvider;
+pub use secret::{get_secret_env_var, get_secret_value};
+pub use store::LocalStore;
+pub use utils::{get_local_dir, get_project_id, get_region, get_user_id};
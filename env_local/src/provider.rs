@@ -0,0 +1,426 @@
+use async_trait::async_trait;
+use env_defs::{
+    CloudProvider, Dependent, DeploymentResp, EventData, GenericFunctionResponse,
+    InfraChangeRecord, JobStatus, ModuleResp, PolicyResp, ProjectData, ProviderResp,
+};
+use env_utils::{
+    _get_change_records, _get_dependents, _get_deployment, _get_deployment_and_dependents,
+    _get_deployments, _get_events, _get_module_optional, _get_modules, _get_policies, _get_policy,
+    _get_provider_optional, _get_providers,
+};
+use serde_json::{json, Value};
+use std::{future::Future, pin::Pin};
+
+/// Provider keeping all state on the local filesystem, see `get_local_dir`.
+/// Selected with `PROVIDER=local`.
+#[derive(Clone)]
+pub struct LocalCloudProvider {
+    pub project_id: String,
+    pub region: String,
+    pub function_endpoint: Option<String>,
+}
+
+#[async_trait]
+impl CloudProvider for LocalCloudProvider {
+    fn get_project_id(&self) -> &str {
+        &self.project_id
+    }
+    async fn get_user_id(&self) -> Result<String, anyhow::Error> {
+        crate::get_user_id().await
+    }
+    fn get_region(&self) -> &str {
+        &self.region
+    }
+    fn get_function_endpoint(&self) -> Option<String> {
+        self.function_endpoint.clone()
+    }
+    fn get_cloud_provider(&self) -> &str {
+        "local"
+    }
+    fn get_backend_provider(&self) -> &str {
+        "local"
+    }
+    fn get_storage_basepath(&self) -> String {
+        format!("{}/", self.project_id)
+    }
+    async fn get_backend_provider_arguments(
+        &self,
+        environment: &str,
+        deployment_id: &str,
+    ) -> serde_json::Value {
+        json!({
+            "path": crate::get_state_path(&self.get_storage_basepath(), environment, deployment_id).display().to_string(),
+        })
+    }
+    async fn set_backend(
+        &self,
+        exec: &mut tokio::process::Command,
+        deployment_id: &str,
+        environment: &str,
+    ) {
+        crate::set_backend(
+            exec,
+            &self.get_storage_basepath(),
+            deployment_id,
+            environment,
+        )
+        .await;
+    }
+    async fn get_current_job_id(&self) -> Result<String, anyhow::Error> {
+        crate::get_current_job_id().await
+    }
+    async fn get_project_map(&self) -> Result<Value, anyhow::Error> {
+        self.read_db_generic("config", &crate::get_project_map_query())
+            .await
+            .map(|mut items| items.pop().unwrap_or_else(|| json!({"data": {}})))
+    }
+    async fn get_all_regions(&self) -> Result<Vec<String>, anyhow::Error> {
+        Ok(vec![self.region.clone()])
+    }
+    async fn run_function(
+        &self,
+        payload: &Value,
+    ) -> Result<GenericFunctionResponse, anyhow::Error> {
+        crate::run_function(payload).await
+    }
+    fn read_db_generic(
+        &self,
+        table: &str,
+        query: &Value,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Value>, anyhow::Error>> + Send>> {
+        let table = table.to_string();
+        let query = query.clone();
+        Box::pin(async move { crate::get_store().query(&table, &query) })
+    }
+    async fn get_latest_module_version(
+        &self,
+        module: &str,
+        track: &str,
+    ) -> Result<Option<ModuleResp>, anyhow::Error> {
+        _get_module_optional(self, crate::get_latest_module_version_query(module, track)).await
+    }
+    async fn get_latest_stack_version(
+        &self,
+        stack: &str,
+        track: &str,
+    ) -> Result<Option<ModuleResp>, anyhow::Error> {
+        _get_module_optional(self, crate::get_latest_stack_version_query(stack, track)).await
+    }
+    async fn get_job_status(&self, job_id: &str) -> Result<Option<JobStatus>, anyhow::Error> {
+        crate::get_job_status(job_id).map(Some)
+    }
+    async fn cancel_job(&self, job_id: &str) -> Result<(), anyhow::Error> {
+        crate::cancel_job(job_id).await
+    }
+    async fn get_latest_provider_version(
+        &self,
+        provider: &str,
+    ) -> Result<Option<ProviderResp>, anyhow::Error> {
+        _get_provider_optional(self, crate::get_latest_provider_version_query(provider)).await
+    }
+    async fn generate_presigned_url(
+        &self,
+        key: &str,
+        bucket: &str,
+    ) -> Result<String, anyhow::Error> {
+        Ok(format!(
+            "file://{}",
+            crate::get_blob_path(bucket, key).display()
+        ))
+    }
+    async fn get_all_latest_module(&self, track: &str) -> Result<Vec<ModuleResp>, anyhow::Error> {
+        _get_modules(self, crate::get_all_latest_modules_query(track)).await
+    }
+    async fn get_all_latest_stack(&self, track: &str) -> Result<Vec<ModuleResp>, anyhow::Error> {
+        _get_modules(self, crate::get_all_latest_stacks_query(track)).await
+    }
+    async fn get_all_latest_provider(&self) -> Result<Vec<ProviderResp>, anyhow::Error> {
+        _get_providers(self, crate::get_all_latest_providers_query()).await
+    }
+    async fn get_all_module_versions(
+        &self,
+        module: &str,
+        track: &str,
+    ) -> Result<Vec<ModuleResp>, anyhow::Error> {
+        _get_modules(self, crate::get_all_module_versions_query(module, track)).await
+    }
+    async fn get_all_stack_versions(
+        &self,
+        stack: &str,
+        track: &str,
+    ) -> Result<Vec<ModuleResp>, anyhow::Error> {
+        _get_modules(self, crate::get_all_stack_versions_query(stack, track)).await
+    }
+    async fn get_module_version(
+        &self,
+        module: &str,
+        track: &str,
+        version: &str,
+    ) -> Result<Option<ModuleResp>, anyhow::Error> {
+        _get_module_optional(
+            self,
+            crate::get_module_version_query(module, track, version)?,
+        )
+        .await
+    }
+    async fn get_stack_version(
+        &self,
+        stack: &str,
+        track: &str,
+        version: &str,
+    ) -> Result<Option<ModuleResp>, anyhow::Error> {
+        _get_module_optional(self, crate::get_stack_version_query(stack, track, version)?).await
+    }
+    // Deployment
+    async fn get_all_deployments(
+        &self,
+        environment: &str,
+        include_deleted: bool,
+    ) -> Result<Vec<DeploymentResp>, anyhow::Error> {
+        _get_deployments(
+            self,
+            crate::get_all_deployments_query(
+                &self.project_id,
+                &self.region,
+                environment,
+                include_deleted,
+            ),
+        )
+        .await
+    }
+    async fn get_deployment_and_dependents(
+        &self,
+        deployment_id: &str,
+        environment: &str,
+        include_deleted: bool,
+    ) -> Result<(Option<DeploymentResp>, Vec<Dependent>), anyhow::Error> {
+        _get_deployment_and_dependents(
+            self,
+            crate::get_deployment_and_dependents_query(
+                &self.project_id,
+                &self.region,
+                deployment_id,
+                environment,
+                include_deleted,
+            ),
+        )
+        .await
+    }
+    async fn get_deployment(
+        &self,
+        deployment_id: &str,
+        environment: &str,
+        include_deleted: bool,
+    ) -> Result<Option<DeploymentResp>, anyhow::Error> {
+        _get_deployment(
+            self,
+            crate::get_deployment_query(
+                &self.project_id,
+                &self.region,
+                deployment_id,
+                environment,
+                include_deleted,
+            ),
+        )
+        .await
+    }
+    async fn get_deployments_using_module(
+        &self,
+        module: &str,
+        environment: &str,
+        include_deleted: bool,
+    ) -> Result<Vec<DeploymentResp>, anyhow::Error> {
+        _get_deployments(
+            self,
+            crate::get_deployments_using_module_query(
+                &self.project_id,
+                &self.region,
+                module,
+                environment,
+                include_deleted,
+            ),
+        )
+        .await
+    }
+    async fn get_plan_deployment(
+        &self,
+        deployment_id: &str,
+        environment: &str,
+        job_id: &str,
+    ) -> Result<Option<DeploymentResp>, anyhow::Error> {
+        _get_deployment(
+            self,
+            crate::get_plan_deployment_query(
+                &self.project_id,
+                &self.region,
+                deployment_id,
+                environment,
+                job_id,
+            ),
+        )
+        .await
+    }
+    async fn get_dependents(
+        &self,
+        deployment_id: &str,
+        environment: &str,
+    ) -> Result<Vec<Dependent>, anyhow::Error> {
+        _get_dependents(
+            self,
+            crate::get_dependents_query(&self.project_id, &self.region, deployment_id, environment),
+        )
+        .await
+    }
+    async fn get_deployments_to_driftcheck(&self) -> Result<Vec<DeploymentResp>, anyhow::Error> {
+        _get_deployments(
+            self,
+            crate::get_deployments_to_driftcheck_query(&self.project_id, &self.region),
+        )
+        .await
+    }
+    async fn get_all_projects(&self) -> Result<Vec<ProjectData>, anyhow::Error> {
+        Ok(vec![self.get_current_project().await?])
+    }
+    async fn get_current_project(&self) -> Result<ProjectData, anyhow::Error> {
+        Ok(ProjectData {
+            project_id: self.project_id.clone(),
+            name: self.project_id.clone(),
+            description: "Local project".to_string(),
+            regions: vec![self.region.clone()],
+            repositories: vec![],
+        })
+    }
+    // Event
+    async fn get_events(
+        &self,
+        deployment_id: &str,
+        environment: &str,
+    ) -> Result<Vec<EventData>, anyhow::Error> {
+        _get_events(
+            self,
+            crate::get_events_query(&self.project_id, &self.region, deployment_id, environment),
+        )
+        .await
+    }
+    async fn get_all_events_between(
+        &self,
+        start_epoch: u128,
+        end_epoch: u128,
+    ) -> Result<Vec<EventData>, anyhow::Error> {
+        _get_events(
+            self,
+            crate::get_all_events_between_query(&self.region, start_epoch, end_epoch),
+        )
+        .await
+    }
+    // Change record
+    async fn get_change_record(
+        &self,
+        environment: &str,
+        deployment_id: &str,
+        job_id: &str,
+        change_type: &str,
+    ) -> Result<InfraChangeRecord, anyhow::Error> {
+        _get_change_records(
+            self,
+            crate::get_change_records_query(
+                &self.project_id,
+                &self.region,
+                environment,
+                deployment_id,
+                job_id,
+                change_type,
+            ),
+        )
+        .await
+    }
+    // Rollout
+    async fn get_rollout(&self, module: &str) -> Result<Option<Value>, anyhow::Error> {
+        self.read_db_generic(
+            "deployments",
+            &crate::get_rollout_query(&self.project_id, &self.region, module),
+        )
+        .await
+        .map(|mut items| items.pop())
+    }
+    // Policy
+    async fn get_newest_policy_version(
+        &self,
+        policy: &str,
+        environment: &str,
+    ) -> Result<PolicyResp, anyhow::Error> {
+        _get_policy(
+            self,
+            crate::get_newest_policy_version_query(policy, environment),
+        )
+        .await
+    }
+    async fn get_all_policies(&self, environment: &str) -> Result<Vec<PolicyResp>, anyhow::Error> {
+        _get_policies(self, crate::get_all_policies_query(environment)).await
+    }
+    async fn get_policy_download_url(&self, key: &str) -> Result<String, anyhow::Error> {
+        self.generate_presigned_url(key, "policies").await
+    }
+    async fn get_policy(
+        &self,
+        policy: &str,
+        environment: &str,
+        version: &str,
+    ) -> Result<PolicyResp, anyhow::Error> {
+        _get_policy(self, crate::get_policy_query(policy, environment, version)?).await
+    }
+    async fn get_environment_variables(&self) -> Result<serde_json::Value, anyhow::Error> {
+        let response = crate::run_function(&json!({"event": "get_environment_variables"})).await?;
+        Ok(response.payload["body"].clone())
+    }
+
+    async fn download_state_file(
+        &self,
+        environment: &str,
+        deployment_id: &str,
+        output: Option<String>,
+    ) -> Result<(), anyhow::Error> {
+        let path = crate::get_state_path(&self.get_storage_basepath(), environment, deployment_id);
+        let data = std::fs::read(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read state {}: {}", path.display(), e))?;
+
+        if let Some(output_path) = output {
+            std::fs::write(output_path, &data)?;
+        } else {
+            let state_str = String::from_utf8_lossy(&data);
+            println!("{}", state_str);
+        }
+
+        Ok(())
+    }
+
+    async fn move_state_file(
+        &self,
+        environment: &str,
+        deployment_id: &str,
+        new_environment: &str,
+        new_deployment_id: &str,
+    ) -> Result<(), anyhow::Error> {
+        let path = crate::get_state_path(&self.get_storage_basepath(), environment, deployment_id);
+        let new_path = crate::get_state_path(
+            &self.get_storage_basepath(),
+            new_environment,
+            new_deployment_id,
+        );
+        if let Some(parent) = new_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(&path, &new_path).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to move state {} to {}: {}",
+                path.display(),
+                new_path.display(),
+                e
+            )
+        })
+    }
+
+    async fn get_secret_value(&self, name: &str) -> Result<String, anyhow::Error> {
+        crate::get_secret_value(name).await
+    }
+}
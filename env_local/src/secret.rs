@@ -0,0 +1,88 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::get_local_dir;
+
+/// Environment variable holding the secret `name`, `INFRAWEAVE_SECRET_DB_PASSWORD` for `db-password`
+pub fn get_secret_env_var(name: &str) -> String {
+    let name: String = name
+        .trim_start_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("INFRAWEAVE_SECRET_{}", name)
+}
+
+/// File holding the secret `name` below `secrets_dir`, a leading `/` is ignored. Names that would
+/// resolve outside of `secrets_dir`, e.g. containing `..` or a drive, are rejected.
+fn get_secret_path(secrets_dir: &Path, name: &str) -> Result<PathBuf, anyhow::Error> {
+    let relative = Path::new(name.trim_start_matches('/'));
+    let is_valid = relative.components().next().is_some()
+        && relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+    if !is_valid {
+        return Err(anyhow::anyhow!("Invalid secret name {}", name));
+    }
+    Ok(secrets_dir.join(relative))
+}
+
+/// Reads a secret from its environment variable, or from the file `<local dir>/secrets/<name>`
+pub async fn get_secret_value(name: &str) -> Result<String, anyhow::Error> {
+    if let Ok(value) = std::env::var(get_secret_env_var(name)) {
+        return Ok(value);
+    }
+    let path = get_secret_path(&get_local_dir().join("secrets"), name)?;
+    match std::fs::read_to_string(&path) {
+        Ok(value) => Ok(value.trim_end_matches(['\n', '\r']).to_string()),
+        Err(e) => Err(anyhow::anyhow!(
+            "Secret {} not found, set {} or create {}: {}",
+            name,
+            get_secret_env_var(name),
+            path.display(),
+            e
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_get_secret_env_var() {
+        assert_eq!(
+            get_secret_env_var("db-password"),
+            "INFRAWEAVE_SECRET_DB_PASSWORD"
+        );
+        assert_eq!(
+            get_secret_env_var("/team/api.key"),
+            "INFRAWEAVE_SECRET_TEAM_API_KEY"
+        );
+    }
+
+    #[test]
+    fn test_get_secret_path() {
+        let secrets_dir = Path::new("/local/secrets");
+        assert_eq!(
+            get_secret_path(secrets_dir, "db-password").unwrap(),
+            PathBuf::from("/local/secrets/db-password")
+        );
+        assert_eq!(
+            get_secret_path(secrets_dir, "/team/api.key").unwrap(),
+            PathBuf::from("/local/secrets/team/api.key")
+        );
+
+        assert!(get_secret_path(secrets_dir, "../db-password").is_err());
+        assert!(get_secret_path(secrets_dir, "team/../../db-password").is_err());
+        assert!(get_secret_path(secrets_dir, "./db-password").is_err());
+        assert!(get_secret_path(secrets_dir, "/").is_err());
+        assert!(get_secret_path(secrets_dir, "").is_err());
+    }
+}
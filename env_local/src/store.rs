@@ -0,0 +1,425 @@
+use std::{
+    cmp::Ordering,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Query understood by the local store.
+///
+/// It covers the subset of DynamoDB key conditions and filter expressions
+/// that the other providers rely on, see `api.rs` for how each query is built.
+#[derive(Debug, Default, Deserialize)]
+pub struct LocalQuery {
+    pub pk: Option<String>,
+    pub pk_prefix: Option<String>,
+    pub sk: Option<String>,
+    pub sk_prefix: Option<String>,
+    #[serde(default)]
+    pub equals: Map<String, Value>,
+    #[serde(default)]
+    pub not_equals: Map<String, Value>,
+    #[serde(default)]
+    pub begins_with: Map<String, Value>,
+    pub between: Option<Between>,
+    #[serde(default)]
+    pub descending: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Between {
+    pub field: String,
+    pub start: Value,
+    pub end: Value,
+}
+
+/// File backed item store, every item is stored as a json file in
+/// `<root>/<table>/<PK>/<SK>.json` so that writes from the cli and from
+/// concurrently running jobs never rewrite each others items.
+#[derive(Clone, Debug)]
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn put(&self, table: &str, item: &Value) -> Result<(), anyhow::Error> {
+        let (pk, sk) = get_keys(item)?;
+        let path = self.item_path(table, &pk, &sk);
+        write_atomic(&path, serde_json::to_string(item)?.as_bytes())
+    }
+
+    pub fn delete(&self, table: &str, key: &Value) -> Result<(), anyhow::Error> {
+        let (pk, sk) = get_keys(key)?;
+        let path = self.item_path(table, &pk, &sk);
+        match fs::remove_file(&path) {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("Failed to delete {}", path.display())),
+        }
+    }
+
+    /// Applies the Put and Delete items of a `transact_write` payload in order
+    pub fn transact_write(&self, items: &[Value]) -> Result<(), anyhow::Error> {
+        for item in items {
+            if let Some(put) = item.get("Put") {
+                self.put(get_table_name(put)?, &put["Item"])?;
+            } else if let Some(delete) = item.get("Delete") {
+                self.delete(get_table_name(delete)?, &delete["Key"])?;
+            } else {
+                return Err(anyhow::anyhow!("Unsupported transaction item: {}", item));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn query(&self, table: &str, query: &Value) -> Result<Vec<Value>, anyhow::Error> {
+        let query: LocalQuery = serde_json::from_value(query.clone())
+            .with_context(|| format!("Invalid local query: {}", query))?;
+
+        let table_dir = self.root.join(table);
+        if !table_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let pk_dirs: Vec<PathBuf> = match &query.pk {
+            Some(pk) => vec![table_dir.join(encode_key(pk))],
+            None => list_dir(&table_dir)?
+                .into_iter()
+                .filter(|path| match &query.pk_prefix {
+                    Some(prefix) => file_name_decoded(path).starts_with(prefix.as_str()),
+                    None => true,
+                })
+                .collect(),
+        };
+
+        let mut items = vec![];
+        for pk_dir in pk_dirs {
+            if !pk_dir.is_dir() {
+                continue;
+            }
+            let sk_files = match &query.sk {
+                Some(sk) => vec![pk_dir.join(format!("{}.json", encode_key(sk)))],
+                None => list_dir(&pk_dir)?,
+            };
+            for sk_file in sk_files {
+                if !sk_file.is_file() {
+                    continue;
+                }
+                let content = fs::read_to_string(&sk_file)
+                    .with_context(|| format!("Failed to read {}", sk_file.display()))?;
+                let item: Value = serde_json::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", sk_file.display()))?;
+                if matches(&query, &item) {
+                    items.push(item);
+                }
+            }
+        }
+
+        items.sort_by(|a, b| {
+            let ordering = a["PK"]
+                .as_str()
+                .cmp(&b["PK"].as_str())
+                .then_with(|| a["SK"].as_str().cmp(&b["SK"].as_str()));
+            if query.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        if let Some(limit) = query.limit {
+            items.truncate(limit);
+        }
+
+        Ok(items)
+    }
+
+    fn item_path(&self, table: &str, pk: &str, sk: &str) -> PathBuf {
+        self.root
+            .join(table)
+            .join(encode_key(pk))
+            .join(format!("{}.json", encode_key(sk)))
+    }
+}
+
+fn matches(query: &LocalQuery, item: &Value) -> bool {
+    let sk = item["SK"].as_str().unwrap_or("");
+    if let Some(prefix) = &query.sk_prefix
+        && !sk.starts_with(prefix.as_str())
+    {
+        return false;
+    }
+    // A missing attribute never equals anything, but is always not equal (same as DynamoDB)
+    if !query
+        .equals
+        .iter()
+        .all(|(field, value)| item.get(field).is_some_and(|v| values_equal(v, value)))
+    {
+        return false;
+    }
+    if query
+        .not_equals
+        .iter()
+        .any(|(field, value)| item.get(field).is_some_and(|v| values_equal(v, value)))
+    {
+        return false;
+    }
+    if !query.begins_with.iter().all(|(field, prefix)| {
+        match (item.get(field).and_then(|v| v.as_str()), prefix.as_str()) {
+            (Some(v), Some(prefix)) => v.starts_with(prefix),
+            _ => false,
+        }
+    }) {
+        return false;
+    }
+    if let Some(between) = &query.between {
+        return match item.get(&between.field) {
+            Some(value) => {
+                compare_values(value, &between.start) != Ordering::Less
+                    && compare_values(value, &between.end) != Ordering::Greater
+            }
+            None => false,
+        };
+    }
+    true
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(_), Value::Number(_)) => as_number(a) == as_number(b),
+        _ => a == b,
+    }
+}
+
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (as_number(a), as_number(b)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+fn get_keys(item: &Value) -> Result<(String, String), anyhow::Error> {
+    let pk = item["PK"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Item is missing PK: {}", item))?;
+    let sk = item["SK"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Item is missing SK: {}", item))?;
+    Ok((pk.to_string(), sk.to_string()))
+}
+
+fn get_table_name(item: &Value) -> Result<&str, anyhow::Error> {
+    item["TableName"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Transaction item is missing TableName: {}", item))
+}
+
+fn list_dir(dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let mut entries = vec![];
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to list {}", dir.display()))? {
+        entries.push(entry?.path());
+    }
+    Ok(entries)
+}
+
+fn file_name_decoded(path: &Path) -> String {
+    decode_key(&path.file_name().unwrap_or_default().to_string_lossy())
+}
+
+/// Makes the temporary file of every write unique, as concurrent writes to the
+/// same key happen within one process
+static WRITE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> Result<(), anyhow::Error> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Invalid path {}", path.display()))?;
+    fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    let tmp_path = parent.join(format!(
+        ".{}.{}.{}.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id(),
+        WRITE_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+    ));
+    fs::write(&tmp_path, content)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Keys contain characters such as `#`, `:`, `/` and `|`, so they are
+/// percent-encoded to be usable as file names
+fn encode_key(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' || byte == b'.' {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn decode_key(encoded: &str) -> String {
+    let encoded = encoded.strip_suffix(".json").unwrap_or(encoded);
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && let Ok(byte) = u8::from_str_radix(&encoded[i + 1..i + 3], 16)
+        {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn store_with_items() -> (tempfile::TempDir, LocalStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalStore::new(dir.path());
+        for (pk, sk, deleted) in [
+            ("DEPLOYMENT#p::r::dev/a::s3bucket/one", "METADATA", 0),
+            ("DEPLOYMENT#p::r::dev/a::s3bucket/two", "METADATA", 1),
+            ("DEPLOYMENT#p::r::dev/b::s3bucket/three", "METADATA", 0),
+        ] {
+            store
+                .put(
+                    "deployments",
+                    &json!({"PK": pk, "SK": sk, "deleted": deleted}),
+                )
+                .unwrap();
+        }
+        (dir, store)
+    }
+
+    #[test]
+    fn test_write_atomic_concurrently() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("item.json");
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || write_atomic(&path, format!("{}", i).as_bytes()))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+        let content: u32 = fs::read_to_string(&path).unwrap().parse().unwrap();
+        assert!(content < 8);
+        assert_eq!(list_dir(dir.path()).unwrap(), vec![path]);
+    }
+
+    #[test]
+    fn test_encode_decode_key() {
+        let key = "DEPLOYMENT#p::r::dev/a::s3bucket/one|0";
+        assert_eq!(decode_key(&encode_key(key)), key);
+        assert!(!encode_key(key).contains('/'));
+    }
+
+    #[test]
+    fn test_query_pk_prefix_with_filter() {
+        let (_dir, store) = store_with_items();
+        let items = store
+            .query(
+                "deployments",
+                &json!({
+                    "pk_prefix": "DEPLOYMENT#p::r::dev/a",
+                    "equals": {"SK": "METADATA", "deleted": 0},
+                }),
+            )
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["PK"], "DEPLOYMENT#p::r::dev/a::s3bucket/one");
+    }
+
+    #[test]
+    fn test_query_not_equals_matches_missing_attribute() {
+        let (_dir, store) = store_with_items();
+        store
+            .put(
+                "deployments",
+                &json!({"PK": "DEPLOYMENT#p::r::dev/a::s3bucket/one", "SK": "DEPENDENT#x"}),
+            )
+            .unwrap();
+        let items = store
+            .query(
+                "deployments",
+                &json!({
+                    "pk": "DEPLOYMENT#p::r::dev/a::s3bucket/one",
+                    "not_equals": {"deleted": 1},
+                }),
+            )
+            .unwrap();
+        assert_eq!(items.len(), 2);
+    }
+
+    #[test]
+    fn test_query_descending_limit_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalStore::new(dir.path());
+        for version in ["VERSION#000.001.000", "VERSION#000.002.000"] {
+            store
+                .put("policies", &json!({"PK": "POLICY#p", "SK": version}))
+                .unwrap();
+        }
+        let query = json!({"pk": "POLICY#p", "descending": true, "limit": 1});
+        let items = store.query("policies", &query).unwrap();
+        assert_eq!(items[0]["SK"], "VERSION#000.002.000");
+
+        store
+            .transact_write(&[json!({
+                "Delete": {"TableName": "policies", "Key": {"PK": "POLICY#p", "SK": "VERSION#000.002.000"}}
+            })])
+            .unwrap();
+        let items = store.query("policies", &query).unwrap();
+        assert_eq!(items[0]["SK"], "VERSION#000.001.000");
+    }
+
+    #[test]
+    fn test_query_between() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalStore::new(dir.path());
+        for epoch in ["100", "200", "300"] {
+            store
+                .put("events", &json!({"PK": "EVENT#a", "SK": epoch}))
+                .unwrap();
+        }
+        let items = store
+            .query(
+                "events",
+                &json!({"between": {"field": "SK", "start": "150", "end": "300"}}),
+            )
+            .unwrap();
+        assert_eq!(items.len(), 2);
+    }
+}
@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+
+/// Directory holding the database, blobs, state and logs of the local provider.
+/// Defaults to `~/.infraweave/local` and can be overridden with `INFRAWEAVE_LOCAL_DIR`.
+pub fn get_local_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("INFRAWEAVE_LOCAL_DIR") {
+        return PathBuf::from(dir);
+    }
+    match std::env::var("HOME") {
+        Ok(home) => PathBuf::from(home).join(".infraweave").join("local"),
+        Err(_) => std::env::temp_dir().join("infraweave-local"),
+    }
+}
+
+pub async fn get_project_id() -> Result<String, anyhow::Error> {
+    Ok(std::env::var("INFRAWEAVE_LOCAL_PROJECT").unwrap_or_else(|_| "local".to_string()))
+}
+
+pub async fn get_region() -> String {
+    std::env::var("REGION").unwrap_or_else(|_| "local".to_string())
+}
+
+pub async fn get_user_id() -> Result<String, anyhow::Error> {
+    Ok(std::env::var("USER").unwrap_or_else(|_| "local".to_string()))
+}
@@ -32,6 +32,8 @@ pub struct GroupedFile {
     pub deleted: Option<(FileChange, String)>,
     // The renamed file and its YAML document (if applicable)
     pub renamed: Option<(FileChange, String)>,
+    // The old and new manifest of a claim with a new name and/or namespace (if applicable)
+    pub moved: Option<MovedManifest>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,3 +42,10 @@ pub struct ManifestChange {
     pub content: String, // YAML document representation of the manifest
     pub file: FileChange,
 }
+
+/// A claim that is deleted and added with a new name and/or namespace but is otherwise identical
+#[derive(Debug, Clone)]
+pub struct MovedManifest {
+    pub from: ManifestChange,
+    pub to: ManifestChange,
+}
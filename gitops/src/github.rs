@@ -3,8 +3,8 @@ use base64::Engine;
 use chrono::{DateTime, Utc};
 use env_common::interface::GenericCloudHandler;
 use env_common::logic::{
-    get_deployment_details, publish_module_from_zip, publish_notification, run_claim,
-    set_deployment,
+    get_deployment_details, move_deployment, publish_module_from_zip, publish_notification,
+    run_claim, set_deployment,
 };
 use env_defs::{ArtifactType, CloudProvider, ModuleResp, OciArtifactSet};
 use env_defs::{
@@ -30,6 +30,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use std::{env, error::Error};
 use subtle::ConstantTimeEq;
 
+use crate::defs::MovedManifest;
 use crate::{
     get_project_id_for_repository_path, get_securestring_aws, group_files_by_manifest, FileChange,
     ProcessedFiles,
@@ -1054,6 +1055,63 @@ pub async fn handle_process_push_event(event: &Value) -> Result<Value, anyhow::E
                             }
                         }
                     }
+                } else if let Some(moved) = group.moved {
+                    if !project_id_found {
+                        inform_missing_project_configuration(
+                            &mut extra_data,
+                            moved.to.file.path.as_str(),
+                            private_key_pem,
+                        )
+                        .await;
+                        return; // Exit early if project id is not found
+                    }
+                    println!(
+                        "Move job for: {:?} to {:?} from path: {}",
+                        moved.from.key, moved.to.key, moved.to.file.path
+                    );
+                    let full_file_url = format!(
+                        "{}/blob/{}/{}",
+                        repository_url, default_branch, moved.to.file.path
+                    );
+                    let is_default_branch = branch == format!("refs/heads/{}", default_branch);
+                    let result = move_claim(
+                        &moved,
+                        project_id,
+                        repo_full_name,
+                        &full_file_url,
+                        is_default_branch,
+                    )
+                    .await;
+                    if let ExtraData::GitHub(ref mut github_check_run) = extra_data {
+                        github_check_run.job_details.file_path = moved.to.file.path.clone();
+                        github_check_run.check_run.name = get_check_run_name(
+                            &moved.to.key.name,
+                            &moved.to.file.path,
+                            &moved.to.key.region,
+                            &moved.to.key.namespace,
+                        );
+                        github_check_run.check_run.status = "completed".to_string();
+                        github_check_run.check_run.completed_at = Some(Utc::now().to_rfc3339());
+                        match result {
+                            Ok(output) => {
+                                github_check_run.check_run.conclusion = Some("success".to_string());
+                                github_check_run.check_run.output = Some(output);
+                            }
+                            Err(e) => {
+                                println!("Move job failed: {:?}", e);
+                                github_check_run.check_run.conclusion = Some("failure".to_string());
+                                github_check_run.check_run.output = Some(CheckRunOutput {
+                                    title: "Move job failed".into(),
+                                    summary: format!(
+                                        "Failed to move {} to {}",
+                                        moved.from.key.name, github_check_run.check_run.name
+                                    ),
+                                    text: Some(format!("Error: {}", e)),
+                                    annotations: None,
+                                });
+                            }
+                        }
+                    }
                 } else {
                     println!("Group with key {:?} has no file!", group.key);
                 }
@@ -1799,6 +1857,71 @@ pub async fn get_check_run_rerequested_data(
     Ok(push_payload)
 }
 
+/// Moves the deployment of a claim that changed name and/or namespace to its new identifier.
+/// Pushes to other branches than the default branch only describe the move.
+async fn move_claim(
+    moved: &MovedManifest,
+    project_id: &str,
+    repo_full_name: &str,
+    full_file_url: &str,
+    is_default_branch: bool,
+) -> Result<CheckRunOutput, anyhow::Error> {
+    let from: DeploymentManifest = serde_yaml::from_str(&moved.from.content)?;
+    let to: DeploymentManifest = serde_yaml::from_str(&moved.to.content)?;
+    // Prevent collision between repos by using repo_full_name
+    let repo_full_name_dash = repo_full_name.replace("/", "-").to_lowercase();
+    let (_region, environment, deployment_id, _module, _name) = get_deployment_details(
+        &format!(
+            "github-{}/{}",
+            repo_full_name_dash, moved.from.key.namespace
+        ),
+        from,
+    )?;
+    let (region, new_environment, new_deployment_id, _module, _name) = get_deployment_details(
+        &format!("github-{}/{}", repo_full_name_dash, moved.to.key.namespace),
+        to,
+    )?;
+
+    if !is_default_branch {
+        return Ok(CheckRunOutput {
+            title: "Move planned".into(),
+            summary: format!(
+                "`{}` in `{}` will be moved to `{}` in `{}` when merged.",
+                deployment_id, environment, new_deployment_id, new_environment
+            ),
+            text: Some(
+                "No resources will be changed, only the state and history are moved.".to_string(),
+            ),
+            annotations: None,
+        });
+    }
+
+    let handler = GenericCloudHandler::workload(project_id, &region).await;
+    let mut deployment = move_deployment(
+        &handler,
+        &deployment_id,
+        &environment,
+        &new_deployment_id,
+        &new_environment,
+    )
+    .await?;
+    deployment.reference = full_file_url.to_string();
+    set_deployment(&handler, &deployment, false).await?;
+
+    Ok(CheckRunOutput {
+        title: "Moved deployment".into(),
+        summary: format!(
+            "`{}` in `{}` has been moved to `{}` in `{}`.",
+            deployment_id, environment, new_deployment_id, new_environment
+        ),
+        text: Some(
+            "No run has been triggered, the state, events and dependencies have been moved."
+                .to_string(),
+        ),
+        annotations: None,
+    })
+}
+
 fn get_check_run_name(name: &str, path: &str, region: &str, namespace: &str) -> String {
     format!("{} ({}) - {} ({})", name, region, path, namespace)
 }
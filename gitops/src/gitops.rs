@@ -1,7 +1,9 @@
 use env_defs::DeploymentManifest;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::defs::{FileChange, GroupKey, GroupedFile, ManifestChange, ProcessedFiles};
+use crate::defs::{
+    FileChange, GroupKey, GroupedFile, ManifestChange, MovedManifest, ProcessedFiles,
+};
 
 fn extract_manifest_changes(file: &FileChange) -> Vec<ManifestChange> {
     let mut changes = Vec::new();
@@ -67,6 +69,7 @@ pub fn group_files_by_manifest(processed: ProcessedFiles) -> Vec<GroupedFile> {
     }
 
     let mut groups = Vec::new();
+    let mut new_changes: Vec<&ManifestChange> = Vec::new();
 
     for (key, active_change) in active_changes.iter() {
         if let Some(deleted_change) = deleted_changes.get(key).cloned() {
@@ -80,6 +83,7 @@ pub fn group_files_by_manifest(processed: ProcessedFiles) -> Vec<GroupedFile> {
                     active: None,
                     deleted: None,
                     renamed: Some((active_change.file.clone(), active_change.content.clone())),
+                    moved: None,
                 });
                 // nothing more to do for this doc
                 continue;
@@ -106,6 +110,7 @@ pub fn group_files_by_manifest(processed: ProcessedFiles) -> Vec<GroupedFile> {
                     active: None,
                     deleted: Some((deleted_change.file.clone(), deleted_change.content.clone())),
                     renamed: None,
+                    moved: None,
                 });
 
                 // 3b) APPLY the new‐region doc
@@ -117,6 +122,7 @@ pub fn group_files_by_manifest(processed: ProcessedFiles) -> Vec<GroupedFile> {
                     active: Some((active_change.file.clone(), active_change.content.clone())),
                     deleted: None,
                     renamed: None,
+                    moved: None,
                 });
 
                 // mark the old key consumed so it is not double‐emitted below
@@ -130,26 +136,72 @@ pub fn group_files_by_manifest(processed: ProcessedFiles) -> Vec<GroupedFile> {
                 active: Some((active_change.file.clone(), active_change.content.clone())),
                 deleted: None,
                 renamed: None,
+                moved: None,
+            });
+        } else {
+            // 5) Brand‑new manifest, handled below since it can be a moved claim
+            new_changes.push(active_change);
+        }
+    }
+
+    // Anything left only in deleted_changes is deleted or moved
+    let deleted_changes: Vec<ManifestChange> = deleted_changes
+        .into_values()
+        .filter(|change| !active_changes.contains_key(&change.key))
+        .collect();
+
+    // 6) A new manifest that only differs from a deleted one in name and/or namespace is the same
+    //    deployment being moved, as long as there is exactly one such pair - MOVE
+    let new_identities: Vec<Option<String>> = new_changes
+        .iter()
+        .map(|change| get_moved_identity(&change.content))
+        .collect();
+    let deleted_identities: Vec<Option<String>> = deleted_changes
+        .iter()
+        .map(|change| get_moved_identity(&change.content))
+        .collect();
+    let mut moved_deleted = HashSet::new();
+    for (new_change, identity) in new_changes.iter().zip(new_identities.iter()) {
+        let same = |other: &Option<String>| identity.is_some() && other == identity;
+        let candidates: Vec<usize> = (0..deleted_changes.len())
+            .filter(|index| same(&deleted_identities[*index]))
+            .collect();
+        let competing = new_identities.iter().filter(|other| same(other)).count();
+        if let [index] = candidates[..]
+            && competing == 1
+        {
+            moved_deleted.insert(index);
+            groups.push(GroupedFile {
+                key: new_change.key.clone(),
+                active: None,
+                deleted: None,
+                renamed: None,
+                moved: Some(MovedManifest {
+                    from: deleted_changes[index].clone(),
+                    to: (*new_change).clone(),
+                }),
             });
         } else {
             // 5) Brand‑new manifest - APPLY
             groups.push(GroupedFile {
-                key: key.clone(),
-                active: Some((active_change.file.clone(), active_change.content.clone())),
+                key: new_change.key.clone(),
+                active: Some((new_change.file.clone(), new_change.content.clone())),
                 deleted: None,
                 renamed: None,
+                moved: None,
             });
         }
     }
 
-    // Anything left only in deleted_changes is a pure deletion - DELETE
-    for (key, deleted_change) in deleted_changes.into_iter() {
-        if !active_changes.contains_key(&key) {
+    // 7) Pure deletion - DELETE
+    for (index, deleted_change) in deleted_changes.into_iter().enumerate() {
+        if !moved_deleted.contains(&index) {
             groups.push(GroupedFile {
-                key,
+                key: deleted_change.key,
                 active: None,
-                deleted: Some((deleted_change.file.clone(), deleted_change.content.clone())),
+                deleted: Some((deleted_change.file, deleted_change.content)),
                 renamed: None,
+                moved: None,
             });
         }
     }
@@ -157,6 +209,15 @@ pub fn group_files_by_manifest(processed: ProcessedFiles) -> Vec<GroupedFile> {
     groups
 }
 
+/// The manifest without its name and namespace, claims with the same moved identity only differ
+/// in where they are deployed
+fn get_moved_identity(content: &str) -> Option<String> {
+    let mut manifest: DeploymentManifest = serde_yaml::from_str(content).ok()?;
+    manifest.metadata.name = String::new();
+    manifest.metadata.namespace = None;
+    serde_yaml::to_string(&manifest).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,16 +388,16 @@ spec:
 
     #[test]
     fn test_renamed_file_and_modified_as_new() {
-        // Simulate a rename where the old file (deleted) has a different manifest name than the new (active) file. (which is a new manifest)
+        // Simulate a rename where the old file (deleted) has a different manifest name and version than the new (active) file. (which is a new manifest)
         let active = FileChange {
             path: "new.yaml".to_string(),
-            // New active file has name "minimal2"
+            // New active file has name "minimal2" and version "2.0.0"
             content: valid_manifest(
                 "infraweave.io/v1",
                 "Minimal",
                 "minimal2",
                 Some("default"),
-                "1.0.0",
+                "2.0.0",
                 "us-west-2",
             ),
         };
@@ -379,6 +440,115 @@ spec:
         );
     }
 
+    #[test]
+    fn test_renamed_claim_as_move() {
+        // Only the name of the claim changes, which is the same deployment with a new name
+        let active = FileChange {
+            path: "new.yaml".to_string(),
+            content: valid_manifest(
+                "infraweave.io/v1",
+                "Minimal",
+                "minimal2",
+                Some("default"),
+                "1.0.0",
+                "us-west-2",
+            ),
+        };
+        let deleted = FileChange {
+            path: "old.yaml".to_string(),
+            content: valid_manifest(
+                "infraweave.io/v1",
+                "Minimal",
+                "minimal1",
+                Some("default"),
+                "1.0.0",
+                "us-west-2",
+            ),
+        };
+        let processed = ProcessedFiles {
+            active_files: vec![active],
+            deleted_files: vec![deleted],
+        };
+        let groups = group_files_by_manifest(processed);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].active.is_none(), true);
+        assert_eq!(groups[0].deleted.is_none(), true);
+        assert_eq!(groups[0].renamed.is_none(), true);
+        let moved = groups[0].moved.as_ref().unwrap();
+        assert_eq!(moved.from.key.name, "minimal1");
+        assert_eq!(moved.from.file.path, "old.yaml");
+        assert_eq!(moved.to.key.name, "minimal2");
+        assert_eq!(moved.to.file.path, "new.yaml");
+    }
+
+    #[test]
+    fn test_namespace_change_as_move() {
+        let active = FileChange {
+            path: "claim.yaml".to_string(),
+            content: valid_manifest(
+                "infraweave.io/v1",
+                "Minimal",
+                "minimal1",
+                Some("prod"),
+                "1.0.0",
+                "us-west-2",
+            ),
+        };
+        let deleted = FileChange {
+            path: "claim.yaml".to_string(),
+            content: valid_manifest(
+                "infraweave.io/v1",
+                "Minimal",
+                "minimal1",
+                Some("dev"),
+                "1.0.0",
+                "us-west-2",
+            ),
+        };
+        let processed = ProcessedFiles {
+            active_files: vec![active],
+            deleted_files: vec![deleted],
+        };
+        let groups = group_files_by_manifest(processed);
+
+        assert_eq!(groups.len(), 1);
+        let moved = groups[0].moved.as_ref().unwrap();
+        assert_eq!(moved.from.key.namespace, "dev");
+        assert_eq!(moved.to.key.namespace, "prod");
+    }
+
+    #[test]
+    fn test_ambiguous_move_as_new_and_deleted() {
+        // Two identical claims are replaced by another one, it is unknown which one was moved
+        let manifest = |name: &str| {
+            valid_manifest(
+                "infraweave.io/v1",
+                "Minimal",
+                name,
+                Some("default"),
+                "1.0.0",
+                "us-west-2",
+            )
+        };
+        let processed = ProcessedFiles {
+            active_files: vec![FileChange {
+                path: "claims.yaml".to_string(),
+                content: manifest("minimal3"),
+            }],
+            deleted_files: vec![FileChange {
+                path: "claims.yaml".to_string(),
+                content: format!("{}\n---\n{}", manifest("minimal1"), manifest("minimal2")),
+            }],
+        };
+        let groups = group_files_by_manifest(processed);
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups.iter().all(|g| g.moved.is_none()), true);
+        assert_eq!(groups.iter().filter(|g| g.active.is_some()).count(), 1);
+        assert_eq!(groups.iter().filter(|g| g.deleted.is_some()).count(), 2);
+    }
+
     #[test]
     fn test_multiple_files() {
         let active1 = FileChange {
@@ -746,7 +916,7 @@ spec:
         );
         let before_multi_yaml = format!("{}\n---\n{}", doc_minimal, doc_minimal2);
 
-        // After state: two documents: one unchanged for "minimal" and one for "minimal3"
+        // After state: two documents: one unchanged for "minimal" and one for "minimal3" on another version
         let doc_minimal_after = valid_manifest(
             "infraweave.io/v1",
             "Minimal",
@@ -760,7 +930,7 @@ spec:
             "Minimal",
             "minimal3",
             Some("ns4"),
-            "0.0.1-dev",
+            "0.0.2-dev",
             "us-west-2",
         );
         let after_multi_yaml = format!("{}\n---\n{}", doc_minimal_after, doc_minimal3);
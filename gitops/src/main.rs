@@ -2,7 +2,10 @@ use std::env;
 
 use aws_lambda_events::event::sqs::SqsEvent;
 use env_common::interface::{initialize_project_id_and_region, GenericCloudHandler};
-use env_defs::{CheckRunOutput, CloudProvider, ExtraData};
+use env_defs::{
+    CheckRunOutput, CloudProvider, CostEstimate, ExtraData, JobDetails, PolicyResult,
+    RiskAssessment,
+};
 use env_utils::setup_logging;
 use gitops::{
     get_project_id_for_repository_path, get_securestring_aws, handle_check_run_event,
@@ -147,23 +150,57 @@ async fn process_runner_event(payload: Value) -> Result<Value, Error> {
 
             let status = github_event.job_details.status.as_str();
 
-            let information = if status == "success" {
-                github_event.check_run.conclusion = Some("success".into());
+            let (information, upgrade_warnings, cost_estimate, risk_assessment) =
+                if status == "success" {
+                    github_event.check_run.conclusion = Some("success".into());
 
-                let change_record = handler
-                    .get_change_record(
-                        &github_event.job_details.environment,
-                        &github_event.job_details.deployment_id,
-                        &github_event.job_details.job_id,
-                        &github_event.job_details.change_type,
+                    let change_record = handler
+                        .get_change_record(
+                            &github_event.job_details.environment,
+                            &github_event.job_details.deployment_id,
+                            &github_event.job_details.job_id,
+                            &github_event.job_details.change_type,
+                        )
+                        .await
+                        .expect("Failed to get change record");
+                    (
+                        change_record.plan_std_output,
+                        change_record.upgrade_warnings,
+                        change_record.cost_estimate,
+                        change_record.risk_assessment,
+                    )
+                } else {
+                    github_event.check_run.conclusion = Some("failure".into());
+                    (
+                        github_event.job_details.error_text.clone(),
+                        vec![],
+                        None,
+                        None,
                     )
-                    .await
-                    .expect("Failed to get change record");
-                change_record.plan_std_output
+                };
+            let upgrade_warnings = if upgrade_warnings.is_empty() {
+                "".to_string()
             } else {
-                github_event.check_run.conclusion = Some("failure".into());
-                github_event.job_details.error_text.clone()
+                format!(
+                    "\n## Module upgrade warnings\n\n{}\n",
+                    upgrade_warnings
+                        .iter()
+                        .map(|w| format!("- {}", w))
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                )
+            };
+            let cost_estimate = match cost_estimate {
+                Some(cost_estimate) => format_cost_estimate(&cost_estimate),
+                None => "".to_string(),
             };
+            let risk_assessment = match risk_assessment {
+                Some(risk_assessment) => format_risk_assessment(&risk_assessment),
+                None => "".to_string(),
+            };
+            let policy_results = format_policy_results(
+                &get_policy_results(&handler, &github_event.job_details).await,
+            );
 
             // Process GitHub event.
             github_event.check_run.status = "completed".into();
@@ -183,7 +220,7 @@ async fn process_runner_event(payload: Value) -> Result<Value, Error> {
 File: **{}**
 Deployment ID: **{}**
 Environment: **{}**
-
+{}{}{}{}
 ## Information
 
 ```diff
@@ -193,6 +230,10 @@ Environment: **{}**
                     github_event.job_details.file_path,
                     github_event.job_details.deployment_id,
                     github_event.job_details.environment,
+                    upgrade_warnings,
+                    cost_estimate,
+                    risk_assessment,
+                    policy_results,
                     information
                 )),
                 annotations: None,
@@ -224,6 +265,88 @@ Environment: **{}**
     Ok(serde_json::json!({ "status": "Runner event processed" }))
 }
 
+fn format_cost_estimate(cost_estimate: &CostEstimate) -> String {
+    let mut text = format!("\n## Estimated monthly cost\n\n{}\n", cost_estimate);
+    if !cost_estimate.resources.is_empty() {
+        text.push_str("\n| Resource | Before | After |\n|---|---|---|\n");
+        for resource in cost_estimate.resources.iter() {
+            text.push_str(&format!(
+                "| {} | {:.2} | {:.2} |\n",
+                resource.address, resource.monthly_cost_before, resource.monthly_cost_after
+            ));
+        }
+    }
+    text
+}
+
+/// Policy results of the job, from the deployment of the plan or the deployment itself if the job
+/// is still its latest
+async fn get_policy_results(
+    handler: &GenericCloudHandler,
+    job_details: &JobDetails,
+) -> Vec<PolicyResult> {
+    let deployment = if job_details.change_type == "PLAN" {
+        handler
+            .get_plan_deployment(
+                &job_details.deployment_id,
+                &job_details.environment,
+                &job_details.job_id,
+            )
+            .await
+    } else {
+        handler
+            .get_deployment(&job_details.deployment_id, &job_details.environment, true)
+            .await
+    };
+    match deployment {
+        Ok(Some(deployment)) if deployment.job_id == job_details.job_id => {
+            deployment.policy_results
+        }
+        Ok(_) => vec![],
+        Err(e) => {
+            println!("Failed to get policy results: {}", e);
+            vec![]
+        }
+    }
+}
+
+fn format_policy_results(policy_results: &[PolicyResult]) -> String {
+    let format_section = |title: &str, get_lines: fn(&PolicyResult) -> Vec<String>| {
+        let lines: Vec<String> = policy_results
+            .iter()
+            .flat_map(|r| {
+                get_lines(r)
+                    .into_iter()
+                    .map(|line| format!("- **{}** {}\n", r.policy, line))
+            })
+            .collect();
+        match lines.is_empty() {
+            true => "".to_string(),
+            false => format!("\n## {}\n\n{}", title, lines.concat()),
+        }
+    };
+    [
+        format_section("Policy violations", PolicyResult::get_violations),
+        format_section("Policy warnings", PolicyResult::get_warnings),
+        format_section(
+            "Waived policy violations",
+            PolicyResult::get_waived_violations,
+        ),
+    ]
+    .concat()
+}
+
+fn format_risk_assessment(risk_assessment: &RiskAssessment) -> String {
+    let mut text = format!("\n## Risk\n\n{}\n", risk_assessment);
+    if !risk_assessment.findings.is_empty() {
+        text.push('\n');
+        for finding in risk_assessment.findings.iter() {
+            text.push_str(&format!("- {}\n", finding.description));
+        }
+    }
+    text
+}
+
 async fn validator_func(event: LambdaEvent<Value>) -> Result<Value, Error> {
     let (_generic_event, _context) = event.into_parts();
 
@@ -6,7 +6,8 @@ use env_common::{
 };
 use env_defs::CloudProvider;
 use env_defs::{
-    DeploymentManifest, DeploymentMetadata, DeploymentResp, DeploymentSpec, ExtraData, ModuleResp,
+    DeploymentManifest, DeploymentMetadata, DeploymentResp, DeploymentSpec, DeploymentStatus,
+    ExtraData, ModuleResp,
 };
 use log::info;
 use pyo3::Bound;
@@ -526,7 +527,7 @@ impl Deployment {
                 )));
             }
         };
-        if status != "successful" {
+        if status != DeploymentStatus::Successful {
             self.has_error = true;
             return Err(DeploymentFailure::new_err(format!(
                 "Apply failed with status: {}, error: {}",
@@ -570,7 +571,7 @@ impl Deployment {
                 )));
             }
         };
-        if status != "successful" {
+        if status != DeploymentStatus::Successful {
             return Err(DeploymentFailure::new_err(format!(
                 "Plan failed with status: {}, error: {}",
                 status,
@@ -609,7 +610,7 @@ impl Deployment {
                 )));
             }
         };
-        if status != "successful" {
+        if status != DeploymentStatus::Successful {
             return Err(DeploymentFailure::new_err(format!(
                 "Destroy failed with status: {}, error: {}",
                 status,
@@ -706,7 +707,7 @@ pub fn get_namespace(namespace_arg: &str) -> String {
 async fn run_job(
     command: &str,
     deployment: &Deployment,
-) -> Result<(String, String, Option<DeploymentResp>), anyhow::Error> {
+) -> Result<(String, DeploymentStatus, Option<DeploymentResp>), anyhow::Error> {
     let handler = &GenericCloudHandler::region(&deployment.region).await;
     let result = match command {
         "destroy" => {
@@ -730,7 +731,7 @@ async fn run_job(
         }
     };
 
-    let final_status: String;
+    let final_status: DeploymentStatus;
     let deployment_result: Option<DeploymentResp>;
 
     loop {
@@ -758,7 +759,7 @@ async fn run_job(
         if !in_progress {
             let status = match &deployment_job_result {
                 Some(deployment_job_result) => deployment_job_result.status.clone(),
-                None => "unknown".to_string(),
+                None => DeploymentStatus::Unknown("unknown".to_string()),
             };
             println!(
                 "Finished {} with status {}! (job_id: {})\n{}",
@@ -770,7 +771,7 @@ async fn run_job(
                     .map(|d| d.error_text.clone())
                     .unwrap_or_else(|| "No error_text".to_string())
             );
-            final_status = status.to_string();
+            final_status = status;
             deployment_result = deployment_job_result;
             break;
         }
@@ -808,6 +809,9 @@ async fn plan_or_apply_deployment(
         variables: variables_yaml_mapping,
         dependencies: None,
         drift_detection: None,
+        ttl: None,
+        expires_at: None,
+        imports: None,
     };
 
     let deployment_manifest = DeploymentManifest {
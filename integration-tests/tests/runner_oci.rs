@@ -6,6 +6,7 @@ mod runner_tests {
     use super::*;
     use env_common::{interface::GenericCloudHandler, logic::run_claim};
     use env_defs::CloudProvider;
+    use env_defs::DeploymentStatus;
     use env_defs::ExtraData;
     use env_defs::OciArtifactSet;
     use pretty_assertions::assert_eq;
@@ -190,7 +191,7 @@ is_expected_branch if {
             {
                 Ok(deployment) => {
                     assert_eq!(deployment.is_some(), true);
-                    assert_eq!(deployment.unwrap().status, "successful"); // This is set as last step in the runner
+                    assert_eq!(deployment.unwrap().status, DeploymentStatus::Successful); // This is set as last step in the runner
                 }
                 Err(_e) => panic!("Failed to get deployment"),
             };
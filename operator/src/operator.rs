@@ -1,6 +1,8 @@
 use env_common::interface::GenericCloudHandler;
 use env_common::logic::{is_deployment_in_progress, run_claim};
-use env_defs::{CloudProvider, CloudProviderCommon, DeploymentResp, ExtraData, ModuleResp};
+use env_defs::{
+    CloudProvider, CloudProviderCommon, DeploymentResp, DeploymentStatus, ExtraData, ModuleResp,
+};
 use env_utils::{epoch_to_timestamp, get_timestamp, indent};
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use kube::api::{ApiResource, DynamicObject, PostParams};
@@ -647,6 +649,8 @@ async fn reconcile_resource_nonblocking(
     println!("Doing full status check for job {}", current_job_id);
     let (in_progress, _, depl_status, depl) =
         is_deployment_in_progress(handler, current_deployment_id, environment, false, true).await;
+    // A deployment that is not found has no status
+    let depl_status = depl_status.unwrap_or(DeploymentStatus::Unknown(String::new()));
 
     println!(
         "[API-RESPONSE] is_deployment_in_progress(apply) - deployment_id: {}, in_progress: {}, status: {:?}",
@@ -764,7 +768,10 @@ async fn reconcile_resource_nonblocking(
     }
 
     // Check if job failed or errored - treat "error" same as "failed" for retry logic
-    let is_failure = depl_status == "failed" || depl_status == "error";
+    let is_failure = matches!(
+        depl_status,
+        DeploymentStatus::Failed | DeploymentStatus::Error
+    );
 
     if is_failure {
         let retry_count = fresh_resource
@@ -889,7 +896,7 @@ async fn reconcile_resource_nonblocking(
     }
 
     // Job succeeded - update status and clear jobId, reset retry count
-    if depl_status == "successful" {
+    if depl_status == DeploymentStatus::Successful {
         let retry_count = fresh_resource
             .data
             .get("status")
@@ -1095,6 +1102,8 @@ async fn handle_resource_deletion_nonblocking(
     println!("Doing full status check for destroy job {}", current_job_id);
     let (in_progress, _, depl_status, depl) =
         is_deployment_in_progress(handler, current_deployment_id, environment, false, true).await;
+    // A deployment that is not found has no status
+    let depl_status = depl_status.unwrap_or(DeploymentStatus::Unknown(String::new()));
 
     println!(
         "[API-RESPONSE] is_deployment_in_progress(destroy) - deployment_id: {}, in_progress: {}, status: {:?}",
@@ -1149,7 +1158,10 @@ async fn handle_resource_deletion_nonblocking(
     };
 
     // Implement retry logic for failed/errored destroy operations
-    let is_failure = depl_status == "failed" || depl_status == "error";
+    let is_failure = matches!(
+        depl_status,
+        DeploymentStatus::Failed | DeploymentStatus::Error
+    );
 
     // Build error message if there is one
     let mut error_message = String::new();
@@ -1280,7 +1292,7 @@ async fn handle_resource_deletion_nonblocking(
     }
 
     // Destroy succeeded, remove finalizer to allow deletion
-    if depl_status == "successful" {
+    if depl_status == DeploymentStatus::Successful {
         println!("Destroy successful, removing finalizer from {}", name);
 
         // Update status with success and clear jobId
@@ -1721,7 +1733,7 @@ mod tests {
                 deployment_id: "TestModule/test-deployment".to_string(),
                 project_id: "12345678910".to_string(),
                 region: "us-west-2".to_string(),
-                status: "Pending".to_string(),
+                status: DeploymentStatus::from("Pending"),
                 job_id: "test-job".to_string(),
                 environment: "k8s-cluster-1/test-namespace".to_string(),
                 module: "test-module".to_string(),
@@ -1741,6 +1753,8 @@ mod tests {
                     interval: "1h".to_string(),
                     auto_remediate: false,
                     webhooks: vec![],
+                    jitter: None,
+                    quiet_hours: vec![],
                 },
                 next_drift_check_epoch: -1,
                 has_drifted: false,
@@ -1754,6 +1768,8 @@ mod tests {
                 memory: "2048".to_string(),
                 reference: "https://github.com/somerepo/somepath/here.yaml".to_string(),
                 tf_resources: None,
+                expires_at_epoch: None,
+                labels: BTreeMap::new(),
             },
         );
         let expected_claim = r#"
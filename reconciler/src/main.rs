@@ -1,6 +1,7 @@
 use env_common::interface::{initialize_project_id_and_region, GenericCloudHandler};
-use env_common::logic::driftcheck_infra;
+use env_common::logic::{driftcheck_infra, expire_deployments};
 use env_defs::{CloudProvider, ExtraData};
+use env_utils::{get_epoch, is_in_quiet_hours};
 use futures::future::join_all;
 use lambda_runtime::{service_fn, Error, LambdaEvent};
 use log::{error, info};
@@ -20,6 +21,21 @@ async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
             vec![]
         }
     };
+    // Deployments in quiet hours stay due and are picked up once the window has passed
+    let now = get_epoch();
+    let deployments = deployments
+        .into_iter()
+        .filter(|deployment| {
+            let quiet = is_in_quiet_hours(&deployment.drift_detection, now);
+            if quiet {
+                info!(
+                    "Skipping drift check of {} in {} during quiet hours",
+                    deployment.deployment_id, deployment.environment
+                );
+            }
+            !quiet
+        })
+        .collect::<Vec<_>>();
 
     // Launch drift checks for each deployment asynchronously to run them in parallel
     let drift_checks = deployments.clone().into_iter().map(|deployment| {
@@ -65,9 +81,21 @@ async fn func(event: LambdaEvent<Value>) -> Result<Value, Error> {
         })
         .collect::<Vec<Value>>();
 
+    let expired_deployments = match expire_deployments(&handler).await {
+        Ok(expired_deployments) => {
+            info!("Expiring deployments: {:?}", expired_deployments);
+            expired_deployments
+        }
+        Err(e) => {
+            error!("Failed to expire deployments: {}", e);
+            vec![]
+        }
+    };
+
     let response = json!({
         "status": "successful",
         "drift_checked_deployments": drift_checked_deployment_ids,
+        "expired_deployments": expired_deployments,
     });
     println!("{}", serde_json::to_string_pretty(&response).unwrap());
     Ok(response)
@@ -17,21 +17,12 @@ target "tofu-stage" {
   platforms = ["linux/arm64"]
 }
 
-# Build the opa stage (depends on chef)
-target "opa-stage" {
-  context = "."
-  dockerfile = "terraform_runner/Dockerfile.opa"
-  target = "opa"
-  platforms = ["linux/arm64"]
-}
-
 # Runner using terraform
 target "runner-terraform" {
   context = "."
   dockerfile = "terraform_runner/Dockerfile.runner"
   contexts = {
     terraform = "target:terraform-stage"
-    opa = "target:opa-stage"
   }
   args = {
     REGISTRY_API_HOSTNAME = "registry.terraform.io"
@@ -46,7 +37,6 @@ target "runner-tofu" {
   dockerfile = "terraform_runner/Dockerfile.runner"
   contexts = {
     terraform = "target:tofu-stage"  # Map tofu stage to terraform context
-    opa = "target:opa-stage"
   }
   args = {
     REGISTRY_API_HOSTNAME = "registry.opentofu.org"
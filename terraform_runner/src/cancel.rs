@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::Notify;
+
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+static TERRAFORM_KILLED: AtomicBool = AtomicBool::new(false);
+static CANCEL_NOTIFY: Notify = Notify::const_new();
+
+/// Listens for SIGINT (local jobs) and SIGTERM (stopped containers) and marks the job as cancelled,
+/// a running command is then interrupted by `run_generic_command`
+pub fn listen_for_cancel() {
+    tokio::spawn(async {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let mut sigterm =
+                signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = sigterm.recv() => {}
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+        println!("Received cancel signal, stopping job...");
+        request_cancel();
+    });
+}
+
+pub fn request_cancel() {
+    CANCEL_REQUESTED.store(true, Ordering::SeqCst);
+    CANCEL_NOTIFY.notify_waiters();
+}
+
+pub fn is_cancel_requested() -> bool {
+    CANCEL_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Completes when the job is cancelled
+pub async fn cancel_requested() {
+    let notified = CANCEL_NOTIFY.notified();
+    if is_cancel_requested() {
+        return;
+    }
+    notified.await;
+}
+
+/// Terraform had to be killed since it did not stop in time, so it may not have released the state lock
+pub fn set_terraform_killed() {
+    TERRAFORM_KILLED.store(true, Ordering::SeqCst);
+}
+
+pub fn was_terraform_killed() -> bool {
+    TERRAFORM_KILLED.load(Ordering::SeqCst)
+}
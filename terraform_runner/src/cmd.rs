@@ -1,15 +1,40 @@
 use anyhow::{anyhow, Result};
 use std::collections::VecDeque;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
 
+use crate::cancel::{cancel_requested, is_cancel_requested, set_terraform_killed};
+use crate::variables::redact_secrets;
+
+/// How long a command gets to stop after it is interrupted since the job was cancelled, before it
+/// is killed. Must be shorter than the time the platform waits before it kills the runner itself.
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(20);
+
 pub struct CommandResult {
     pub stdout: String,
     pub stderr: String,
 }
 
+/// Runs a command, it is interrupted if the job is cancelled
 pub async fn run_generic_command(
     exec: &mut tokio::process::Command,
     max_output_lines: usize,
+) -> Result<CommandResult, anyhow::Error> {
+    run_command(exec, max_output_lines, true).await
+}
+
+/// Runs a command that must complete even if the job is cancelled, e.g. to record the state
+pub async fn run_uninterruptible_command(
+    exec: &mut tokio::process::Command,
+    max_output_lines: usize,
+) -> Result<CommandResult, anyhow::Error> {
+    run_command(exec, max_output_lines, false).await
+}
+
+async fn run_command(
+    exec: &mut tokio::process::Command,
+    max_output_lines: usize,
+    interruptible: bool,
 ) -> Result<CommandResult, anyhow::Error> {
     if std::env::var("TEST_MODE").is_ok() {
         println!("In test mode, not actually executing the command");
@@ -18,6 +43,9 @@ pub async fn run_generic_command(
             stderr: "{}".to_string(),
         });
     }
+    if interruptible && is_cancel_requested() {
+        return Err(anyhow!("The job was cancelled"));
+    }
     let mut child = exec.spawn()?; // Start the command without waiting for it to finish
                                    // Check if `stdout` was successfully captured
 
@@ -33,11 +61,32 @@ pub async fn run_generic_command(
     let mut stdout_done = false;
     let mut stderr_done = false;
 
+    let mut interrupted = false;
+    let mut killed = false;
+    let kill_deadline = tokio::time::sleep(Duration::MAX);
+    tokio::pin!(kill_deadline);
+
     while !stdout_done || !stderr_done {
         tokio::select! {
+            _ = cancel_requested(), if interruptible && !interrupted => {
+                println!("Interrupting command since the job was cancelled...");
+                interrupt(&mut child);
+                interrupted = true;
+                kill_deadline
+                    .as_mut()
+                    .reset(tokio::time::Instant::now() + CANCEL_GRACE_PERIOD);
+            },
+            _ = &mut kill_deadline, if interrupted && !killed => {
+                println!("Command did not stop within {:?}, killing it", CANCEL_GRACE_PERIOD);
+                let _ = child.start_kill();
+                set_terraform_killed();
+                killed = true;
+            },
             stdout_line = stdout_reader.next_line(), if !stdout_done => {
                 match stdout_line {
                     Ok(Some(line)) => {
+                        // Secret values never reach the job logs or the output of the command
+                        let line = redact_secrets(&line);
                         println!("{}", line); // Print each line to stdout
                         // Collect the line into the buffer
                         last_stdout_lines.push_back(line);
@@ -57,6 +106,7 @@ pub async fn run_generic_command(
             stderr_line = stderr_reader.next_line(), if !stderr_done => {
                 match stderr_line {
                     Ok(Some(line)) => {
+                        let line = redact_secrets(&line);
                         // Collect the line into the buffer
                         last_stderr_lines.push_back(line);
                         if last_stderr_lines.len() > max_output_lines {
@@ -77,6 +127,10 @@ pub async fn run_generic_command(
 
     let exist_status = child.wait().await?;
 
+    if interrupted {
+        return Err(anyhow!("The job was cancelled"));
+    }
+
     let stderr_text = last_stderr_lines
         .iter()
         .fold(String::new(), |acc, line| acc + line.as_str() + "\n");
@@ -98,3 +152,16 @@ pub async fn run_generic_command(
         stderr: stderr_text,
     })
 }
+
+/// Interrupts the command gracefully, terraform then stops after the operations in progress and
+/// releases the state lock
+fn interrupt(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGINT);
+        }
+        return;
+    }
+    let _ = child.start_kill();
+}
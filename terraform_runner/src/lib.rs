@@ -1,25 +1,37 @@
+mod cancel;
 mod cmd;
 mod deployment;
 mod module;
+mod notification;
 mod opa;
+mod protected_resources;
 mod read;
 mod runner;
 mod terraform;
 mod utils;
+mod variables;
 mod webhook;
 
-pub use cmd::{run_generic_command, CommandResult};
+pub use cancel::{is_cancel_requested, listen_for_cancel, was_terraform_killed};
+pub use cmd::{run_generic_command, run_uninterruptible_command, CommandResult};
 pub use deployment::get_initial_deployment;
 pub use module::download_module_oci;
-pub use opa::{
-    download_policy, get_all_rego_filenames_in_cwd, run_opa_command, run_opa_policy_checks,
+pub use notification::{
+    parse_notification_config, render_notification, send_notifications, NotificationConfig,
+    NotificationEvent, NotificationEventType, NotificationRoute, NotificationSink,
+};
+pub use opa::{download_policy, get_all_rego_filenames_in_cwd, run_opa_policy_checks};
+pub use protected_resources::{
+    check_protected_resources, get_protected_resource_changes, parse_protected_resources_config,
+    ProtectedResourceRule, ProtectedResourcesConfig,
 };
 pub use read::read_module_from_file;
 pub use runner::{run_terraform_runner, setup_misc};
 pub use terraform::{
-    record_apply_destroy_changes, run_terraform_command, set_up_provider_mirror,
-    terraform_apply_destroy, terraform_init, terraform_output, terraform_plan, terraform_show,
-    terraform_state_list, terraform_validate,
+    record_apply_destroy_changes, release_state_lock, run_terraform_command,
+    set_up_provider_mirror, terraform_apply_destroy, terraform_init, terraform_output,
+    terraform_plan, terraform_saved_plan, terraform_show, terraform_state_list,
+    terraform_state_version, terraform_validate, TerraformStateVersion,
 };
 pub use utils::get_env_var;
-pub use webhook::post_webhook;
+pub use webhook::{get_webhook_payload, post_webhook, send_webhooks, sign_webhook_payload};
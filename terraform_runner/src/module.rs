@@ -1,10 +1,11 @@
 use anyhow::anyhow;
 use env_common::DeploymentStatusHandler;
-use env_defs::{ApiInfraPayload, CloudProvider, ModuleResp, OciArtifactSet};
+use env_defs::{ApiInfraPayload, CloudProvider, DeploymentStatus, ModuleResp, OciArtifactSet};
 use env_utils::get_module_zip_from_oci_targz;
 use log::{error, info};
 use std::path::Path;
 
+use env_common::logic::{get_import_module_name, imports_file};
 use env_common::{get_modules_download_url, interface::GenericCloudHandler};
 
 pub async fn download_module_zip(
@@ -149,8 +150,8 @@ pub async fn get_module(
                         // Module is deprecated and cannot be used
                         error!("Module deprecation check failed: {:?}", e);
                         let error_text = e.to_string();
-                        let status = "failed_init".to_string();
-                        status_handler.set_status(status);
+                        let status = DeploymentStatus::FailedInit;
+                        status_handler.set_status(status)?;
                         status_handler.set_event_duration();
                         status_handler.set_error_text(error_text.clone());
                         status_handler.send_event(handler).await;
@@ -161,8 +162,8 @@ pub async fn get_module(
             } else {
                 let error_text = "Module does not exist";
                 println!("{}", error_text);
-                let status = "failed_init".to_string();
-                status_handler.set_status(status);
+                let status = DeploymentStatus::FailedInit;
+                status_handler.set_status(status)?;
                 status_handler.set_event_duration();
                 status_handler.set_error_text(error_text.to_string());
                 status_handler.send_event(handler).await;
@@ -172,9 +173,9 @@ pub async fn get_module(
         }
         Err(e) => {
             error!("Failed to get module: {:?}", e);
-            let status = "failed_init".to_string();
+            let status = DeploymentStatus::FailedInit;
             let error_text: String = e.to_string();
-            status_handler.set_status(status);
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.set_error_text(error_text);
             status_handler.send_event(handler).await;
@@ -203,8 +204,8 @@ pub async fn download_module(
             Ok(module) => Ok(module),
             Err(e) => {
                 println!("Error preparing: {:?}", e);
-                let status = "failed_prepare".to_string();
-                status_handler.set_status(status);
+                let status = DeploymentStatus::FailedPrepare;
+                status_handler.set_status(status)?;
                 status_handler.set_event_duration();
                 status_handler.send_event(handler).await;
                 status_handler.send_deployment(handler).await?;
@@ -228,6 +229,36 @@ pub async fn download_module(
     Ok(())
 }
 
+/// Writes the import blocks for the resources the claim adopts next to the generated root module,
+/// so the plan imports them instead of creating new ones
+pub async fn store_imports_file(
+    payload: &ApiInfraPayload,
+    module: &ModuleResp,
+    handler: &GenericCloudHandler,
+    status_handler: &mut DeploymentStatusHandler<'_>,
+) -> Result<(), anyhow::Error> {
+    if payload.imports.is_empty() {
+        return Ok(());
+    }
+    let imports_tf = imports_file(&payload.imports, get_import_module_name(module));
+    match imports_tf.and_then(|imports_tf| Ok(std::fs::write("imports.tf", imports_tf)?)) {
+        Ok(_) => {
+            println!("Importing {} existing resources", payload.imports.len());
+            Ok(())
+        }
+        Err(e) => {
+            println!("Error storing imports: {:?}", e);
+            let status = DeploymentStatus::FailedPrepare;
+            status_handler.set_status(status)?;
+            status_handler.set_event_duration();
+            status_handler.set_error_text(format!("Failed to import resources: {}", e));
+            status_handler.send_event(handler).await;
+            status_handler.send_deployment(handler).await?;
+            Err(anyhow!("Failed to store imports: {}", e))
+        }
+    }
+}
+
 async fn compare_module_integrity(
     module_oci: &ModuleResp,
     module_from_db: &ModuleResp,
@@ -252,8 +283,8 @@ async fn compare_module_integrity(
                 serde_json::to_string_pretty(&oci_value).unwrap(),
                 serde_json::to_string_pretty(&db_value).unwrap()
             );
-            let status = "failed_integrity_check".to_string();
-            status_handler.set_status(status);
+            let status = DeploymentStatus::FailedIntegrityCheck;
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.send_event(handler).await;
             status_handler.send_deployment(handler).await?;
@@ -0,0 +1,486 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+use env_common::interface::GenericCloudHandler;
+use env_defs::ApiInfraPayload;
+use env_utils::{get_timestamp, matches_pattern};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tera::{Context, Tera};
+
+use crate::webhook::{post_json_with_retry, resolve_webhook_target};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEventType {
+    PlanSucceeded,
+    PlanFailed,
+    ApplySucceeded,
+    ApplyFailed,
+    DestroySucceeded,
+    DestroyFailed,
+    DriftDetected,
+    DeprecatedModuleUsed,
+}
+
+impl NotificationEventType {
+    /// Event for a finished job running `command`
+    pub fn for_job(command: &str, succeeded: bool) -> Option<Self> {
+        match (command, succeeded) {
+            ("plan", true) => Some(NotificationEventType::PlanSucceeded),
+            ("plan", false) => Some(NotificationEventType::PlanFailed),
+            ("apply", true) => Some(NotificationEventType::ApplySucceeded),
+            ("apply", false) => Some(NotificationEventType::ApplyFailed),
+            ("destroy", true) => Some(NotificationEventType::DestroySucceeded),
+            ("destroy", false) => Some(NotificationEventType::DestroyFailed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEventType::PlanSucceeded => "plan_succeeded",
+            NotificationEventType::PlanFailed => "plan_failed",
+            NotificationEventType::ApplySucceeded => "apply_succeeded",
+            NotificationEventType::ApplyFailed => "apply_failed",
+            NotificationEventType::DestroySucceeded => "destroy_succeeded",
+            NotificationEventType::DestroyFailed => "destroy_failed",
+            NotificationEventType::DriftDetected => "drift_detected",
+            NotificationEventType::DeprecatedModuleUsed => "deprecated_module_used",
+        }
+    }
+
+    fn default_template(&self) -> &'static str {
+        match self {
+            NotificationEventType::PlanSucceeded => {
+                "Plan of {{ deployment_id }} in {{ environment }} succeeded"
+            }
+            NotificationEventType::PlanFailed => {
+                "Plan of {{ deployment_id }} in {{ environment }} failed: {{ error_text }}"
+            }
+            NotificationEventType::ApplySucceeded => {
+                "Apply of {{ deployment_id }} in {{ environment }} succeeded"
+            }
+            NotificationEventType::ApplyFailed => {
+                "Apply of {{ deployment_id }} in {{ environment }} failed: {{ error_text }}"
+            }
+            NotificationEventType::DestroySucceeded => {
+                "Destroy of {{ deployment_id }} in {{ environment }} succeeded"
+            }
+            NotificationEventType::DestroyFailed => {
+                "Destroy of {{ deployment_id }} in {{ environment }} failed: {{ error_text }}"
+            }
+            NotificationEventType::DriftDetected => {
+                "Drift detected for {{ deployment_id }} in {{ environment }}\
+                {% if details.drifted_resources %}: \
+                {{ details.drifted_resources | join(sep=\", \") }}{% endif %}"
+            }
+            NotificationEventType::DeprecatedModuleUsed => {
+                "{{ deployment_id }} in {{ environment }} uses deprecated \
+                {{ module }} {{ module_version }}\
+                {% if details.deprecated_message %}: {{ details.deprecated_message }}{% endif %}"
+            }
+        }
+    }
+
+    fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            NotificationEventType::PlanFailed
+                | NotificationEventType::ApplyFailed
+                | NotificationEventType::DestroyFailed
+                | NotificationEventType::DriftDetected
+        )
+    }
+}
+
+/// Deployment context of an event, available to templates and sent as-is to webhook sinks
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct NotificationEvent {
+    pub event: NotificationEventType,
+    pub timestamp: String,
+    pub project_id: String,
+    pub region: String,
+    pub environment: String,
+    pub deployment_id: String,
+    pub module: String,
+    pub module_version: String,
+    pub job_id: String,
+    pub command: String,
+    pub error_text: String,
+    /// Event specific details, `drifted_resources` or `deprecated_message`
+    pub details: Value,
+}
+
+impl NotificationEvent {
+    pub fn new(event: NotificationEventType, payload: &ApiInfraPayload, job_id: &str) -> Self {
+        NotificationEvent {
+            event,
+            timestamp: get_timestamp(),
+            project_id: payload.project_id.clone(),
+            region: payload.region.clone(),
+            environment: payload.environment.clone(),
+            deployment_id: payload.deployment_id.clone(),
+            module: payload.module.clone(),
+            module_version: payload.module_version.clone(),
+            job_id: job_id.to_string(),
+            command: payload.command.clone(),
+            error_text: "".to_string(),
+            details: json!({}),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationSink {
+    /// Posts the event with the rendered message as `text`, signed like drift detection webhooks
+    Webhook {
+        url: Option<String>,
+        alias: Option<String>,
+        #[serde(rename = "signingSecret")]
+        signing_secret: Option<String>,
+    },
+    Slack {
+        url: Option<String>,
+        alias: Option<String>,
+    },
+    Teams {
+        url: Option<String>,
+        alias: Option<String>,
+    },
+    /// Appends the event as a JSON line, for local testing
+    File {
+        path: String,
+    },
+    Stdout,
+}
+
+/// Sends matching events to `sinks`, empty filters match everything and `*` in a project or
+/// environment matches any characters
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct NotificationRoute {
+    #[serde(default)]
+    pub projects: Vec<String>,
+    #[serde(default)]
+    pub environments: Vec<String>,
+    #[serde(default)]
+    pub events: Vec<NotificationEventType>,
+    pub sinks: Vec<String>,
+    pub template: Option<String>,
+}
+
+/// Read from the `NOTIFICATION_CONFIG` environment variable as JSON or YAML
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct NotificationConfig {
+    #[serde(default)]
+    pub sinks: BTreeMap<String, NotificationSink>,
+    #[serde(default)]
+    pub routes: Vec<NotificationRoute>,
+    /// Replaces the default template of an event type
+    #[serde(default)]
+    pub templates: BTreeMap<NotificationEventType, String>,
+}
+
+pub fn parse_notification_config(config: &str) -> Result<NotificationConfig, anyhow::Error> {
+    let config: NotificationConfig = serde_yaml::from_str(config)
+        .map_err(|e| anyhow::anyhow!("Invalid notification config: {}", e))?;
+    for route in config.routes.iter() {
+        if let Some(sink) = route.sinks.iter().find(|s| !config.sinks.contains_key(*s)) {
+            return Err(anyhow::anyhow!(
+                "Notification route refers to unknown sink {}",
+                sink
+            ));
+        }
+    }
+    Ok(config)
+}
+
+fn load_notification_config() -> Result<Option<NotificationConfig>, anyhow::Error> {
+    match std::env::var("NOTIFICATION_CONFIG") {
+        Ok(config) if !config.trim().is_empty() => parse_notification_config(&config).map(Some),
+        _ => Ok(None),
+    }
+}
+
+impl NotificationRoute {
+    fn matches(&self, event: &NotificationEvent) -> bool {
+        (self.projects.is_empty()
+            || self
+                .projects
+                .iter()
+                .any(|p| matches_pattern(p, &event.project_id)))
+            && (self.environments.is_empty()
+                || self
+                    .environments
+                    .iter()
+                    .any(|e| matches_pattern(e, &event.environment)))
+            && (self.events.is_empty() || self.events.contains(&event.event))
+    }
+}
+
+/// Sinks to send `event` to with the template to render, a sink in several matching routes only
+/// gets the event once using the template of the first route
+fn get_deliveries<'a>(
+    config: &'a NotificationConfig,
+    event: &NotificationEvent,
+) -> Vec<(&'a str, &'a NotificationSink, &'a str)> {
+    let mut sent_to = BTreeSet::new();
+    let mut deliveries = vec![];
+    for route in config.routes.iter().filter(|r| r.matches(event)) {
+        let template = route
+            .template
+            .as_deref()
+            .or(config.templates.get(&event.event).map(|t| t.as_str()))
+            .unwrap_or(event.event.default_template());
+        for name in route.sinks.iter() {
+            if let Some(sink) = config.sinks.get(name)
+                && sent_to.insert(name.as_str())
+            {
+                deliveries.push((name.as_str(), sink, template));
+            }
+        }
+    }
+    deliveries
+}
+
+pub fn render_notification(
+    template: &str,
+    event: &NotificationEvent,
+) -> Result<String, anyhow::Error> {
+    let context = Context::from_serialize(event)?;
+    Tera::one_off(template, &context, false)
+        .map_err(|e| anyhow::anyhow!("Failed to render notification template: {:?}", e))
+}
+
+fn get_teams_message(text: &str, event: &NotificationEvent) -> Value {
+    json!({
+        "@type": "MessageCard",
+        "@context": "https://schema.org/extensions",
+        "summary": text.lines().next().unwrap_or(text),
+        "themeColor": match event.event {
+            NotificationEventType::DeprecatedModuleUsed => "dbab09",
+            e if e.is_failure() => "d73a49",
+            _ => "2cbe4e",
+        },
+        "text": text,
+    })
+}
+
+fn get_webhook_message(text: &str, event: &NotificationEvent) -> Result<Value, anyhow::Error> {
+    let mut message = serde_json::to_value(event)?;
+    message["text"] = json!(text);
+    Ok(message)
+}
+
+async fn deliver(
+    handler: &GenericCloudHandler,
+    sink: &NotificationSink,
+    event: &NotificationEvent,
+    text: &str,
+) -> Result<(), anyhow::Error> {
+    let delivery_id = format!("{}-{}", event.job_id, event.event.as_str());
+    let (url, alias, signing_secret, body) = match sink {
+        NotificationSink::Webhook {
+            url,
+            alias,
+            signing_secret,
+        } => (
+            url,
+            alias,
+            signing_secret.as_deref(),
+            get_webhook_message(text, event)?,
+        ),
+        NotificationSink::Slack { url, alias } => (url, alias, None, json!({ "text": text })),
+        NotificationSink::Teams { url, alias } => {
+            (url, alias, None, get_teams_message(text, event))
+        }
+        NotificationSink::File { path } => {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            writeln!(file, "{}", get_webhook_message(text, event)?)?;
+            return Ok(());
+        }
+        NotificationSink::Stdout => {
+            println!("[{}] {}", event.event.as_str(), text);
+            return Ok(());
+        }
+    };
+    let (url, signing_key) =
+        resolve_webhook_target(handler, url.as_deref(), alias.as_deref(), signing_secret).await?;
+    post_json_with_retry(
+        &url,
+        signing_key.as_deref(),
+        event.event.as_str(),
+        &delivery_id,
+        body.to_string(),
+    )
+    .await
+}
+
+/// Renders and sends `event` to the sinks of all matching routes, failures are logged and never
+/// fail the job
+pub async fn send_notifications(handler: &GenericCloudHandler, event: &NotificationEvent) {
+    let config = match load_notification_config() {
+        Ok(Some(config)) => config,
+        Ok(None) => return,
+        Err(e) => {
+            println!("Not sending notifications: {}", e);
+            return;
+        }
+    };
+    for (name, sink, template) in get_deliveries(&config, event) {
+        let result = match render_notification(template, event) {
+            Ok(text) => deliver(handler, sink, event, &text).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(_) => println!("Sent {} notification to {}", event.event.as_str(), name),
+            Err(e) => println!(
+                "Error sending {} notification to {}: {}",
+                event.event.as_str(),
+                name,
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event: NotificationEventType, environment: &str) -> NotificationEvent {
+        NotificationEvent {
+            event,
+            timestamp: "2024-05-06T10:15:00.000Z".to_string(),
+            project_id: "123456789012".to_string(),
+            region: "eu-central-1".to_string(),
+            environment: environment.to_string(),
+            deployment_id: "s3bucket/my-bucket".to_string(),
+            module: "s3bucket".to_string(),
+            module_version: "1.2.0".to_string(),
+            job_id: "job-1".to_string(),
+            command: "apply".to_string(),
+            error_text: "".to_string(),
+            details: json!({}),
+        }
+    }
+
+    const CONFIG: &str = r#"
+sinks:
+  platform-slack:
+    type: slack
+    alias: platform-slack-url
+  audit:
+    type: webhook
+    url: https://audit.example.com
+    signingSecret: audit-key
+  local:
+    type: stdout
+routes:
+  - environments: ["k8s-prod/*"]
+    events: [apply_failed, drift_detected]
+    sinks: [platform-slack, audit]
+    template: "PROD {{ deployment_id }} {{ event }}"
+  - projects: ["123456789012"]
+    sinks: [audit, local]
+templates:
+  apply_succeeded: "{{ deployment_id }} applied"
+"#;
+
+    #[test]
+    fn test_parse_notification_config() {
+        let config = parse_notification_config(CONFIG).unwrap();
+        assert_eq!(
+            config.sinks["platform-slack"],
+            NotificationSink::Slack {
+                url: None,
+                alias: Some("platform-slack-url".to_string())
+            }
+        );
+        assert_eq!(config.routes.len(), 2);
+
+        let unknown_sink = "sinks: {}\nroutes:\n  - sinks: [missing]\n";
+        assert!(parse_notification_config(unknown_sink).is_err());
+        assert!(parse_notification_config("sinks:\n  x:\n    type: email\n").is_err());
+    }
+
+    #[test]
+    fn test_get_deliveries() {
+        let config = parse_notification_config(CONFIG).unwrap();
+
+        let prod_failure = event(NotificationEventType::ApplyFailed, "k8s-prod/team-a");
+        let deliveries: Vec<(&str, &str)> = get_deliveries(&config, &prod_failure)
+            .into_iter()
+            .map(|(name, _, template)| (name, template))
+            .collect();
+        assert_eq!(
+            deliveries,
+            vec![
+                ("platform-slack", "PROD {{ deployment_id }} {{ event }}"),
+                ("audit", "PROD {{ deployment_id }} {{ event }}"),
+                (
+                    "local",
+                    NotificationEventType::ApplyFailed.default_template()
+                ),
+            ]
+        );
+
+        let dev_success = event(NotificationEventType::ApplySucceeded, "k8s-dev/team-a");
+        let deliveries: Vec<(&str, &str)> = get_deliveries(&config, &dev_success)
+            .into_iter()
+            .map(|(name, _, template)| (name, template))
+            .collect();
+        assert_eq!(
+            deliveries,
+            vec![
+                ("audit", "{{ deployment_id }} applied"),
+                ("local", "{{ deployment_id }} applied"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_default_templates() {
+        let mut drift = event(NotificationEventType::DriftDetected, "k8s-prod/team-a");
+        assert_eq!(
+            render_notification(drift.event.default_template(), &drift).unwrap(),
+            "Drift detected for s3bucket/my-bucket in k8s-prod/team-a"
+        );
+        drift.details = json!({"drifted_resources": ["aws_s3_bucket.a", "aws_s3_bucket.b"]});
+        assert_eq!(
+            render_notification(drift.event.default_template(), &drift).unwrap(),
+            "Drift detected for s3bucket/my-bucket in k8s-prod/team-a: aws_s3_bucket.a, aws_s3_bucket.b"
+        );
+
+        let mut failure = event(NotificationEventType::ApplyFailed, "k8s-prod/team-a");
+        failure.error_text = "Error: timeout".to_string();
+        assert_eq!(
+            render_notification(failure.event.default_template(), &failure).unwrap(),
+            "Apply of s3bucket/my-bucket in k8s-prod/team-a failed: Error: timeout"
+        );
+
+        let mut deprecated = event(NotificationEventType::DeprecatedModuleUsed, "k8s-dev/a");
+        deprecated.details = json!({"deprecated_message": "use s3bucket 2.x"});
+        assert_eq!(
+            render_notification(deprecated.event.default_template(), &deprecated).unwrap(),
+            "s3bucket/my-bucket in k8s-dev/a uses deprecated s3bucket 1.2.0: use s3bucket 2.x"
+        );
+    }
+
+    #[test]
+    fn test_sink_messages() {
+        let failure = event(NotificationEventType::ApplyFailed, "k8s-prod/team-a");
+        let teams = get_teams_message("Apply failed\ndetails", &failure);
+        assert_eq!(teams["summary"], "Apply failed");
+        assert_eq!(teams["themeColor"], "d73a49");
+
+        let webhook = get_webhook_message("Apply failed", &failure).unwrap();
+        assert_eq!(webhook["event"], "apply_failed");
+        assert_eq!(webhook["deployment_id"], "s3bucket/my-bucket");
+        assert_eq!(webhook["text"], "Apply failed");
+    }
+}
@@ -1,44 +1,13 @@
 use env_common::interface::GenericCloudHandler;
 use env_common::DeploymentStatusHandler;
-use env_defs::{CloudProvider, PolicyResult};
+use env_defs::{
+    ApiInfraPayload, CloudProvider, DeploymentStatus, PolicyResp, PolicyResult, PolicySpec,
+};
+use env_utils::{
+    apply_policy_waivers, evaluate_policy, get_epoch, get_policy_data, matches_pattern,
+};
 use serde_json::{json, Value};
-use std::{env, fs::File, path::Path, process::exit};
-
-use crate::cmd::{run_generic_command, CommandResult};
-
-pub async fn run_opa_command(
-    max_output_lines: usize,
-    policy_name: &str,
-    rego_files: &Vec<String>,
-) -> Result<CommandResult, anyhow::Error> {
-    println!("Running opa eval on policy {}", policy_name);
-
-    let mut exec = tokio::process::Command::new("opa");
-    exec.arg("eval").arg("--format").arg("pretty");
-
-    for rego_file in rego_files {
-        println!("Adding arg to opa command --data {}", rego_file);
-        exec.arg("--data");
-        exec.arg(rego_file);
-    }
-
-    exec.arg("--input")
-        .arg("./tf_plan.json")
-        .arg("--data")
-        .arg("./env_data.json")
-        .arg("--data")
-        .arg("./policy_input.json")
-        .arg("data.infraweave")
-        .current_dir(Path::new("./"))
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped()); // Capture stdout
-
-    println!("Running opa command...");
-    // Print command
-    println!("{:?}", exec);
-
-    run_generic_command(&mut exec, max_output_lines).await
-}
+use std::{collections::BTreeMap, path::Path};
 
 pub async fn download_policy(policy: &env_defs::PolicyResp) {
     println!("Downloading policy for {}...", policy.policy);
@@ -89,23 +58,61 @@ pub fn get_all_rego_filenames_in_cwd() -> Vec<String> {
     rego_files
 }
 
-pub async fn run_opa_policy_checks(
-    handler: &GenericCloudHandler,
-    status_handler: &mut DeploymentStatusHandler<'_>,
-) -> Result<(), anyhow::Error> {
-    // Store specific environment variables in a JSON file to be used by OPA policies
-    let file_path = "./env_data.json";
-    match store_env_as_json(file_path) {
-        Ok(_) => println!("Environment variables stored in {}.", file_path),
-        Err(e) => eprintln!("Failed to write file: {}", e),
-    }
+/// Whether the policy applies to a deployment of `module` with `labels`. The module of a claim is
+/// its lowercased kind, so module patterns are matched in lowercase
+fn policy_applies_to(policy: &PolicySpec, module: &str, labels: &BTreeMap<String, String>) -> bool {
+    (policy.modules.is_empty()
+        || policy
+            .modules
+            .iter()
+            .any(|m| matches_pattern(&m.to_lowercase(), module)))
+        && policy
+            .labels
+            .iter()
+            .all(|(key, value)| labels.get(key).is_some_and(|l| matches_pattern(value, l)))
+}
+
+/// Policies published to `stable` apply to deployments in every environment
+const STABLE_POLICY_ENVIRONMENT: &str = "stable";
 
-    let policy_environment = "stable".to_string();
+/// The `stable` policies and the policies published to the environment of the deployment
+async fn get_policies(
+    handler: &GenericCloudHandler,
+    environment: &str,
+) -> Result<Vec<PolicyResp>, anyhow::Error> {
     println!(
         "Finding all applicable policies for {}...",
-        &policy_environment
+        STABLE_POLICY_ENVIRONMENT
     );
-    let policies = handler.get_all_policies(&policy_environment).await.unwrap();
+    let mut policies = handler.get_all_policies(STABLE_POLICY_ENVIRONMENT).await?;
+    if environment != STABLE_POLICY_ENVIRONMENT {
+        println!("Finding all applicable policies for {}...", environment);
+        policies.extend(handler.get_all_policies(environment).await?);
+    }
+    Ok(policies)
+}
+
+pub async fn run_opa_policy_checks(
+    payload: &ApiInfraPayload,
+    handler: &GenericCloudHandler,
+    status_handler: &mut DeploymentStatusHandler<'_>,
+) -> Result<(), anyhow::Error> {
+    let plan: Value = serde_json::from_str(&std::fs::read_to_string("./tf_plan.json")?)?;
+
+    // Includes the cost estimate and risk assessment written by terraform_show
+    let data = get_policy_data(Path::new("./"))?;
+
+    let mut policies = get_policies(handler, &payload.environment).await?;
+    policies.retain(|policy| {
+        let applies = policy_applies_to(&policy.manifest.spec, &payload.module, &payload.labels);
+        if !applies {
+            println!(
+                "Skipping policy {}, it does not apply to module {} with labels {:?}",
+                policy.policy, payload.module, payload.labels
+            );
+        }
+        applies
+    });
 
     let mut policy_results: Vec<PolicyResult> = vec![];
     let mut failed_policy_evaluation = false;
@@ -114,96 +121,74 @@ pub async fn run_opa_policy_checks(
     for policy in policies {
         download_policy(&policy).await;
 
-        // Store policy input in a JSON file
-        let policy_input_file = "./policy_input.json";
-        let policy_input_file_path = Path::new(policy_input_file);
-        let policy_input_file = File::create(policy_input_file_path).unwrap();
-        serde_json::to_writer(policy_input_file, &policy.data).unwrap();
-
         let rego_files: Vec<String> = get_all_rego_filenames_in_cwd();
 
-        match run_opa_command(500, &policy.policy, &rego_files).await {
-            Ok(command_result) => {
-                println!("OPA policy evaluation for {} finished", &policy.policy);
+        let mut policy_data = data.clone();
+        policy_data.push(policy.data.clone());
+        let result = evaluate_policy(Path::new("./"), &plan, &policy_data);
 
-                let opa_result: Value = match serde_json::from_str(command_result.stdout.as_str()) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        panic!("Could not parse the opa output json from stdout: {:?}\nString was:'{:?}", e, command_result.stdout.as_str());
-                    }
-                };
+        // Delete rego files after each policy check to avoid conflicts
+        for rego_file in &rego_files {
+            std::fs::remove_file(rego_file).unwrap();
+        }
 
-                // == opa_result example: ==
-                //  {
-                //     "helpers": {},
-                //     "terraform_plan": {
-                //       "deny": [
-                //         "Invalid region: 'eu-central-1'. The allowed AWS regions are: [\"us-east-1\", \"eu-west-1\"]"
-                //       ]
-                //     }
-                //  }
-                // =========================
-
-                let mut failed: bool = false;
-                let mut policy_violations: Value = json!({});
-                for (opa_package_name, value) in opa_result.as_object().unwrap() {
-                    if let Some(violations) = value.get("deny")
-                        && !violations.as_array().unwrap().is_empty()
-                    {
-                        failed = true;
-                        failed_policy_evaluation = true;
-                        policy_violations[opa_package_name] = violations.clone();
-
-                        // println!("Policy violations found for policy: {}", policy.policy);
-                        // println!("Violations: {}", violations);
-                        // println!("Current rego files for further information:");
-                        // cat_file("./tf_plan.json"); // BE CARFEFUL WITH THIS LINE, CAN EXPOSE SENSITIVE DATA
-                        // cat_file("./env_data.json");
-                        // cat_file("./policy_input.json");
-                        // for file in &rego_files {
-                        //     cat_file(file);
-                        // }
-                    }
+        match result {
+            Ok(violations) => {
+                println!("OPA policy evaluation for {} finished", &policy.policy);
+
+                // Only violations of deny rules that are not waived for the deployment fail it
+                let waived = apply_policy_waivers(
+                    violations.deny,
+                    &policy.manifest.spec.waivers,
+                    &payload.deployment_id,
+                    get_epoch(),
+                );
+                let failed = !waived.deny.is_empty();
+                if failed {
+                    failed_policy_evaluation = true;
                 }
-                policy_results.push(PolicyResult {
+                let policy_result = PolicyResult {
                     policy: policy.policy.clone(),
                     version: policy.version.clone(),
                     environment: policy.environment.clone(),
                     description: policy.description.clone(),
                     policy_name: policy.policy_name.clone(),
                     failed,
-                    violations: policy_violations,
-                });
+                    violations: json!(waived.deny),
+                    warnings: violations.warn,
+                    info: violations.info,
+                    waived: waived.waived,
+                    waivers: waived.waivers,
+                };
+                for warning in policy_result.get_warnings() {
+                    println!("Warning: policy {}: {}", policy.policy, warning);
+                }
+                for violation in policy_result.get_waived_violations() {
+                    println!("Waived: policy {}: {}", policy.policy, violation);
+                }
+                policy_results.push(policy_result);
             }
             Err(e) => {
-                println!(
-                    "Error running OPA policy evaluation command for {}",
-                    policy.policy
-                ); // TODO: use stderr from command_result
-                let error_text = e.to_string();
-                let status = "failed_policy".to_string();
-                status_handler.set_status(status);
+                println!("Error evaluating OPA policy {}: {}", policy.policy, e);
+                let error_text = format!("Error evaluating policy {}: {}", policy.policy, e);
+                let status = DeploymentStatus::FailedPolicy;
+                status_handler.set_status(status)?;
                 status_handler.set_event_duration();
-                status_handler.set_error_text(error_text);
+                status_handler.set_error_text(error_text.clone());
                 status_handler.send_event(handler).await;
                 status_handler.send_deployment(handler).await?;
                 status_handler.set_error_text("".to_string());
-                exit(0);
+                return Err(anyhow::anyhow!(error_text));
             }
         }
-
-        // Delete rego files after each policy check to avoid conflicts
-        for rego_file in &rego_files {
-            std::fs::remove_file(rego_file).unwrap();
-        }
     }
 
     status_handler.set_policy_results(policy_results);
 
     if failed_policy_evaluation {
         println!("Error: OPA Policy evaluation found policy violations, aborting deployment");
-        let status = "failed_policy".to_string();
-        status_handler.set_status(status);
+        let status = DeploymentStatus::FailedPolicy;
+        status_handler.set_status(status)?;
         status_handler.set_event_duration();
         status_handler.send_event(handler).await;
         status_handler.send_deployment(handler).await?;
@@ -215,21 +200,68 @@ pub async fn run_opa_policy_checks(
     Ok(())
 }
 
-fn store_env_as_json(file_path: &str) -> std::io::Result<()> {
-    // Important for OPA policy checks
-    let aws_default_region = env::var("AWS_DEFAULT_REGION").unwrap_or_else(|_| "".to_string());
-    let aws_region = env::var("AWS_REGION").unwrap_or_else(|_| "".to_string());
-
-    let env_vars = json!({
-        "env": {
-            "AWS_DEFAULT_REGION": aws_default_region,
-            "AWS_REGION": aws_region
+/// Environment variables available to policies as `data.env`
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy_spec(modules: &[&str], labels: &[(&str, &str)]) -> PolicySpec {
+        PolicySpec {
+            policy_name: "Allowed regions".to_string(),
+            version: "0.1.0".to_string(),
+            description: "".to_string(),
+            reference: "".to_string(),
+            data: json!({}),
+            waivers: vec![],
+            modules: modules.iter().map(|m| m.to_string()).collect(),
+            labels: labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
         }
-    });
-
-    let env_file_path = Path::new(file_path);
-    let env_file = File::create(env_file_path).unwrap();
-    serde_json::to_writer(env_file, &env_vars).unwrap();
+    }
 
-    Ok(())
+    #[test]
+    fn test_policy_applies_to() {
+        let labels = BTreeMap::from([
+            ("team".to_string(), "payments".to_string()),
+            ("env".to_string(), "production".to_string()),
+        ]);
+
+        assert!(policy_applies_to(
+            &policy_spec(&[], &[]),
+            "s3bucket",
+            &labels
+        ));
+        assert!(policy_applies_to(
+            &policy_spec(&[], &[]),
+            "s3bucket",
+            &BTreeMap::new()
+        ));
+        assert!(policy_applies_to(
+            &policy_spec(&["S3Bucket", "rds*"], &[]),
+            "s3bucket",
+            &labels
+        ));
+        assert!(!policy_applies_to(
+            &policy_spec(&["rds*"], &[]),
+            "s3bucket",
+            &labels
+        ));
+        assert!(policy_applies_to(
+            &policy_spec(&["s3*"], &[("team", "payments"), ("env", "prod*")]),
+            "s3bucket",
+            &labels
+        ));
+        assert!(!policy_applies_to(
+            &policy_spec(&[], &[("team", "platform")]),
+            "s3bucket",
+            &labels
+        ));
+        assert!(!policy_applies_to(
+            &policy_spec(&[], &[("team", "payments")]),
+            "s3bucket",
+            &BTreeMap::new()
+        ));
+    }
 }
@@ -0,0 +1,254 @@
+use env_common::interface::GenericCloudHandler;
+use env_common::DeploymentStatusHandler;
+use env_defs::{
+    sanitize_resource_changes_from_plan, ApiInfraPayload, DeploymentStatus,
+    ProtectedResourceOverride, ResourceAction, ResourceMode, SanitizedResourceChange,
+    PROTECTED_RESOURCES_OVERRIDE_ANNOTATION,
+};
+use env_utils::matches_pattern;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Read from the `PROTECTED_RESOURCES_CONFIG` environment variable as JSON or YAML
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct ProtectedResourcesConfig {
+    #[serde(default)]
+    pub rules: Vec<ProtectedResourceRule>,
+}
+
+/// Resources of `resource_types` or with an address matching `addresses` in the matching
+/// projects and environments may not be deleted or replaced. Empty project and environment
+/// filters match everything and `*` matches any characters
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ProtectedResourceRule {
+    #[serde(default)]
+    pub projects: Vec<String>,
+    #[serde(default)]
+    pub environments: Vec<String>,
+    #[serde(default)]
+    pub resource_types: Vec<String>,
+    #[serde(default)]
+    pub addresses: Vec<String>,
+}
+
+impl ProtectedResourceRule {
+    fn applies_to(&self, project_id: &str, environment: &str) -> bool {
+        (self.projects.is_empty() || self.projects.iter().any(|p| matches_pattern(p, project_id)))
+            && (self.environments.is_empty()
+                || self
+                    .environments
+                    .iter()
+                    .any(|e| matches_pattern(e, environment)))
+    }
+
+    fn protects(&self, change: &SanitizedResourceChange) -> bool {
+        self.resource_types
+            .iter()
+            .any(|t| matches_pattern(t, &change.resource_type))
+            || self
+                .addresses
+                .iter()
+                .any(|a| matches_pattern(a, &change.address))
+    }
+}
+
+pub fn parse_protected_resources_config(
+    config: &str,
+) -> Result<ProtectedResourcesConfig, anyhow::Error> {
+    let config: ProtectedResourcesConfig = serde_yaml::from_str(config)
+        .map_err(|e| anyhow::anyhow!("Invalid protected resources config: {}", e))?;
+    if let Some(rule) = config
+        .rules
+        .iter()
+        .find(|r| r.resource_types.is_empty() && r.addresses.is_empty())
+    {
+        return Err(anyhow::anyhow!(
+            "Protected resources rule for projects {:?} and environments {:?} has neither resource_types nor addresses",
+            rule.projects,
+            rule.environments
+        ));
+    }
+    Ok(config)
+}
+
+fn load_protected_resources_config() -> Result<Option<ProtectedResourcesConfig>, anyhow::Error> {
+    match std::env::var("PROTECTED_RESOURCES_CONFIG") {
+        Ok(config) if !config.trim().is_empty() => {
+            parse_protected_resources_config(&config).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Addresses of the resources in `changes` that are deleted or replaced although a rule for the
+/// project and environment protects them
+pub fn get_protected_resource_changes(
+    config: &ProtectedResourcesConfig,
+    project_id: &str,
+    environment: &str,
+    changes: &[SanitizedResourceChange],
+) -> Vec<String> {
+    let rules: Vec<&ProtectedResourceRule> = config
+        .rules
+        .iter()
+        .filter(|r| r.applies_to(project_id, environment))
+        .collect();
+    changes
+        .iter()
+        .filter(|c| c.mode == ResourceMode::Managed)
+        .filter(|c| matches!(c.action, ResourceAction::Delete | ResourceAction::Replace))
+        .filter(|c| rules.iter().any(|r| r.protects(c)))
+        .map(|c| c.address.clone())
+        .collect()
+}
+
+/// Reason given in the override annotation of the claim, an empty reason is no override
+fn get_override_reason(annotations: &Value) -> Option<String> {
+    annotations
+        .get(PROTECTED_RESOURCES_OVERRIDE_ANNOTATION)
+        .and_then(|r| r.as_str())
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty())
+}
+
+/// Fails the job if the plan deletes or replaces protected resources, unless the claim has the
+/// override annotation. The overridden resources are returned to be recorded on the change record
+pub async fn check_protected_resources(
+    payload: &ApiInfraPayload,
+    handler: &GenericCloudHandler,
+    status_handler: &mut DeploymentStatusHandler<'_>,
+) -> Result<Option<ProtectedResourceOverride>, anyhow::Error> {
+    // An invalid config fails the job instead of letting protected resources through
+    let error_text = match load_protected_resources_config() {
+        Ok(None) => return Ok(None),
+        Ok(Some(config)) => {
+            let plan = tokio::fs::read_to_string("./tf_plan.json").await?;
+            let plan: Value = serde_json::from_str(&plan)?;
+            let resources = get_protected_resource_changes(
+                &config,
+                &payload.project_id,
+                &payload.environment,
+                &sanitize_resource_changes_from_plan(&plan),
+            );
+            if resources.is_empty() {
+                return Ok(None);
+            }
+            match get_override_reason(&payload.annotations) {
+                Some(reason) => {
+                    println!(
+                        "Protected resources {} are deleted or replaced, overridden by the claim: {}",
+                        resources.join(", "),
+                        reason
+                    );
+                    return Ok(Some(ProtectedResourceOverride { reason, resources }));
+                }
+                None => format!(
+                    "The plan deletes or replaces protected resources: {}. Add the annotation {} with the reason to the claim to allow it",
+                    resources.join(", "),
+                    PROTECTED_RESOURCES_OVERRIDE_ANNOTATION
+                ),
+            }
+        }
+        Err(e) => e.to_string(),
+    };
+
+    println!("Error: {}", error_text);
+    status_handler.set_status(DeploymentStatus::FailedPolicy)?;
+    status_handler.set_event_duration();
+    status_handler.set_error_text(error_text.clone());
+    status_handler.send_event(handler).await;
+    status_handler.send_deployment(handler).await?;
+    status_handler.set_error_text("".to_string());
+    Err(anyhow::anyhow!(error_text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use env_defs::sanitize_resource_changes;
+    use serde_json::json;
+
+    fn resource_change(address: &str, actions: &[&str]) -> Value {
+        let (resource_type, name) = address.rsplit_once('.').unwrap();
+        let resource_type = resource_type.rsplit('.').next().unwrap();
+        json!({
+            "address": address,
+            "mode": "managed",
+            "type": resource_type,
+            "name": name,
+            "change": {"actions": actions, "before": {}, "after": {}}
+        })
+    }
+
+    #[test]
+    fn test_parse_protected_resources_config() {
+        let config = parse_protected_resources_config(
+            r#"
+rules:
+  - environments: ["prod*"]
+    resource_types: ["aws_db_instance", "aws_rds_*"]
+    addresses: ["module.*.aws_s3_bucket.data"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.rules.len(), 1);
+        assert_eq!(config.rules[0].environments, vec!["prod*"]);
+        assert!(config.rules[0].projects.is_empty());
+
+        assert!(parse_protected_resources_config(r#"{"rules": [{"projects": ["*"]}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_get_protected_resource_changes() {
+        let config = parse_protected_resources_config(
+            r#"
+rules:
+  - environments: ["prod*"]
+    resource_types: ["aws_db_instance", "aws_rds_*"]
+  - projects: ["123456789012"]
+    addresses: ["module.*.aws_s3_bucket.data"]
+"#,
+        )
+        .unwrap();
+        let changes = sanitize_resource_changes(&json!([
+            resource_change("aws_db_instance.main", &["delete", "create"]),
+            resource_change("aws_rds_cluster.main", &["update"]),
+            resource_change("aws_rds_cluster_instance.one", &["delete"]),
+            resource_change("module.storage.aws_s3_bucket.data", &["delete"]),
+            resource_change("module.storage.aws_s3_bucket.logs", &["delete"]),
+            resource_change("aws_instance.web", &["delete", "create"]),
+        ]));
+
+        assert_eq!(
+            get_protected_resource_changes(&config, "123456789012", "prod/eu", &changes),
+            vec![
+                "aws_db_instance.main",
+                "aws_rds_cluster_instance.one",
+                "module.storage.aws_s3_bucket.data"
+            ]
+        );
+        assert_eq!(
+            get_protected_resource_changes(&config, "123456789012", "dev", &changes),
+            vec!["module.storage.aws_s3_bucket.data"]
+        );
+        assert!(
+            get_protected_resource_changes(&config, "210987654321", "dev", &changes).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_get_override_reason() {
+        assert_eq!(
+            get_override_reason(&json!({
+                PROTECTED_RESOURCES_OVERRIDE_ANNOTATION: "Migrating to Aurora, CHG-1234"
+            })),
+            Some("Migrating to Aurora, CHG-1234".to_string())
+        );
+        assert_eq!(
+            get_override_reason(&json!({ PROTECTED_RESOURCES_OVERRIDE_ANNOTATION: "  " })),
+            None
+        );
+        assert_eq!(get_override_reason(&json!({})), None);
+        assert_eq!(get_override_reason(&Value::Null), None);
+    }
+}
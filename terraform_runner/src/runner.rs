@@ -1,35 +1,39 @@
 use anyhow::{anyhow, Result};
 use env_common::interface::GenericCloudHandler;
-use env_common::logic::{driftcheck_infra, publish_notification};
+use env_common::logic::{outputs_changed, publish_notification, trigger_dependent_deployments};
 use env_common::DeploymentStatusHandler;
 use env_defs::{
-    ApiInfraPayload, ApiInfraPayloadWithVariables, CloudProvider, Dependency, Dependent,
-    DeploymentResp, ExtraData, JobDetails, NotificationData,
+    ApiInfraPayload, ApiInfraPayloadWithVariables, CascadeTrigger, CloudProvider, Dependency,
+    DeploymentResp, DeploymentStatus, ExtraData, JobDetails, NotificationData, WebhookEventType,
 };
-use env_utils::{store_backend_file, store_tf_vars_json};
-use futures::future::join_all;
+use env_utils::store_backend_file;
 use log::{error, info};
 use serde_json::{json, Value};
 use std::env;
 use std::process::exit;
 use std::vec;
 
-use crate::module::{download_module, get_module};
+use crate::module::{download_module, get_module, store_imports_file};
+use crate::variables::store_variables;
 use crate::{
-    get_initial_deployment, record_apply_destroy_changes, run_opa_policy_checks,
-    set_up_provider_mirror, terraform_apply_destroy, terraform_init, terraform_output,
-    terraform_plan, terraform_show, terraform_state_list, terraform_validate,
+    check_protected_resources, get_initial_deployment, get_webhook_payload, is_cancel_requested,
+    listen_for_cancel, record_apply_destroy_changes, release_state_lock, run_opa_policy_checks,
+    send_notifications, send_webhooks, set_up_provider_mirror, terraform_apply_destroy,
+    terraform_init, terraform_output, terraform_plan, terraform_saved_plan, terraform_show,
+    terraform_state_list, terraform_validate, was_terraform_killed, NotificationEvent,
+    NotificationEventType,
 };
 
 pub async fn run_terraform_runner(
     handler: &GenericCloudHandler,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    listen_for_cancel();
+
     // Due to length constraints in environment variables, deployment claim variables need to be fetched from the database
-    let (payload_with_variables, job_id_for_variables) = get_payload_with_variables(handler).await;
+    let (payload_with_variables, job_id_for_variables) =
+        get_payload_with_variables(handler).await?;
     let payload = &payload_with_variables.payload;
 
-    println!("Storing terraform variables in tf_vars.json...");
-    store_tf_vars_json(&payload_with_variables.variables, ".");
     store_backend_file(
         GenericCloudHandler::default().await.get_backend_provider(),
         ".",
@@ -72,12 +76,52 @@ pub async fn run_terraform_runner(
                 info!("Terraform flow completed successfully");
                 ("success", "".to_string())
             }
+            Err(_) if is_cancel_requested() => {
+                info!("Terraform flow was cancelled");
+                let error_text = record_cancelled(handler, &mut status_handler).await;
+                ("cancelled", error_text)
+            }
             Err(e) => {
                 error!("Terraform flow failed: {:?}", e);
                 ("failure", e.to_string())
             }
         };
 
+    let webhook_event = match result {
+        "success" => Some(WebhookEventType::Success),
+        "failure" => Some(WebhookEventType::Failure),
+        _ => None,
+    };
+    if let Some(event) = webhook_event {
+        let mut webhook_payload = get_webhook_payload(
+            payload,
+            &job_id,
+            event,
+            format!(
+                "{} of {} in {} {}",
+                command,
+                payload.deployment_id,
+                payload.environment,
+                if event == WebhookEventType::Success {
+                    "succeeded"
+                } else {
+                    "failed"
+                }
+            ),
+        );
+        if !error_text.is_empty() {
+            webhook_payload.error_text = Some(error_text.clone());
+        }
+        send_webhooks(handler, &payload.drift_detection.webhooks, &webhook_payload).await;
+    }
+    if let Some(event) = NotificationEventType::for_job(command, result == "success")
+        && result != "cancelled"
+    {
+        let mut notification = NotificationEvent::new(event, payload, &job_id);
+        notification.error_text = error_text.clone();
+        send_notifications(handler, &notification).await;
+    }
+
     let mut extra_data = payload.extra_data.clone();
     match extra_data {
         ExtraData::GitHub(ref mut github_data) => {
@@ -118,6 +162,40 @@ pub async fn run_terraform_runner(
     Ok(())
 }
 
+async fn record_cancelled(
+    handler: &GenericCloudHandler,
+    status_handler: &mut DeploymentStatusHandler<'_>,
+) -> String {
+    let mut error_text = "The job was cancelled".to_string();
+    if was_terraform_killed() {
+        // Terraform releases the state lock when it is interrupted, unless it had to be killed
+        match release_state_lock().await {
+            Ok(_) => {
+                error_text.push_str(", terraform had to be killed but the state lock was released");
+            }
+            Err(e) => {
+                println!("Error releasing state lock: {:?}", e);
+                error_text.push_str(&format!(
+                    ", terraform had to be killed and the state lock could not be released: {}",
+                    e
+                ));
+            }
+        }
+    }
+
+    if let Err(e) = status_handler.set_status(DeploymentStatus::Cancelled) {
+        println!("Error recording cancelled job: {:?}", e);
+        return error_text;
+    }
+    status_handler.set_error_text(error_text.clone());
+    status_handler.set_event_duration();
+    status_handler.send_event(handler).await;
+    if let Err(e) = status_handler.send_deployment(handler).await {
+        println!("Error recording cancelled job: {:?}", e);
+    }
+    error_text
+}
+
 async fn terraform_flow<'a>(
     handler: &GenericCloudHandler,
     status_handler: &mut DeploymentStatusHandler<'a>,
@@ -136,6 +214,15 @@ async fn terraform_flow<'a>(
     }
 
     let module = get_module(handler, payload, status_handler).await?;
+    if module.deprecated {
+        let mut notification =
+            NotificationEvent::new(NotificationEventType::DeprecatedModuleUsed, payload, job_id);
+        notification.details = json!({
+            "deprecated_message": module.deprecated_message.clone().unwrap_or_default(),
+        });
+        send_notifications(handler, &notification).await;
+    }
+    let mut has_changed_outputs = false;
 
     match set_up_provider_mirror(handler, &module.tf_lock_providers, "linux_arm64").await {
         Ok(_) => {
@@ -151,11 +238,21 @@ async fn terraform_flow<'a>(
 
     download_module(handler, &module, status_handler).await?;
 
+    store_imports_file(payload, &module, handler, status_handler).await?;
+
+    store_variables(handler, status_handler).await?;
+
     terraform_init(payload, handler, status_handler).await?;
 
     terraform_validate(payload, handler, status_handler).await?;
 
-    let plan_output = terraform_plan(payload, handler, status_handler).await?;
+    // A job applying a saved plan applies exactly what was planned instead of planning again
+    let plan_output = match &payload.from_plan {
+        Some(plan_job_id) => {
+            terraform_saved_plan(payload, plan_job_id, &module, handler, status_handler).await?
+        }
+        None => terraform_plan(payload, handler, status_handler).await?,
+    };
 
     terraform_show(
         payload,
@@ -167,9 +264,12 @@ async fn terraform_flow<'a>(
     )
     .await?;
 
-    run_opa_policy_checks(handler, status_handler).await?;
+    run_opa_policy_checks(payload, handler, status_handler).await?;
 
     if command == "apply" || command == "destroy" {
+        let protected_resource_override =
+            check_protected_resources(payload, handler, status_handler).await?;
+
         let apply_result = terraform_apply_destroy(payload, handler, status_handler).await;
 
         // Always capture the current state resources, even if apply/destroy failed partway through
@@ -194,6 +294,7 @@ async fn terraform_flow<'a>(
             job_id,
             &module,
             apply_output_str,
+            protected_resource_override,
             handler,
             status_handler,
         )
@@ -232,66 +333,70 @@ async fn terraform_flow<'a>(
 
         // Only get outputs for apply command (destroy has no outputs since resources are gone)
         if command == "apply" {
+            let previous_output = status_handler.get_output();
             terraform_output(payload, handler, status_handler).await?;
+            has_changed_outputs = outputs_changed(&previous_output, &status_handler.get_output());
         }
     }
 
     // Set deployment status to successful after all operations complete
-    status_handler.set_status("successful".to_string());
+    status_handler.set_status(DeploymentStatus::Successful)?;
     status_handler.set_event_duration();
     status_handler.set_last_event_epoch();
     status_handler.send_event(handler).await;
     status_handler.send_deployment(handler).await?;
 
-    // if !dependents.is_empty() {
-    //     _trigger_dependent_deployments(&dependents).await; // TODO: WIP: needs to launch with replaced variables
-    // }
+    if has_changed_outputs {
+        trigger_dependents(payload, handler, status_handler, job_id).await;
+    }
 
     Ok(())
 }
 
-async fn _trigger_dependent_deployments(dependent_deployments: &Vec<Dependent>) {
-    // Retrigger each deployment asynchronously to run them in parallel
-    let dependent_deployment_runs = dependent_deployments.clone().into_iter().map(|dependent| {
-        let deployment_id = dependent.dependent_id.clone();
-        let environment = dependent.environment.clone();
-        async move {
-            println!(
-                "Deploymentid: {}, environment: {}",
-                deployment_id, environment
-            );
-            let remediate = true; // Always apply remediation for dependent deployments (=> terraform apply)
-            let handler = GenericCloudHandler::default().await;
-            match driftcheck_infra(
-                &handler,
-                &deployment_id,
-                &environment,
-                remediate,
-                ExtraData::None,
-            )
-            .await
-            {
-                Ok(_) => {
-                    info!("Successfully requested drift check");
-                }
-                Err(e) => {
-                    error!("Failed to request drift check: {}", e);
-                }
-            }
-        }
-    });
-
-    join_all(dependent_deployment_runs).await;
+async fn trigger_dependents(
+    payload: &ApiInfraPayload,
+    handler: &GenericCloudHandler,
+    status_handler: &mut DeploymentStatusHandler<'_>,
+    job_id: &str,
+) {
+    // The job that started the cascade has already requested applies for all deployments
+    // depending on it (directly or indirectly), so a job that is part of it must not do it again
+    if let Some(triggered_by) = &payload.triggered_by {
+        println!(
+            "Outputs changed, dependents were already triggered by job {} for {}",
+            triggered_by.job_id, triggered_by.deployment_id
+        );
+        return;
+    }
 
-    info!(
-        "Successfully retriggered dependent deployments {:?}",
-        dependent_deployments
-    );
+    println!("Outputs changed, triggering apply of dependent deployments...");
+    let triggered_by = CascadeTrigger {
+        deployment_id: payload.deployment_id.clone(),
+        environment: payload.environment.clone(),
+        job_id: job_id.to_string(),
+    };
+    match trigger_dependent_deployments(handler, &triggered_by).await {
+        Ok(triggered) if triggered.is_empty() => {
+            println!("No dependent deployments to trigger");
+        }
+        Ok(triggered) => {
+            println!("Triggered dependent deployments: {:?}", triggered);
+            status_handler.set_command("trigger_dependents");
+            status_handler.set_metadata_field("triggered_dependents", json!(triggered));
+            status_handler.set_event_duration();
+            status_handler.send_event(handler).await;
+        }
+        Err(e) => {
+            println!("Warning: Failed to trigger dependent deployments: {:?}", e);
+        }
+    }
 }
 
+const MAX_DEPLOYMENT_READ_ATTEMPTS: u32 = 10;
+
 async fn get_payload_with_variables(
     handler: &GenericCloudHandler,
-) -> (ApiInfraPayloadWithVariables, String) {
+) -> Result<(ApiInfraPayloadWithVariables, String), anyhow::Error> {
     let payload_env = env::var("PAYLOAD").unwrap();
     let payload: ApiInfraPayload = match serde_json::from_str(&payload_env) {
         Ok(json) => json,
@@ -304,24 +409,61 @@ async fn get_payload_with_variables(
         }
     };
 
-    let result = match &payload.command.as_str() {
-        &"plan" => {
-            let job_id = handler.get_current_job_id().await.unwrap();
-            handler
-                .get_plan_deployment(&payload.deployment_id, &payload.environment, &job_id)
-                .await
-        }
-        _ => {
-            // For other commands, fetch the deployment as usual (apply, destroy)
-            handler
-                .get_deployment(&payload.deployment_id, &payload.environment, false)
-                .await
+    let current_job_id = handler.get_current_job_id().await?;
+    // The deployment is stored right after the job is submitted. A local job starts as a
+    // process right away and may have to wait for it, cloud jobs take longer to start
+    let max_attempts = match handler.get_cloud_provider() {
+        "local" => MAX_DEPLOYMENT_READ_ATTEMPTS,
+        _ => 1,
+    };
+    let mut attempts = 0;
+    let result = loop {
+        let result = match &payload.command.as_str() {
+            &"plan" => {
+                handler
+                    .get_plan_deployment(
+                        &payload.deployment_id,
+                        &payload.environment,
+                        &current_job_id,
+                    )
+                    .await
+            }
+            _ => {
+                // For other commands, fetch the deployment as usual (apply, destroy)
+                handler
+                    .get_deployment(&payload.deployment_id, &payload.environment, false)
+                    .await
+            }
+        };
+        attempts += 1;
+        let is_stored =
+            matches!(&result, Ok(Some(deployment)) if deployment.job_id == current_job_id);
+        if is_stored || attempts >= max_attempts {
+            break result;
         }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     };
 
     let (variables, job_id) = match result {
         Ok(deployment) => match deployment {
-            Some(deployment) => (deployment.variables, deployment.job_id),
+            Some(deployment) => {
+                // A job that has already finished must not be started again (e.g. if the job is retried)
+                if deployment.job_id == current_job_id
+                    && !deployment
+                        .status
+                        .can_transition_to(&DeploymentStatus::Initiated)
+                {
+                    eprintln!(
+                        "Deployment {} in {} has status {} for job {}, refusing to start it again",
+                        payload.deployment_id,
+                        payload.environment,
+                        deployment.status,
+                        current_job_id
+                    );
+                    std::process::exit(1);
+                }
+                (deployment.variables, deployment.job_id)
+            }
             None => {
                 eprintln!(
                     "Deployment not found: {} in {}",
@@ -336,7 +478,7 @@ async fn get_payload_with_variables(
         }
     };
 
-    (ApiInfraPayloadWithVariables { payload, variables }, job_id)
+    Ok((ApiInfraPayloadWithVariables { payload, variables }, job_id))
 }
 
 async fn ensure_valid_job_id(
@@ -350,9 +492,9 @@ async fn ensure_valid_job_id(
     if job_id != job_id_for_variables {
         let error_text = format!("Job ID does not match the one in the database, which means that the variables cannot be trusted: {} != {}", job_id, job_id_for_variables);
         println!("{}", &error_text);
-        let status = "failed".to_string();
+        let status = DeploymentStatus::Failed;
         status_handler.set_error_text(error_text);
-        status_handler.set_status(status);
+        let _ = status_handler.set_status(status);
         status_handler.set_event_duration();
         status_handler.send_event(handler).await;
         let _ = status_handler.send_deployment(handler).await;
@@ -370,16 +512,49 @@ async fn ensure_valid_job_id(
 //     println!("{}", String::from_utf8_lossy(&output.stdout));
 // }
 
-async fn check_dependency_status(dependency: &Dependency) -> Result<(), anyhow::Error> {
+const DEPENDENCY_POLL_INTERVAL_SECONDS: u64 = 10;
+const MAX_DEPENDENCY_WAIT_SECONDS: u64 = 60 * 60;
+
+async fn check_dependency_status(
+    dependency: &Dependency,
+    wait_while_in_progress: bool,
+) -> Result<(), anyhow::Error> {
     println!("Checking dependency status...");
     let handler = GenericCloudHandler::default().await;
-    match handler
-        .get_deployment(&dependency.deployment_id, &dependency.environment, false)
-        .await
-    {
+    let mut waited_seconds = 0;
+    loop {
+        let deployment = handler
+            .get_deployment(&dependency.deployment_id, &dependency.environment, false)
+            .await;
+        // Dependents re-applied in a cascade are requested at the same time, so wait for any
+        // dependency that is being re-applied before it in the same cascade to finish
+        if wait_while_in_progress
+            && waited_seconds < MAX_DEPENDENCY_WAIT_SECONDS
+            && let Ok(Some(deployment)) = &deployment
+            && deployment.status.is_in_progress()
+        {
+            println!(
+                "Dependency {} is {}, waiting...",
+                dependency.deployment_id, deployment.status
+            );
+            tokio::time::sleep(std::time::Duration::from_secs(
+                DEPENDENCY_POLL_INTERVAL_SECONDS,
+            ))
+            .await;
+            waited_seconds += DEPENDENCY_POLL_INTERVAL_SECONDS;
+            continue;
+        }
+        return check_deployment_successful(deployment);
+    }
+}
+
+fn check_deployment_successful(
+    deployment: Result<Option<DeploymentResp>, anyhow::Error>,
+) -> Result<(), anyhow::Error> {
+    match deployment {
         Ok(deployment) => match deployment {
             Some(deployment) => {
-                if deployment.status == "successful" {
+                if deployment.status == DeploymentStatus::Successful {
                     Ok(())
                 } else {
                     Err(anyhow!("Dependency not finished"))
@@ -405,11 +580,11 @@ async fn get_current_job_id(
         }
         Err(e) => {
             println!("Error getting current job id: {:?}", e);
-            let status = "failed".to_string();
+            let status = DeploymentStatus::Failed;
             status_handler.set_error_text(
                 "The job failed to fetch the job id, please retry again.".to_string(),
             );
-            status_handler.set_status(status);
+            let _ = status_handler.set_status(status);
             status_handler.set_event_duration();
             status_handler.send_event(handler).await;
             let _ = status_handler.send_deployment(handler).await;
@@ -429,11 +604,11 @@ fn initiate_deployment_status_handler<'a>(
     let project_id = &payload.project_id;
     let region = &payload.region;
     let error_text = "".to_string();
-    let status = "initiated".to_string(); // received, initiated, completed, failed
+    let status = DeploymentStatus::Initiated;
     let job_id = "unknown_jobid".to_string();
     let initiated_by = &payload.initiated_by;
 
-    DeploymentStatusHandler::new(
+    let mut status_handler = DeploymentStatusHandler::new(
         command,
         &payload.module,
         &payload.module_version,
@@ -465,7 +640,14 @@ fn initiate_deployment_status_handler<'a>(
         payload.cpu.clone(),
         payload.memory.clone(),
         payload.reference.clone(),
-    )
+    );
+    status_handler.set_expires_at_epoch(payload.expires_at_epoch);
+    status_handler.set_labels(payload.labels.clone());
+    // Link all events of a job re-applying a dependent to the job that triggered it
+    if let Some(triggered_by) = &payload.triggered_by {
+        status_handler.set_triggered_by(triggered_by);
+    }
+    status_handler
 }
 
 async fn check_dependencies(
@@ -475,7 +657,7 @@ async fn check_dependencies(
 ) -> Result<(), anyhow::Error> {
     let mut dependencies_not_finished: Vec<env_defs::Dependency> = Vec::new();
     for dep in &payload.dependencies {
-        match check_dependency_status(dep).await {
+        match check_dependency_status(dep, payload.triggered_by.is_some()).await {
             Ok(_) => {
                 println!("Dependency finished");
             }
@@ -487,9 +669,9 @@ async fn check_dependencies(
     }
 
     if !dependencies_not_finished.is_empty() {
-        let status = "waiting-on-dependency".to_string();
+        let status = DeploymentStatus::WaitingOnDependency;
         // status_handler.set_error_text(error_text);
-        status_handler.set_status(status);
+        status_handler.set_status(status)?;
         status_handler.set_event_duration();
         status_handler.send_event(handler).await;
         status_handler.send_deployment(handler).await?;
@@ -514,10 +696,10 @@ async fn check_dependants(
         Ok(deployment_and_dependants) => deployment_and_dependants,
         Err(e) => {
             println!("Error getting deployment and dependants: {}", e);
-            let status = "error".to_string();
+            let status = DeploymentStatus::Error;
             status_handler
                 .set_error_text(format!("Error getting deployment and dependants: {}", e));
-            status_handler.set_status(status);
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.send_event(handler).await;
             status_handler.send_deployment(handler).await?;
@@ -526,9 +708,9 @@ async fn check_dependants(
     };
 
     if !dependants.is_empty() {
-        let status = "has-dependants".to_string();
+        let status = DeploymentStatus::HasDependants;
         status_handler.set_error_text("This deployment has other deployments depending on it, and hence cannot be removed until they are removed".to_string());
-        status_handler.set_status(status);
+        status_handler.set_status(status)?;
         status_handler.set_event_duration();
         status_handler.send_event(handler).await;
         status_handler.send_deployment(handler).await?;
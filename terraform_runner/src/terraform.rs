@@ -1,11 +1,15 @@
 use env_common::interface::GenericCloudHandler;
-use env_common::logic::insert_infra_change_record;
+use env_common::logic::{insert_infra_change_record, upload_change_record_file};
 use env_common::DeploymentStatusHandler;
 use env_defs::{
-    sanitize_resource_changes_from_plan, ApiInfraPayload, CloudProvider, InfraChangeRecord,
-    TfLockProvider,
+    sanitize_resource_changes_from_plan, ApiInfraPayload, CloudProvider, CostEstimate,
+    DeploymentStatus, InfraChangeRecord, ProtectedResourceOverride, TfLockProvider,
+    WebhookEventType,
+};
+use env_utils::{
+    assess_plan_risk, estimate_monthly_cost, get_epoch, get_extra_environment_variables,
+    get_price_catalog, get_provider_url_key, get_timestamp,
 };
-use env_utils::{get_epoch, get_extra_environment_variables, get_provider_url_key, get_timestamp};
 use futures::stream::{self, StreamExt};
 use std::{
     env,
@@ -13,12 +17,17 @@ use std::{
 };
 use tokio::fs;
 
-use serde_json::Value;
+use serde::Deserialize;
+use serde_json::{json, Value};
 
 use anyhow::{anyhow, Context, Result};
 use env_aws::assume_role;
 
-use crate::{post_webhook, run_generic_command, CommandResult};
+use crate::variables::{has_resolved_secrets, redact_secrets, redact_secrets_in_json};
+use crate::{
+    get_webhook_payload, run_generic_command, run_uninterruptible_command, send_notifications,
+    send_webhooks, CommandResult, NotificationEvent, NotificationEventType,
+};
 
 #[allow(clippy::too_many_arguments)]
 pub async fn run_terraform_command(
@@ -155,8 +164,8 @@ pub async fn terraform_init(
         }
         Err(e) => {
             println!("Error running \"terraform {}\" command: {:?}", cmd, e);
-            let status = "failed_init".to_string();
-            status_handler.set_status(status);
+            let status = DeploymentStatus::FailedInit;
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.send_event(handler).await;
             status_handler.send_deployment(handler).await?;
@@ -199,8 +208,8 @@ pub async fn terraform_validate(
         Err(e) => {
             println!("Error running \"terraform {}\" command: {:?}", cmd, e);
             let error_text: String = e.to_string();
-            let status = "failed_validate".to_string();
-            status_handler.set_status(status);
+            let status = DeploymentStatus::FailedValidate;
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.set_error_text(error_text);
             status_handler.send_event(handler).await;
@@ -249,8 +258,8 @@ pub async fn terraform_plan(
         Err(e) => {
             println!("Error running \"terraform plan\" command: {:?}", e);
             let error_text = e.to_string();
-            let status = "failed_plan".to_string();
-            status_handler.set_status(status);
+            let status = DeploymentStatus::FailedPlan;
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.set_error_text(error_text);
             status_handler.send_event(handler).await;
@@ -261,6 +270,149 @@ pub async fn terraform_plan(
     }
 }
 
+/// Used instead of `terraform_plan` when the job applies the plan file of a previous plan job.
+/// Refuses the job if the state or the module version changed since the plan.
+pub async fn terraform_saved_plan(
+    payload: &ApiInfraPayload,
+    plan_job_id: &str,
+    module: &env_defs::ModuleResp,
+    handler: &GenericCloudHandler,
+    status_handler: &mut DeploymentStatusHandler<'_>,
+) -> Result<String, anyhow::Error> {
+    status_handler.set_metadata_field("from_plan", Value::String(plan_job_id.to_string()));
+    match download_saved_plan(payload, plan_job_id, module, handler).await {
+        Ok(plan_output) => {
+            println!("Using plan file from plan job {}", plan_job_id);
+            Ok(plan_output)
+        }
+        Err(e) => {
+            println!("Cannot apply plan file of job {}: {:?}", plan_job_id, e);
+            let error_text = format!("Cannot apply plan {}: {}", plan_job_id, e);
+            let status = DeploymentStatus::FailedPlan;
+            status_handler.set_status(status)?;
+            status_handler.set_event_duration();
+            status_handler.set_error_text(error_text.clone());
+            status_handler.send_event(handler).await;
+            status_handler.send_deployment(handler).await?;
+            status_handler.set_error_text("".to_string());
+            Err(anyhow!(error_text))
+        }
+    }
+}
+
+async fn download_saved_plan(
+    payload: &ApiInfraPayload,
+    plan_job_id: &str,
+    module: &env_defs::ModuleResp,
+    handler: &GenericCloudHandler,
+) -> Result<String, anyhow::Error> {
+    let change_record = handler
+        .get_change_record(
+            &payload.environment,
+            &payload.deployment_id,
+            plan_job_id,
+            "PLAN",
+        )
+        .await
+        .context("Failed to get the change record of the plan")?;
+    let state_version = terraform_state_version().await?;
+    verify_saved_plan(&change_record, &module.version, &state_version)?;
+
+    let plan_file = handler.get_plan_file(&change_record).await?;
+    fs::write("./planfile", plan_file)
+        .await
+        .context("Failed to write plan file")?;
+    Ok(change_record.plan_std_output)
+}
+
+/// A saved plan may only be applied to the state and module version it was planned for
+fn verify_saved_plan(
+    change_record: &InfraChangeRecord,
+    module_version: &str,
+    state_version: &Option<TerraformStateVersion>,
+) -> Result<(), anyhow::Error> {
+    if change_record.module_version != module_version {
+        return Err(anyhow!(
+            "the module version changed since the plan ({} -> {})",
+            change_record.module_version,
+            module_version
+        ));
+    }
+    let planned_state = change_record.state_serial.map(|serial| {
+        (
+            serial,
+            change_record.state_lineage.clone().unwrap_or_default(),
+        )
+    });
+    let current_state = state_version
+        .as_ref()
+        .map(|s| (s.serial, s.lineage.clone()));
+    if planned_state != current_state {
+        let describe = |state: &Option<(u64, String)>| match state {
+            Some((serial, lineage)) => format!("serial {} of {}", serial, lineage),
+            None => "no state".to_string(),
+        };
+        return Err(anyhow!(
+            "the state changed since the plan ({} -> {})",
+            describe(&planned_state),
+            describe(&current_state)
+        ));
+    }
+    Ok(())
+}
+
+/// Stores the plan file so it can be applied later, along with the version of the state it was
+/// planned from. Nothing is stored if the state version cannot be read, since the plan file could
+/// then not be verified before it is applied. Nothing is stored either when secrets were resolved,
+/// since the plan file holds every variable value in clear text.
+async fn store_plan_file(
+    handler: &GenericCloudHandler,
+    environment: &str,
+    deployment_id: &str,
+    command: &str,
+    job_id: &str,
+) -> (Option<String>, Option<TerraformStateVersion>) {
+    if has_resolved_secrets() {
+        println!("Not storing plan file since it contains the values of resolved secrets");
+        return (None, None);
+    }
+    let state_version = match terraform_state_version().await {
+        Ok(state_version) => state_version,
+        Err(e) => {
+            println!(
+                "Warning: Failed to read state version, not storing plan file: {:?}",
+                e
+            );
+            return (None, None);
+        }
+    };
+    let plan_file = match fs::read("./planfile").await {
+        Ok(plan_file) => plan_file,
+        Err(e) => {
+            println!("Warning: Failed to read plan file: {:?}", e);
+            return (None, None);
+        }
+    };
+    let plan_file_key = format!(
+        "{}{}/{}/{}_{}_planfile",
+        handler.get_storage_basepath(),
+        environment,
+        deployment_id,
+        command,
+        job_id
+    );
+    match upload_change_record_file(handler, &plan_file_key, &plan_file).await {
+        Ok(_) => {
+            println!("Plan file stored");
+            (Some(plan_file_key), state_version)
+        }
+        Err(e) => {
+            println!("Warning: Failed to store plan file: {:?}", e);
+            (None, None)
+        }
+    }
+}
+
 pub async fn terraform_show(
     payload: &ApiInfraPayload,
     job_id: &str,
@@ -301,11 +453,28 @@ pub async fn terraform_show(
 
             let tf_plan = "./tf_plan.json";
             let tf_plan_file_path = Path::new(tf_plan);
-            // Write the stdout content to the file without parsing to be used for OPA policy checks
-            std::fs::write(tf_plan_file_path, &command_result.stdout)
-                .expect("Unable to write to file");
-
-            let content: Value = serde_json::from_str(command_result.stdout.as_str()).unwrap();
+            // Secrets are redacted before the plan is stored or used for OPA policy checks
+            let plan_json = redact_secrets_in_json(&command_result.stdout);
+            std::fs::write(tf_plan_file_path, &plan_json).expect("Unable to write to file");
+
+            let content: Value = serde_json::from_str(plan_json.as_str()).unwrap();
+
+            let cost_estimate = get_cost_estimate(&content);
+            // Exposed to OPA policies as `data.cost_estimate`, for example to enforce a budget
+            std::fs::write(
+                "./cost_estimate.json",
+                json!({ "cost_estimate": cost_estimate }).to_string(),
+            )
+            .expect("Unable to write to file");
+
+            let resource_changes = sanitize_resource_changes_from_plan(&content);
+            let risk_assessment = assess_plan_risk(&resource_changes);
+            // Exposed to OPA policies as `data.risk_assessment`, for example to require approval
+            std::fs::write(
+                "./risk_assessment.json",
+                json!({ "risk_assessment": risk_assessment }).to_string(),
+            )
+            .expect("Unable to write to file");
 
             if command == "plan" && refresh_only {
                 let drift_has_occurred = !content
@@ -317,35 +486,35 @@ pub async fn terraform_show(
                 status_handler.set_drift_has_occurred(drift_has_occurred);
 
                 if drift_has_occurred {
-                    for webhook in &payload.drift_detection.webhooks {
-                        match &webhook.url {
-                            Some(url) => {
-                                match post_webhook(
-                                    url,
-                                    &format!(
-                                        "Drift has occurred for {} in {}",
-                                        deployment_id, environment
-                                    ),
-                                )
-                                .await
-                                {
-                                    Ok(_) => {
-                                        println!("Webhook {:?} sent successfully", webhook);
-                                    }
-                                    Err(e) => {
-                                        println!(
-                                            "Error sending webhook: {:?} with url: {:?}",
-                                            e, webhook
-                                        );
-                                        // Don't fail the deployment if the webhook fails
-                                    }
-                                }
-                            }
-                            None => {
-                                println!("Webhook URL not provided");
-                            }
-                        }
-                    }
+                    let mut webhook_payload = get_webhook_payload(
+                        payload,
+                        job_id,
+                        WebhookEventType::Drift,
+                        format!(
+                            "Drift has occurred for {} in {}",
+                            deployment_id, environment
+                        ),
+                    );
+                    let drifted_resources: Vec<String> = content["resource_drift"]
+                        .as_array()
+                        .map(|drift| {
+                            drift
+                                .iter()
+                                .filter_map(|r| r["address"].as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    webhook_payload.drifted_resources = drifted_resources.clone();
+                    send_webhooks(handler, &payload.drift_detection.webhooks, &webhook_payload)
+                        .await;
+
+                    let mut notification = NotificationEvent::new(
+                        NotificationEventType::DriftDetected,
+                        payload,
+                        job_id,
+                    );
+                    notification.details = json!({ "drifted_resources": drifted_resources });
+                    send_notifications(handler, &notification).await;
                 }
             }
 
@@ -361,7 +530,12 @@ pub async fn terraform_show(
                     job_id
                 );
 
-                let resource_changes = sanitize_resource_changes_from_plan(&content);
+                // Drift checks are never applied, so their plan files are not kept
+                let (plan_file_key, state_version) = if refresh_only {
+                    (None, None)
+                } else {
+                    store_plan_file(handler, environment, deployment_id, command, job_id).await
+                };
 
                 let infra_change_record = InfraChangeRecord {
                     deployment_id: deployment_id.to_string(),
@@ -372,20 +546,21 @@ pub async fn terraform_show(
                     module_version: module.version.clone(),
                     epoch: get_epoch(),
                     timestamp: get_timestamp(),
-                    plan_std_output: plan_output.to_string(),
+                    plan_std_output: redact_secrets(plan_output),
                     plan_raw_json_key: plan_raw_json_key.clone(),
                     environment: environment.clone(),
                     change_type: command.to_string(),
                     resource_changes,
                     variables: status_handler.get_variables(),
+                    plan_file_key,
+                    state_serial: state_version.as_ref().map(|s| s.serial),
+                    state_lineage: state_version.map(|s| s.lineage),
+                    upgrade_warnings: payload.upgrade_warnings.clone(),
+                    cost_estimate,
+                    risk_assessment: Some(risk_assessment),
+                    protected_resource_override: None,
                 };
-                match insert_infra_change_record(
-                    handler,
-                    infra_change_record,
-                    &command_result.stdout,
-                )
-                .await
-                {
+                match insert_infra_change_record(handler, infra_change_record, &plan_json).await {
                     Ok(_) => {
                         println!("Infra change record for plan inserted");
                     }
@@ -400,8 +575,8 @@ pub async fn terraform_show(
         Err(e) => {
             println!("Error running \"terraform {}\" command: {:?}", cmd, e);
             let error_text = e.to_string();
-            let status = "failed_show_plan".to_string();
-            status_handler.set_status(status);
+            let status = DeploymentStatus::FailedShowPlan;
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.set_error_text(error_text);
             status_handler.send_event(handler).await;
@@ -412,32 +587,52 @@ pub async fn terraform_show(
     }
 }
 
+/// Estimates the monthly cost of a plan, a missing or invalid price catalog only skips the estimate
+fn get_cost_estimate(plan: &Value) -> Option<CostEstimate> {
+    match get_price_catalog() {
+        Ok(catalog) => Some(estimate_monthly_cost(plan, &catalog)),
+        Err(e) => {
+            println!("Warning: Not estimating cost: {:?}", e);
+            None
+        }
+    }
+}
+
 pub async fn record_apply_destroy_changes(
     payload: &ApiInfraPayload,
     job_id: &str,
     module: &env_defs::ModuleResp,
     apply_output: &str,
+    protected_resource_override: Option<ProtectedResourceOverride>,
     handler: &GenericCloudHandler,
     status_handler: &DeploymentStatusHandler<'_>,
 ) -> Result<(), anyhow::Error> {
     // Extract resource changes from the plan JSON (what was approved before execution)
-    let (resource_changes, raw_plan_json) = match tokio::fs::read_to_string("./tf_plan.json").await
-    {
-        Ok(plan_content) => match serde_json::from_str::<Value>(&plan_content) {
-            Ok(content) => {
-                let sanitized = sanitize_resource_changes_from_plan(&content);
-                (sanitized, plan_content)
-            }
+    let (resource_changes, raw_plan_json, cost_estimate, risk_assessment) =
+        match tokio::fs::read_to_string("./tf_plan.json").await {
+            Ok(plan_content) => match serde_json::from_str::<Value>(&plan_content) {
+                Ok(content) => {
+                    let sanitized = sanitize_resource_changes_from_plan(&content);
+                    let risk_assessment = assess_plan_risk(&sanitized);
+                    (
+                        sanitized,
+                        plan_content,
+                        get_cost_estimate(&content),
+                        Some(risk_assessment),
+                    )
+                }
+                Err(_) => {
+                    println!(
+                        "Warning: Could not parse tf_plan.json, storing empty resource_changes"
+                    );
+                    (Vec::new(), String::new(), None, None)
+                }
+            },
             Err(_) => {
-                println!("Warning: Could not parse tf_plan.json, storing empty resource_changes");
-                (Vec::new(), String::new())
+                println!("Warning: Could not read tf_plan.json, storing empty resource_changes");
+                (Vec::new(), String::new(), None, None)
             }
-        },
-        Err(_) => {
-            println!("Warning: Could not read tf_plan.json, storing empty resource_changes");
-            (Vec::new(), String::new())
-        }
-    };
+        };
 
     let infra_change_record = InfraChangeRecord {
         deployment_id: payload.deployment_id.clone(),
@@ -448,7 +643,7 @@ pub async fn record_apply_destroy_changes(
         module_version: module.version.clone(),
         epoch: get_epoch(),
         timestamp: get_timestamp(),
-        plan_std_output: apply_output.to_string(),
+        plan_std_output: redact_secrets(apply_output),
         plan_raw_json_key: format!(
             "{}{}/{}/{}_{}_apply_output.json",
             handler.get_storage_basepath(),
@@ -461,6 +656,13 @@ pub async fn record_apply_destroy_changes(
         change_type: payload.command.clone(),
         resource_changes,
         variables: status_handler.get_variables(),
+        plan_file_key: None,
+        state_serial: None,
+        state_lineage: None,
+        upgrade_warnings: payload.upgrade_warnings.clone(),
+        cost_estimate,
+        risk_assessment,
+        protected_resource_override,
     };
 
     let _record_id = insert_infra_change_record(handler, infra_change_record, &raw_plan_json)
@@ -490,7 +692,7 @@ pub async fn terraform_apply_destroy<'a>(
         true,
         false,
         false,
-        false,
+        payload.from_plan.is_some(),
         false,
         deployment_id,
         environment,
@@ -511,8 +713,8 @@ pub async fn terraform_apply_destroy<'a>(
         Err(e) => {
             println!("Error running \"terraform {}\" command: {:?}", cmd, e);
             let error_text = e.to_string();
-            let status = "error".to_string();
-            status_handler.set_status(status);
+            let status = DeploymentStatus::Error;
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.set_error_text(error_text);
             status_handler.send_event(handler).await;
@@ -554,7 +756,9 @@ pub async fn terraform_output(
         Ok(command_result) => {
             println!("Terraform {} successful", cmd);
 
-            let output = match serde_json::from_str(command_result.stdout.as_str()) {
+            // Outputs derived from secrets are stored with the secret references
+            let output = match serde_json::from_str(&redact_secrets_in_json(&command_result.stdout))
+            {
                 Ok(json) => json,
                 Err(e) => {
                     return Err(anyhow!(
@@ -565,23 +769,23 @@ pub async fn terraform_output(
                 }
             };
 
-            status_handler.set_status("successful".to_string());
+            status_handler.set_status(DeploymentStatus::Successful)?;
             status_handler.set_output(output);
             status_handler.send_deployment(handler).await?;
+            Ok(())
         }
         Err(e) => {
             println!("Error: {:?}", e);
 
-            let status = "failed_output".to_string();
-            status_handler.set_status(status);
+            let status = DeploymentStatus::FailedOutput;
+            status_handler.set_status(status)?;
             status_handler.set_event_duration();
             status_handler.set_last_event_epoch(); // Reset the event duration timer for the next event
             status_handler.send_event(handler).await;
             status_handler.send_deployment(handler).await?;
+            Err(anyhow!("Error running terraform output: {}", e))
         }
     }
-
-    Ok(())
 }
 
 pub async fn terraform_state_list() -> Result<Option<Vec<String>>, anyhow::Error> {
@@ -596,7 +800,8 @@ pub async fn terraform_state_list() -> Result<Option<Vec<String>>, anyhow::Error
 
     println!("Running terraform state list...");
 
-    match run_generic_command(&mut exec, 10000).await {
+    // Also capture the resources if the job was cancelled during apply/destroy
+    match run_uninterruptible_command(&mut exec, 10000).await {
         Ok(command_result) => {
             println!("Terraform state list successful");
 
@@ -622,6 +827,97 @@ pub async fn terraform_state_list() -> Result<Option<Vec<String>>, anyhow::Error
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TerraformStateVersion {
+    pub serial: u64,
+    pub lineage: String,
+}
+
+/// Serial and lineage of the current state, `None` if there is no state yet
+pub async fn terraform_state_version() -> Result<Option<TerraformStateVersion>, anyhow::Error> {
+    if env::var("TEST_MODE").is_ok() {
+        return Ok(None);
+    }
+    // Not run with run_generic_command since the state must not be printed
+    let output = tokio::process::Command::new("terraform")
+        .arg("state")
+        .arg("pull")
+        .current_dir(Path::new("./"))
+        .env("TF_CLI_CONFIG_FILE", "/app/.terraformrc")
+        .output()
+        .await
+        .context("Failed to run terraform state pull")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "terraform state pull failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    parse_state_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_state_version(state: &str) -> Result<Option<TerraformStateVersion>, anyhow::Error> {
+    if state.trim().is_empty() {
+        return Ok(None);
+    }
+    let state_version = serde_json::from_str(state).context("Failed to parse terraform state")?;
+    Ok(Some(state_version))
+}
+
+/// Releases the state lock if it is still held, which happens if terraform was killed
+pub async fn release_state_lock() -> Result<(), anyhow::Error> {
+    // Any command acquiring the lock reports the lock id if it is held, removing a resource that
+    // does not exist acquires the lock without changing the state
+    let mut exec = tokio::process::Command::new("terraform");
+    exec.arg("state")
+        .arg("rm")
+        .arg("-no-color")
+        .arg("-lock-timeout=0s")
+        .arg("terraform_data.infraweave_lock_check")
+        .current_dir(Path::new("./"))
+        .env("TF_CLI_CONFIG_FILE", "/app/.terraformrc")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+
+    let lock_id = match run_uninterruptible_command(&mut exec, 100).await {
+        Ok(_) => None,
+        Err(e) => parse_lock_id(&e.to_string()),
+    };
+    let lock_id = match lock_id {
+        Some(lock_id) => lock_id,
+        None => {
+            println!("State is not locked");
+            return Ok(());
+        }
+    };
+
+    println!("Releasing state lock {}...", lock_id);
+    let mut exec = tokio::process::Command::new("terraform");
+    exec.arg("force-unlock")
+        .arg("-force")
+        .arg(&lock_id)
+        .current_dir(Path::new("./"))
+        .env("TF_CLI_CONFIG_FILE", "/app/.terraformrc")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    run_uninterruptible_command(&mut exec, 100)
+        .await
+        .map_err(|e| anyhow!("Failed to release state lock {}: {}", lock_id, e))?;
+    Ok(())
+}
+
+fn parse_lock_id(output: &str) -> Option<String> {
+    if !output.contains("Error acquiring the state lock") {
+        return None;
+    }
+    output
+        .lines()
+        .skip_while(|line| !line.trim().starts_with("Lock Info:"))
+        .find_map(|line| line.trim().strip_prefix("ID:"))
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
 async fn download_all_providers(
     handler: &GenericCloudHandler,
     provider_versions: &[TfLockProvider],
@@ -749,3 +1045,102 @@ provider_installation {{
     download_all_providers(handler, provider_versions, target).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lock_id() {
+        let output = r#"
+Error: Error acquiring the state lock
+
+Error message: ConditionalCheckFailedException: The conditional request failed
+Lock Info:
+  ID:        5c8f1a2e-3b4d-4e6f-8a9b-0c1d2e3f4a5b
+  Path:      bucket/dev/s3bucket/my-bucket/terraform.tfstate
+  Operation: OperationTypeApply
+"#;
+        assert_eq!(
+            parse_lock_id(output),
+            Some("5c8f1a2e-3b4d-4e6f-8a9b-0c1d2e3f4a5b".to_string())
+        );
+        assert_eq!(
+            parse_lock_id("Error: Invalid target address\n  ID: something"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_state_version() {
+        let state = r#"{
+  "version": 4,
+  "terraform_version": "1.5.7",
+  "serial": 12,
+  "lineage": "8c6b2a1e-4f3d-2c1b-0a9f-8e7d6c5b4a3f",
+  "outputs": {},
+  "resources": []
+}"#;
+        assert_eq!(
+            parse_state_version(state).unwrap(),
+            Some(TerraformStateVersion {
+                serial: 12,
+                lineage: "8c6b2a1e-4f3d-2c1b-0a9f-8e7d6c5b4a3f".to_string(),
+            })
+        );
+        assert_eq!(parse_state_version("\n").unwrap(), None);
+        assert!(parse_state_version("not a state").is_err());
+    }
+
+    #[test]
+    fn test_verify_saved_plan() {
+        let change_record: InfraChangeRecord = serde_json::from_value(serde_json::json!({
+            "deployment_id": "s3bucket/my-bucket",
+            "project_id": "p",
+            "region": "eu-west-1",
+            "job_id": "plan-job",
+            "module": "s3bucket",
+            "environment": "dev",
+            "change_type": "plan",
+            "module_version": "1.0.0",
+            "epoch": 0,
+            "timestamp": "",
+            "plan_std_output": "",
+            "plan_raw_json_key": "",
+            "plan_file_key": "dev/s3bucket/my-bucket/plan_plan-job_planfile",
+            "state_serial": 3,
+            "state_lineage": "abc",
+        }))
+        .unwrap();
+        let state = |serial: u64, lineage: &str| {
+            Some(TerraformStateVersion {
+                serial,
+                lineage: lineage.to_string(),
+            })
+        };
+
+        assert!(verify_saved_plan(&change_record, "1.0.0", &state(3, "abc")).is_ok());
+        assert_eq!(
+            verify_saved_plan(&change_record, "1.0.0", &state(4, "abc"))
+                .unwrap_err()
+                .to_string(),
+            "the state changed since the plan (serial 3 of abc -> serial 4 of abc)"
+        );
+        assert!(verify_saved_plan(&change_record, "1.0.0", &state(3, "def")).is_err());
+        assert!(verify_saved_plan(&change_record, "1.0.0", &None).is_err());
+        assert_eq!(
+            verify_saved_plan(&change_record, "1.1.0", &state(3, "abc"))
+                .unwrap_err()
+                .to_string(),
+            "the module version changed since the plan (1.0.0 -> 1.1.0)"
+        );
+
+        let first_plan = InfraChangeRecord {
+            state_serial: None,
+            state_lineage: None,
+            ..change_record
+        };
+        assert!(verify_saved_plan(&first_plan, "1.0.0", &None).is_ok());
+        assert!(verify_saved_plan(&first_plan, "1.0.0", &state(1, "abc")).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use anyhow::anyhow;
+use env_common::interface::GenericCloudHandler;
+use env_common::DeploymentStatusHandler;
+use env_defs::{CloudProvider, DeploymentStatus};
+use env_utils::{
+    get_secret_references, redact_secret_values, redact_secret_values_in_json,
+    resolve_secret_references, store_tf_vars_json,
+};
+use serde_json::Value;
+
+/// Secrets resolved for this job, kept in memory only so they can be redacted from stored output
+static RESOLVED_SECRETS: OnceLock<BTreeMap<String, String>> = OnceLock::new();
+
+/// Stores the variables in terraform.tfvars.json with all `{{ secret::name }}` references
+/// resolved. The deployment keeps the references, the values are never persisted.
+pub async fn store_variables(
+    handler: &GenericCloudHandler,
+    status_handler: &mut DeploymentStatusHandler<'_>,
+) -> Result<(), anyhow::Error> {
+    let variables = status_handler.get_variables();
+    match resolve_secrets(handler, &variables).await {
+        Ok(resolved) => {
+            println!("Storing terraform variables in terraform.tfvars.json...");
+            store_tf_vars_json(&resolved, ".");
+            Ok(())
+        }
+        Err(e) => {
+            println!("Error resolving secrets: {:?}", e);
+            let status = DeploymentStatus::FailedPrepare;
+            status_handler.set_status(status)?;
+            status_handler.set_event_duration();
+            status_handler.set_error_text(format!("Failed to resolve secrets: {}", e));
+            status_handler.send_event(handler).await;
+            status_handler.send_deployment(handler).await?;
+            Err(anyhow!("Failed to resolve secrets: {}", e))
+        }
+    }
+}
+
+async fn resolve_secrets(
+    handler: &GenericCloudHandler,
+    variables: &Value,
+) -> Result<Value, anyhow::Error> {
+    let mut secrets = BTreeMap::new();
+    for name in get_secret_references(variables) {
+        let value = handler
+            .get_secret_value(&name)
+            .await
+            .map_err(|e| anyhow!("{}: {}", name, e))?;
+        secrets.insert(name, value);
+    }
+    if !secrets.is_empty() {
+        println!("Resolved {} secrets", secrets.len());
+    }
+    let resolved = resolve_secret_references(variables, &secrets)?;
+    let _ = RESOLVED_SECRETS.set(secrets);
+    Ok(resolved)
+}
+
+/// Whether secrets were resolved for this job, terraform files such as the plan file then hold
+/// their values in clear text
+pub fn has_resolved_secrets() -> bool {
+    RESOLVED_SECRETS
+        .get()
+        .is_some_and(|secrets| !secrets.is_empty())
+}
+
+/// Replaces resolved secret values in terraform output with their references
+pub fn redact_secrets(text: &str) -> String {
+    match RESOLVED_SECRETS.get() {
+        Some(secrets) if !secrets.is_empty() => redact_secret_values(text, secrets),
+        _ => text.to_string(),
+    }
+}
+
+/// Replaces resolved secret values in terraform JSON output with their references
+pub fn redact_secrets_in_json(text: &str) -> String {
+    match RESOLVED_SECRETS.get() {
+        Some(secrets) if !secrets.is_empty() => match serde_json::from_str::<Value>(text) {
+            Ok(value) => redact_secret_values_in_json(&value, secrets).to_string(),
+            Err(_) => redact_secret_values(text, secrets),
+        },
+        _ => text.to_string(),
+    }
+}
@@ -1,20 +1,264 @@
-use reqwest::Client;
-use serde_json::json;
+use std::time::Duration;
 
-pub async fn post_webhook(webhook_url: &str, message: &str) -> Result<(), anyhow::Error> {
-    let client = Client::new();
+use env_common::interface::GenericCloudHandler;
+use env_defs::{
+    ApiInfraPayload, CloudProvider, Webhook, WebhookEventType, WebhookPayload,
+    WEBHOOK_PAYLOAD_SCHEMA_VERSION,
+};
+use env_utils::get_timestamp;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use sha2::Sha256;
 
-    let payload = json!({
-        "text": message,
-    });
+type HmacSha256 = Hmac<Sha256>;
 
-    let response = client.post(webhook_url).json(&payload).send().await?;
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
 
-    if response.status().is_success() {
-        println!("Message sent successfully!");
-    } else {
-        println!("Failed to send message: {}", response.status());
+/// `sha256=<hex>` of the HMAC-SHA256 of `<timestamp>.<body>`, what receivers recompute to verify
+/// that a message came from InfraWeave
+pub fn sign_webhook_payload(signing_key: &str, timestamp: u64, body: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(signing_key.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn get_backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_BACKOFF)
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// The secret of an alias is either the url or `{"url": ..., "signingSecret": ...}`
+fn parse_alias_secret(value: &str) -> Result<(String, Option<String>), anyhow::Error> {
+    match serde_json::from_str::<Value>(value) {
+        Ok(Value::Object(alias)) => {
+            let url = alias
+                .get("url")
+                .and_then(|u| u.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Webhook alias has no url"))?;
+            let signing_key = alias
+                .get("signingSecret")
+                .and_then(|s| s.as_str())
+                .map(str::to_string);
+            Ok((url.to_string(), signing_key))
+        }
+        _ => Ok((value.trim().to_string(), None)),
     }
+}
+
+/// Url and signing key of a webhook or notification sink, looking up its alias and signing
+/// secret in the secret store
+pub(crate) async fn resolve_webhook_target(
+    handler: &GenericCloudHandler,
+    url: Option<&str>,
+    alias: Option<&str>,
+    signing_secret: Option<&str>,
+) -> Result<(String, Option<String>), anyhow::Error> {
+    let (url, signing_key) = match (alias, url) {
+        (Some(alias), _) => {
+            let value = handler
+                .get_secret_value(alias)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to resolve webhook alias {}: {}", alias, e))?;
+            parse_alias_secret(&value)?
+        }
+        (None, Some(url)) => (url.to_string(), None),
+        (None, None) => return Err(anyhow::anyhow!("Webhook has neither url nor alias")),
+    };
+    let signing_key = match signing_secret {
+        Some(name) => Some(handler.get_secret_value(name).await.map_err(|e| {
+            anyhow::anyhow!("Failed to resolve webhook signing secret {}: {}", name, e)
+        })?),
+        None => signing_key,
+    };
+    Ok((url, signing_key))
+}
+
+/// Posts the JSON `body` to `url`, retrying with exponential backoff on network errors,
+/// timeouts, rate limiting and server errors
+pub(crate) async fn post_json_with_retry(
+    url: &str,
+    signing_key: Option<&str>,
+    event: &str,
+    delivery_id: &str,
+    body: String,
+) -> Result<(), anyhow::Error> {
+    let client = Client::builder().timeout(Duration::from_secs(10)).build()?;
+
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("X-InfraWeave-Event", event)
+            .header("X-InfraWeave-Delivery", delivery_id);
+        if let Some(signing_key) = signing_key {
+            // Signed per attempt so receivers can reject old timestamps
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs();
+            request = request
+                .header("X-InfraWeave-Timestamp", timestamp.to_string())
+                .header(
+                    "X-InfraWeave-Signature",
+                    sign_webhook_payload(signing_key, timestamp, &body),
+                );
+        }
+
+        let error = match request.body(body.clone()).send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) if !is_retryable(response.status()) => {
+                return Err(anyhow::anyhow!(
+                    "Webhook responded with {}",
+                    response.status()
+                ));
+            }
+            Ok(response) => anyhow::anyhow!("Webhook responded with {}", response.status()),
+            Err(e) => anyhow::anyhow!("Failed to send webhook: {}", e),
+        };
+
+        attempt += 1;
+        if attempt >= MAX_ATTEMPTS {
+            return Err(anyhow::anyhow!("{} after {} attempts", error, MAX_ATTEMPTS));
+        }
+        let backoff = get_backoff(attempt - 1);
+        println!("{}, retrying in {:?}", error, backoff);
+        tokio::time::sleep(backoff).await;
+    }
+}
+
+/// Posts `payload` to `webhook_url`, see [`post_json_with_retry`]
+pub async fn post_webhook(
+    webhook_url: &str,
+    signing_key: Option<&str>,
+    payload: &WebhookPayload,
+) -> Result<(), anyhow::Error> {
+    post_json_with_retry(
+        webhook_url,
+        signing_key,
+        &payload.event.to_string(),
+        &payload.id,
+        serde_json::to_string(payload)?,
+    )
+    .await
+}
 
-    Ok(())
+pub fn get_webhook_payload(
+    payload: &ApiInfraPayload,
+    job_id: &str,
+    event: WebhookEventType,
+    text: String,
+) -> WebhookPayload {
+    WebhookPayload {
+        schema_version: WEBHOOK_PAYLOAD_SCHEMA_VERSION,
+        id: format!("{}-{}", job_id, event),
+        event,
+        timestamp: get_timestamp(),
+        project_id: payload.project_id.clone(),
+        region: payload.region.clone(),
+        environment: payload.environment.clone(),
+        deployment_id: payload.deployment_id.clone(),
+        module: payload.module.clone(),
+        module_version: payload.module_version.clone(),
+        job_id: job_id.to_string(),
+        command: payload.command.clone(),
+        text,
+        error_text: None,
+        drifted_resources: vec![],
+    }
+}
+
+/// Sends `payload` to every webhook subscribed to its event, failures are logged and never fail
+/// the job
+pub async fn send_webhooks(
+    handler: &GenericCloudHandler,
+    webhooks: &[Webhook],
+    payload: &WebhookPayload,
+) {
+    for webhook in webhooks
+        .iter()
+        .filter(|w| w.events.contains(&payload.event))
+    {
+        // The alias is logged instead of the url since the url may contain a token
+        let name = webhook.alias.as_deref().unwrap_or("with url");
+        let result = match resolve_webhook_target(
+            handler,
+            webhook.url.as_deref(),
+            webhook.alias.as_deref(),
+            webhook.signing_secret.as_deref(),
+        )
+        .await
+        {
+            Ok((url, signing_key)) => post_webhook(&url, signing_key.as_deref(), payload).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(_) => println!("Webhook {} sent {} event", name, payload.event),
+            Err(e) => println!(
+                "Error sending {} event to webhook {}: {}",
+                payload.event, name, e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_webhook_payload() {
+        // echo -n '1700000000.{"event":"drift"}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            sign_webhook_payload("secret", 1700000000, r#"{"event":"drift"}"#),
+            "sha256=4d6e63005d16ec0d19003d3d2f631df865e2aa4957e0f63b87926e9514c5a467"
+        );
+    }
+
+    #[test]
+    fn test_get_backoff() {
+        let backoffs: Vec<u64> = (0..7).map(|a| get_backoff(a).as_secs()).collect();
+        assert_eq!(backoffs, vec![1, 2, 4, 8, 16, 30, 30]);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+    }
+
+    #[test]
+    fn test_parse_alias_secret() {
+        assert_eq!(
+            parse_alias_secret("https://hooks.example.com/T000/B000\n").unwrap(),
+            ("https://hooks.example.com/T000/B000".to_string(), None)
+        );
+        assert_eq!(
+            parse_alias_secret(r#"{"url": "https://hooks.example.com", "signingSecret": "key"}"#)
+                .unwrap(),
+            (
+                "https://hooks.example.com".to_string(),
+                Some("key".to_string())
+            )
+        );
+        assert!(parse_alias_secret(r#"{"signingSecret": "key"}"#).is_err());
+    }
+
+    #[test]
+    fn test_webhook_events_default_to_drift() {
+        let webhook: Webhook = serde_json::from_str(r#"{"alias": "team-slack"}"#).unwrap();
+        assert_eq!(webhook.events, vec![WebhookEventType::Drift]);
+    }
 }
@@ -0,0 +1,235 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use env_defs::{CostEstimate, ResourceCostEstimate};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Price catalog shipped with InfraWeave, replaced by the file in `INFRAWEAVE_PRICE_CATALOG` if set
+const DEFAULT_PRICE_CATALOG: &str = include_str!("../catalogs/price_catalog.json");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceCatalog {
+    pub version: String,
+    pub currency: String,
+    pub resources: BTreeMap<String, ResourcePrice>,
+}
+
+/// Monthly price of a resource type, the sum of a fixed price, a price selected by an attribute
+/// and prices per unit of numeric attributes, times an optional count attribute
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ResourcePrice {
+    #[serde(default)]
+    pub monthly: f64,
+    /// Attribute selecting a price from `prices`, such as `instance_type`
+    pub attribute: Option<String>,
+    #[serde(default)]
+    pub prices: BTreeMap<String, f64>,
+    /// Price per unit of numeric attributes, such as GB of `allocated_storage`.
+    /// Nested attributes are separated by `.` with list indexes, `root_block_device.0.volume_size`
+    #[serde(default)]
+    pub per_unit: BTreeMap<String, f64>,
+    /// Numeric attribute with the number of instances, such as `num_cache_nodes`
+    pub multiplier: Option<String>,
+}
+
+pub fn parse_price_catalog(catalog: &str) -> Result<PriceCatalog, anyhow::Error> {
+    serde_json::from_str(catalog).map_err(|e| anyhow::anyhow!("Invalid price catalog: {}", e))
+}
+
+pub fn get_price_catalog() -> Result<PriceCatalog, anyhow::Error> {
+    match std::env::var("INFRAWEAVE_PRICE_CATALOG") {
+        Ok(path) if !path.is_empty() => parse_price_catalog(&std::fs::read_to_string(&path)?),
+        _ => parse_price_catalog(DEFAULT_PRICE_CATALOG),
+    }
+}
+
+fn get_attribute<'a>(attributes: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(attributes, |value, key| match value {
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => value.get(key),
+        })
+}
+
+fn get_number(attributes: &Value, path: &str) -> Option<f64> {
+    match get_attribute(attributes, path)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Monthly cost of a resource with `attributes`, unknown attributes do not add to the cost
+fn get_monthly_cost(price: &ResourcePrice, attributes: &Value) -> f64 {
+    if attributes.is_null() {
+        return 0.0;
+    }
+    let selected = match &price.attribute {
+        Some(attribute) => get_attribute(attributes, attribute)
+            .and_then(|v| v.as_str())
+            .and_then(|v| price.prices.get(v))
+            .copied()
+            .unwrap_or(0.0),
+        None => 0.0,
+    };
+    let per_unit: f64 = price
+        .per_unit
+        .iter()
+        .map(|(path, unit_price)| get_number(attributes, path).unwrap_or(0.0) * unit_price)
+        .sum();
+    let count = match &price.multiplier {
+        Some(path) => get_number(attributes, path).unwrap_or(1.0),
+        None => 1.0,
+    };
+    (price.monthly + selected + per_unit) * count
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Estimates the monthly cost before and after the plan (`terraform show -json`).
+/// Unchanged resources are part of the totals so they are the cost of the whole deployment.
+pub fn estimate_monthly_cost(plan: &Value, catalog: &PriceCatalog) -> CostEstimate {
+    let mut resources = vec![];
+    let mut unpriced = BTreeSet::new();
+    let (mut before_total, mut after_total) = (0.0, 0.0);
+
+    let resource_changes = plan
+        .get("resource_changes")
+        .and_then(|r| r.as_array())
+        .map(|r| r.as_slice())
+        .unwrap_or_default();
+    for resource in resource_changes {
+        if resource.get("mode").and_then(|m| m.as_str()) == Some("data") {
+            continue;
+        }
+        let resource_type = resource["type"].as_str().unwrap_or_default();
+        let price = match catalog.resources.get(resource_type) {
+            Some(price) => price,
+            None => {
+                unpriced.insert(resource_type.to_string());
+                continue;
+            }
+        };
+        let before = get_monthly_cost(price, &resource["change"]["before"]);
+        let after = get_monthly_cost(price, &resource["change"]["after"]);
+        before_total += before;
+        after_total += after;
+        if round_cents(before) != round_cents(after) {
+            resources.push(ResourceCostEstimate {
+                address: resource["address"].as_str().unwrap_or_default().to_string(),
+                resource_type: resource_type.to_string(),
+                monthly_cost_before: round_cents(before),
+                monthly_cost_after: round_cents(after),
+            });
+        }
+    }
+
+    CostEstimate {
+        currency: catalog.currency.clone(),
+        catalog_version: catalog.version.clone(),
+        monthly_cost_before: round_cents(before_total),
+        monthly_cost_after: round_cents(after_total),
+        monthly_delta: round_cents(after_total - before_total),
+        resources,
+        unpriced_resource_types: unpriced.into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn resource_change(address: &str, actions: &[&str], before: Value, after: Value) -> Value {
+        let (resource_type, name) = address.split_once('.').unwrap();
+        json!({
+            "address": address,
+            "mode": "managed",
+            "type": resource_type,
+            "name": name,
+            "change": {"actions": actions, "before": before, "after": after}
+        })
+    }
+
+    #[test]
+    fn test_default_price_catalog() {
+        let catalog = parse_price_catalog(DEFAULT_PRICE_CATALOG).unwrap();
+        assert_eq!(catalog.currency, "USD");
+        assert!(catalog.resources.contains_key("aws_instance"));
+    }
+
+    #[test]
+    fn test_estimate_monthly_cost() {
+        let catalog = parse_price_catalog(DEFAULT_PRICE_CATALOG).unwrap();
+        let plan = json!({
+            "resource_changes": [
+                resource_change(
+                    "aws_instance.web",
+                    &["update"],
+                    json!({"instance_type": "t3.micro", "root_block_device": [{"volume_size": 8}]}),
+                    json!({"instance_type": "t3.large", "root_block_device": [{"volume_size": 20}]}),
+                ),
+                resource_change("aws_nat_gateway.nat", &["no-op"], json!({}), json!({})),
+                resource_change(
+                    "aws_elasticache_cluster.cache",
+                    &["create"],
+                    Value::Null,
+                    json!({"node_type": "cache.t3.small", "num_cache_nodes": 2}),
+                ),
+                resource_change("aws_eip.ip", &["delete"], json!({}), Value::Null),
+                resource_change("aws_iam_role.role", &["create"], Value::Null, json!({})),
+                json!({
+                    "address": "data.aws_instance.lookup",
+                    "mode": "data",
+                    "type": "aws_instance",
+                    "name": "lookup",
+                    "change": {"actions": ["read"], "before": null, "after": {"instance_type": "m5.large"}}
+                }),
+            ]
+        });
+        let estimate = estimate_monthly_cost(&plan, &catalog);
+
+        // 7.59 + 8 * 0.08 + 32.85 + 3.65 before, 60.74 + 20 * 0.08 + 32.85 + 2 * 24.82 after
+        assert_eq!(estimate.monthly_cost_before, 44.73);
+        assert_eq!(estimate.monthly_cost_after, 144.83);
+        assert_eq!(estimate.monthly_delta, 100.1);
+        assert_eq!(
+            estimate
+                .resources
+                .iter()
+                .map(|r| (
+                    r.address.as_str(),
+                    r.monthly_cost_before,
+                    r.monthly_cost_after
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("aws_instance.web", 8.23, 62.34),
+                ("aws_elasticache_cluster.cache", 0.0, 49.64),
+                ("aws_eip.ip", 3.65, 0.0),
+            ]
+        );
+        assert_eq!(estimate.unpriced_resource_types, vec!["aws_iam_role"]);
+        assert_eq!(
+            estimate.to_string(),
+            "+100.10 USD/month (44.73 -> 144.83, price catalog 2026-10-01)"
+        );
+    }
+
+    #[test]
+    fn test_unknown_attributes_are_not_priced() {
+        let catalog = parse_price_catalog(DEFAULT_PRICE_CATALOG).unwrap();
+        let plan = json!({
+            "resource_changes": [
+                resource_change("aws_instance.web", &["create"], Value::Null, json!({"instance_type": "x9.huge"})),
+                resource_change("aws_dynamodb_table.t", &["create"], Value::Null, json!({"read_capacity": null})),
+            ]
+        });
+        let estimate = estimate_monthly_cost(&plan, &catalog);
+        assert_eq!(estimate.monthly_delta, 0.0);
+        assert!(estimate.resources.is_empty());
+    }
+}
@@ -0,0 +1,405 @@
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Timelike, Utc};
+use env_defs::{DriftDetection, QuietHours};
+
+/// A five field cron expression (minute, hour, day of month, month, day of week) in UTC
+#[derive(Debug, Clone, PartialEq)]
+struct CronSchedule {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    days_of_week: Vec<u32>,
+    // Cron matches either day field when both are restricted
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum DriftSchedule {
+    Interval(std::time::Duration),
+    Cron(CronSchedule),
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+fn parse_cron_value(value: &str, min: u32, names: &[&str]) -> Result<u32, anyhow::Error> {
+    if let Some(index) = names.iter().position(|n| n.eq_ignore_ascii_case(value)) {
+        return Ok(index as u32 + min);
+    }
+    value
+        .parse::<u32>()
+        .map_err(|_| anyhow::anyhow!("Invalid value {}", value))
+}
+
+fn parse_cron_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<Vec<u32>, anyhow::Error> {
+    let mut values = vec![];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid step {}", step))?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (
+                    parse_cron_value(start, min, names)?,
+                    parse_cron_value(end, min, names)?,
+                ),
+                None => {
+                    let value = parse_cron_value(range, min, names)?;
+                    // `5/15` means every 15 starting at 5
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(anyhow::anyhow!("{} is out of range {}-{}", range, min, max));
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    values.sort();
+    values.dedup();
+    Ok(values)
+}
+
+impl CronSchedule {
+    fn parse(expression: &str) -> Result<Self, anyhow::Error> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields.as_slice() else {
+            return Err(anyhow::anyhow!(
+                "Expected 5 fields (minute hour day-of-month month day-of-week)"
+            ));
+        };
+        let days_of_week = parse_cron_field(day_of_week, 0, 7, &DAY_NAMES)?
+            .into_iter()
+            // Both 0 and 7 are Sunday
+            .map(|d| d % 7)
+            .collect();
+        Ok(CronSchedule {
+            minutes: parse_cron_field(minute, 0, 59, &[])?,
+            hours: parse_cron_field(hour, 0, 23, &[])?,
+            days_of_month: parse_cron_field(day_of_month, 1, 31, &[])?,
+            months: parse_cron_field(month, 1, 12, &MONTH_NAMES)?,
+            days_of_week,
+            day_of_month_restricted: *day_of_month != "*",
+            day_of_week_restricted: *day_of_week != "*",
+        })
+    }
+
+    fn matches_day(&self, time: &DateTime<Utc>) -> bool {
+        let day_of_month = self.days_of_month.contains(&time.day());
+        let day_of_week = self
+            .days_of_week
+            .contains(&time.weekday().num_days_from_sunday());
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+
+    /// First matching minute strictly after `after`
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(366 * 5);
+        while time < limit {
+            if !self.months.contains(&time.month()) {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.matches_day(&time) {
+                time = (time + Duration::days(1)).with_hour(0)?.with_minute(0)?;
+            } else if !self.hours.contains(&time.hour()) {
+                time = (time + Duration::hours(1)).with_minute(0)?;
+            } else if !self.minutes.contains(&time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+impl DriftSchedule {
+    fn parse(interval: &str) -> Result<Self, anyhow::Error> {
+        if interval.split_whitespace().count() > 1 {
+            CronSchedule::parse(interval)
+                .map(DriftSchedule::Cron)
+                .map_err(|e| anyhow::anyhow!("Invalid cron expression \"{}\": {}", interval, e))
+        } else {
+            humantime::parse_duration(interval)
+                .map(DriftSchedule::Interval)
+                .map_err(|e| anyhow::anyhow!("Invalid interval \"{}\": {}", interval, e))
+        }
+    }
+}
+
+fn parse_time_of_day(time: &str) -> Result<NaiveTime, anyhow::Error> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| anyhow::anyhow!("Invalid time \"{}\", expected HH:MM", time))
+}
+
+/// End of the quiet hours window `time` is in, if any
+fn quiet_hours_end(
+    quiet_hours: &QuietHours,
+    time: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, anyhow::Error> {
+    let start = parse_time_of_day(&quiet_hours.start)?;
+    let end = parse_time_of_day(&quiet_hours.end)?;
+    let now = time.time();
+    let today = time.date_naive();
+    let end_today = today.and_time(end).and_utc();
+    let in_window = if start <= end {
+        start <= now && now < end
+    } else {
+        // Window crossing midnight, e.g. 22:00-06:00
+        now >= start || now < end
+    };
+    Ok(match in_window {
+        false => None,
+        true if start > end && now >= start => Some(end_today + Duration::days(1)),
+        true => Some(end_today),
+    })
+}
+
+fn get_jitter_millis(drift_detection: &DriftDetection, seed: &str) -> Result<u64, anyhow::Error> {
+    let jitter = match &drift_detection.jitter {
+        Some(jitter) => humantime::parse_duration(jitter)
+            .map_err(|e| anyhow::anyhow!("Invalid jitter \"{}\": {}", jitter, e))?,
+        None => return Ok(0),
+    };
+    let jitter = jitter.as_millis() as u64;
+    if jitter == 0 {
+        return Ok(0);
+    }
+    // FNV-1a, stable across builds unlike the std hasher, so every component computes the same delay
+    let hash = seed.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    Ok(hash % jitter)
+}
+
+fn epoch_to_datetime(epoch_millis: u128) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(epoch_millis as i64)
+        .single()
+        .unwrap_or_default()
+}
+
+/// Checks the schedule, jitter and quiet hours of a drift detection configuration
+pub fn validate_drift_detection(drift_detection: &DriftDetection) -> Result<(), anyhow::Error> {
+    DriftSchedule::parse(&drift_detection.interval)?;
+    get_jitter_millis(drift_detection, "")?;
+    for quiet_hours in drift_detection.quiet_hours.iter() {
+        quiet_hours_end(quiet_hours, Utc::now())?;
+    }
+    Ok(())
+}
+
+/// Whether `epoch_millis` is inside one of the quiet hours of the deployment
+pub fn is_in_quiet_hours(drift_detection: &DriftDetection, epoch_millis: u128) -> bool {
+    let time = epoch_to_datetime(epoch_millis);
+    drift_detection
+        .quiet_hours
+        .iter()
+        .any(|q| matches!(quiet_hours_end(q, time), Ok(Some(_))))
+}
+
+/// Epoch (ms) of the next drift check after `now_millis`, or -1 if drift detection is disabled.
+///
+/// The interval or cron schedule is followed by the jitter of the deployment (derived from
+/// `seed`), and a check that falls in quiet hours is moved to the end of them.
+pub fn get_next_drift_check_epoch(
+    drift_detection: &DriftDetection,
+    seed: &str,
+    now_millis: u128,
+) -> Result<i128, anyhow::Error> {
+    if !drift_detection.enabled || drift_detection.interval.is_empty() {
+        return Ok(-1);
+    }
+    let now = epoch_to_datetime(now_millis);
+    let next = match DriftSchedule::parse(&drift_detection.interval)? {
+        DriftSchedule::Interval(interval) => {
+            now + Duration::milliseconds(interval.as_millis() as i64)
+        }
+        DriftSchedule::Cron(cron) => cron.next_after(now).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cron expression \"{}\" never matches",
+                drift_detection.interval
+            )
+        })?,
+    };
+    let mut next = next + Duration::milliseconds(get_jitter_millis(drift_detection, seed)? as i64);
+
+    // Windows can overlap or follow each other, so check again after moving
+    for _ in 0..=drift_detection.quiet_hours.len() {
+        let mut moved = false;
+        for quiet_hours in drift_detection.quiet_hours.iter() {
+            if let Some(end) = quiet_hours_end(quiet_hours, next)? {
+                next = end;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    Ok(next.timestamp_millis() as i128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn drift_detection(
+        interval: &str,
+        jitter: Option<&str>,
+        quiet: &[(&str, &str)],
+    ) -> DriftDetection {
+        DriftDetection {
+            enabled: true,
+            interval: interval.to_string(),
+            auto_remediate: false,
+            webhooks: vec![],
+            jitter: jitter.map(str::to_string),
+            quiet_hours: quiet
+                .iter()
+                .map(|(start, end)| QuietHours {
+                    start: start.to_string(),
+                    end: end.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn epoch(time: &str) -> u128 {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .timestamp_millis() as u128
+    }
+
+    fn next(drift_detection: &DriftDetection, now: &str) -> String {
+        let next =
+            get_next_drift_check_epoch(drift_detection, "s3bucket/bucket", epoch(now)).unwrap();
+        epoch_to_datetime(next as u128).to_rfc3339()
+    }
+
+    #[test]
+    fn test_interval_schedule() {
+        let schedule = drift_detection("6h", None, &[]);
+        assert_eq!(
+            next(&schedule, "2024-05-06T10:15:00Z"),
+            "2024-05-06T16:15:00+00:00"
+        );
+
+        let disabled = DriftDetection {
+            enabled: false,
+            ..schedule
+        };
+        assert_eq!(
+            get_next_drift_check_epoch(&disabled, "", epoch("2024-05-06T10:15:00Z")).unwrap(),
+            -1
+        );
+    }
+
+    #[test]
+    fn test_cron_schedule() {
+        // Every 6 hours on weekdays, 2024-05-10 is a Friday
+        let schedule = drift_detection("0 */6 * * 1-5", None, &[]);
+        assert_eq!(
+            next(&schedule, "2024-05-06T10:15:00Z"),
+            "2024-05-06T12:00:00+00:00"
+        );
+        assert_eq!(
+            next(&schedule, "2024-05-06T12:00:00Z"),
+            "2024-05-06T18:00:00+00:00"
+        );
+        assert_eq!(
+            next(&schedule, "2024-05-10T19:00:00Z"),
+            "2024-05-13T00:00:00+00:00"
+        );
+
+        let schedule = drift_detection("30 2 1 jan,jul *", None, &[]);
+        assert_eq!(
+            next(&schedule, "2024-05-06T10:15:00Z"),
+            "2024-07-01T02:30:00+00:00"
+        );
+
+        // Either day field matches when both are set, the 15th or any Sunday (0 and 7)
+        let schedule = drift_detection("0 0 15 * 7", None, &[]);
+        assert_eq!(
+            next(&schedule, "2024-05-06T10:15:00Z"),
+            "2024-05-12T00:00:00+00:00"
+        );
+        assert_eq!(
+            next(&schedule, "2024-05-13T10:15:00Z"),
+            "2024-05-15T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_jitter_is_stable() {
+        let schedule = drift_detection("1h", Some("10m"), &[]);
+        let now = epoch("2024-05-06T10:15:00Z");
+        let first = get_next_drift_check_epoch(&schedule, "s3bucket/bucket", now).unwrap();
+        let second = get_next_drift_check_epoch(&schedule, "s3bucket/bucket", now).unwrap();
+        assert_eq!(first, second);
+        let delay = first - now as i128 - 60 * 60 * 1000;
+        assert!((0..10 * 60 * 1000).contains(&delay));
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let schedule = drift_detection("1h", None, &[("22:00", "06:00"), ("06:00", "07:30")]);
+        assert_eq!(
+            next(&schedule, "2024-05-06T10:15:00Z"),
+            "2024-05-06T11:15:00+00:00"
+        );
+        assert_eq!(
+            next(&schedule, "2024-05-06T21:30:00Z"),
+            "2024-05-07T07:30:00+00:00"
+        );
+        assert_eq!(
+            next(&schedule, "2024-05-07T01:00:00Z"),
+            "2024-05-07T07:30:00+00:00"
+        );
+
+        assert!(is_in_quiet_hours(&schedule, epoch("2024-05-06T23:00:00Z")));
+        assert!(is_in_quiet_hours(&schedule, epoch("2024-05-07T06:45:00Z")));
+        assert!(!is_in_quiet_hours(&schedule, epoch("2024-05-07T07:30:00Z")));
+    }
+
+    #[test]
+    fn test_validate_drift_detection() {
+        assert!(
+            validate_drift_detection(&drift_detection("0 */6 * * mon-fri", Some("5m"), &[]))
+                .is_ok()
+        );
+        assert!(validate_drift_detection(&drift_detection("0 */6 * *", None, &[])).is_err());
+        assert!(validate_drift_detection(&drift_detection("61 * * * *", None, &[])).is_err());
+        assert!(validate_drift_detection(&drift_detection("5 minutes", None, &[])).is_err());
+        assert!(validate_drift_detection(&drift_detection("15m", Some("soon"), &[])).is_err());
+        assert!(
+            validate_drift_detection(&drift_detection("15m", None, &[("22", "06:00")])).is_err()
+        );
+    }
+}
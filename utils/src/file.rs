@@ -185,6 +185,15 @@ pub fn merge_zips(input: ZipInput) -> Result<Vec<u8>, Box<dyn std::error::Error>
 
 pub async fn download_zip(url: &str, path: &Path) -> Result<(), anyhow::Error> {
     info!("Downloading ZIP file from {url} to {}", path.display());
+    if let Some(source) = url.strip_prefix("file://") {
+        // Used by the local provider which keeps its blobs on disk
+        if !Path::new(source).exists() {
+            return Err(anyhow::anyhow!("remote object does not exist (404)"));
+        }
+        fs::copy(source, path)
+            .with_context(|| format!("failed to copy {source} to {}", path.display()))?;
+        return Ok(());
+    }
     let resp = reqwest::get(url)
         .await
         .with_context(|| format!("request to {url} failed"))?;
@@ -208,6 +217,9 @@ pub async fn download_zip(url: &str, path: &Path) -> Result<(), anyhow::Error> {
 
 pub async fn download_zip_to_vec(url: &str) -> Result<Vec<u8>, anyhow::Error> {
     info!("Downloading zip file from {} to vec", url);
+    if let Some(source) = url.strip_prefix("file://") {
+        return fs::read(source).with_context(|| format!("failed to read {source}"));
+    }
     let response = reqwest::get(url).await?.bytes().await?;
     Ok(response.to_vec())
 }
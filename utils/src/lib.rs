@@ -1,5 +1,7 @@
+mod cost_estimation;
 mod deployment;
 mod dir;
+mod drift_schedule;
 mod file;
 mod general;
 mod json;
@@ -8,18 +10,30 @@ mod logging;
 mod module;
 mod module_diff;
 mod oci;
+mod plan_risk;
+mod policy_evaluation;
+mod policy_test;
+mod policy_waiver;
 mod provider_util;
 mod schema_validation;
+mod secrets;
 mod stack;
 mod string_utils;
 mod tar;
 mod terraform;
 mod time;
+mod variable_validation;
 mod variables;
 mod versioning;
 
+pub use cost_estimation::{
+    estimate_monthly_cost, get_price_catalog, parse_price_catalog, PriceCatalog, ResourcePrice,
+};
 pub use deployment::{generate_deployment_claim, generate_module_example_deployment};
 pub use dir::create_temp_dir;
+pub use drift_schedule::{
+    get_next_drift_check_epoch, is_in_quiet_hours, validate_drift_detection,
+};
 pub use file::{
     clean_root, copy_dir_recursive, download_zip, download_zip_to_vec, get_terraform_lockfile,
     get_terraform_tfvars, get_zip_file, get_zip_file_from_str, merge_zips, read_file_base64,
@@ -39,19 +53,34 @@ pub use module::{
     validate_tf_backend_not_set, validate_tf_extra_environment_variables,
     validate_tf_required_providers_is_set,
 };
-pub use module_diff::diff_modules;
+pub use module_diff::{diff_modules, get_module_upgrade_warnings};
 pub use oci::{
     get_module_manifest_from_oci_targz, get_module_zip_from_oci_targz, save_oci_artifacts_separate,
     verify_oci_artifacts_offline,
 };
+pub use plan_risk::assess_plan_risk;
+pub use policy_evaluation::{
+    evaluate_policy, evaluate_rego_policy, get_policy_data, read_rego_files, validate_rego_files,
+    PolicyViolations,
+};
+pub use policy_test::{
+    get_violation_diff, read_policy_data, read_policy_test_cases, run_policy_tests,
+    write_policy_test_case, PolicyTestCase, PolicyTestResult, POLICY_TESTS_DIR,
+};
+pub use policy_waiver::{apply_policy_waivers, validate_policy_waivers, WaivedViolations};
 pub use provider_util::{
     _get_change_records, _get_dependents, _get_deployment, _get_deployment_and_dependents,
     _get_deployments, _get_events, _get_module_optional, _get_modules, _get_policies, _get_policy,
     _get_provider_optional, _get_providers, _mutate_deployment, get_projects,
+    is_change_record_not_found,
 };
 pub use schema_validation::{validate_module_schema, validate_policy_schema};
+pub use secrets::{
+    get_secret_reference, get_secret_references, is_secret_reference, redact_secret_values,
+    redact_secret_values_in_json, resolve_secret_references,
+};
 pub use stack::read_stack_directory;
-pub use string_utils::{to_camel_case, to_snake_case};
+pub use string_utils::{matches_pattern, to_camel_case, to_snake_case};
 pub use tar::{get_diff_id_from_zip, targz_to_zip_bytes, zip_bytes_to_targz};
 pub use terraform::{
     get_extra_environment_variables, get_extra_environment_variables_all, get_provider_url_key,
@@ -59,9 +88,11 @@ pub use terraform::{
     store_tf_vars_json, DestructiveChange,
 };
 pub use time::{epoch_to_timestamp, get_epoch, get_timestamp};
+pub use variable_validation::verify_variable_validations;
 pub use variables::{
-    verify_output_name_roundtrip, verify_required_variables_are_set, verify_variable_claim_casing,
-    verify_variable_existence_and_type, verify_variable_name_roundtrip,
+    parse_tf_type, verify_output_name_roundtrip, verify_required_variables_are_set,
+    verify_variable_claim_casing, verify_variable_existence_and_type,
+    verify_variable_name_roundtrip, TfObjectAttribute, TfType,
 };
 pub use versioning::{
     get_version_track, semver_parse, semver_parse_without_build, zero_pad_semver,
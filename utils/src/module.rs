@@ -1,6 +1,7 @@
 use env_defs::TfLockProvider;
 use env_defs::TfOutput;
 use env_defs::TfRequiredProvider;
+use env_defs::TfValidation;
 use env_defs::TfVariable;
 use hcl::de;
 use hcl::Block;
@@ -204,6 +205,7 @@ pub fn get_variables_from_tf_files(contents: &str) -> Result<Vec<TfVariable>, St
     let parsed_hcl: HashMap<String, serde_json::Value> =
         de::from_str(contents).map_err(|err| format!("Failed to parse HCL: {}", err))?;
 
+    let mut validations = get_variable_validations_from_tf_files(contents)?;
     let mut variables = Vec::new();
 
     // Iterate through the HCL blocks (assuming `parsed_hcl` is correctly structured)
@@ -251,6 +253,7 @@ pub fn get_variables_from_tf_files(contents: &str) -> Result<Vec<TfVariable>, St
                     description,
                     nullable,
                     sensitive,
+                    validations: validations.remove(var_name).unwrap_or_default(),
                 };
 
                 debug!("Parsing variable block {:?} as {:?}", var_attrs, variable);
@@ -261,6 +264,47 @@ pub fn get_variables_from_tf_files(contents: &str) -> Result<Vec<TfVariable>, St
     Ok(variables)
 }
 
+/// The `validation` blocks of each variable, the condition is kept as HCL source so it can be
+/// evaluated when a claim is validated
+fn get_variable_validations_from_tf_files(
+    contents: &str,
+) -> Result<HashMap<String, Vec<TfValidation>>, String> {
+    let body = hcl::parse(contents).map_err(|err| format!("Failed to parse HCL: {}", err))?;
+
+    let mut validations: HashMap<String, Vec<TfValidation>> = HashMap::new();
+    for block in body.blocks().filter(|b| b.identifier() == "variable") {
+        let Some(var_name) = block.labels().first() else {
+            continue;
+        };
+        for validation in block.body().blocks().filter(|b| b.identifier() == "validation") {
+            let attribute = |key: &str| {
+                validation
+                    .body()
+                    .attributes()
+                    .find(|a| a.key() == key)
+                    .map(|a| a.expr().clone())
+            };
+            let Some(condition) = attribute("condition") else {
+                continue;
+            };
+            let message = match attribute("error_message") {
+                Some(Expression::String(s)) => s,
+                Some(Expression::TemplateExpr(template)) => template.to_string(),
+                Some(other) => other.to_string(),
+                None => "".to_string(),
+            };
+            validations
+                .entry(var_name.as_str().to_string())
+                .or_default()
+                .push(TfValidation {
+                    expression: condition.to_string(),
+                    message,
+                });
+        }
+    }
+    Ok(validations)
+}
+
 #[allow(dead_code)]
 pub fn get_outputs_from_tf_files(contents: &str) -> Result<Vec<env_defs::TfOutput>, String> {
     let hcl_body = hcl::parse(contents)
@@ -498,10 +542,41 @@ variable "bucket_name" {
                 description: "".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             }
         );
     }
 
+    #[test]
+    fn test_get_variable_block_validations() {
+        let variables_str = r#"
+variable "bucket_name" {
+  type = string
+  validation {
+    condition     = length(var.bucket_name) >= 3
+    error_message = "The bucket name must be at least 3 characters."
+  }
+  validation {
+    condition     = can(regex("^[a-z0-9-]+$", var.bucket_name))
+    error_message = "The bucket name ${var.bucket_name} must be lowercase."
+  }
+}
+"#;
+        assert_eq!(
+            get_variables_from_tf_files(variables_str).unwrap()[0].validations,
+            vec![
+                TfValidation {
+                    expression: "length(var.bucket_name) >= 3".to_string(),
+                    message: "The bucket name must be at least 3 characters.".to_string(),
+                },
+                TfValidation {
+                    expression: r#"can(regex("^[a-z0-9-]+$", var.bucket_name))"#.to_string(),
+                    message: "The bucket name ${var.bucket_name} must be lowercase.".to_string(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_get_variable_block_map_string() {
         let variables_str = r#"
@@ -528,6 +603,7 @@ variable "tags" {
                 description: "".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             }
         );
     }
@@ -551,6 +627,7 @@ variable "tags" {
                 description: "".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             }
         );
     }
@@ -574,6 +651,7 @@ variable "tags" {
                 description: "".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             }
         );
     }
@@ -828,6 +906,7 @@ provider "registry.opentofu.org/hashicorp/kubernetes" {
                 description: "".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "INFRAWEAVE_DEPLOYMENT_ID".to_string(),
@@ -836,6 +915,7 @@ provider "registry.opentofu.org/hashicorp/kubernetes" {
                 description: "Some description maybe".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             },
         ];
 
@@ -857,6 +937,7 @@ provider "registry.opentofu.org/hashicorp/kubernetes" {
                 description: "".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "INFRAWEAVE_DEPLOYMENT_ID".to_string(),
@@ -865,6 +946,7 @@ provider "registry.opentofu.org/hashicorp/kubernetes" {
                 description: "Some description maybe".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             },
         ];
 
@@ -886,6 +968,7 @@ provider "registry.opentofu.org/hashicorp/kubernetes" {
                 description: "".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "INFRAWEAVE_DEPLOYMENT_ID".to_string(),
@@ -894,6 +977,7 @@ provider "registry.opentofu.org/hashicorp/kubernetes" {
                 description: "Some description maybe".to_string(),
                 nullable: true,
                 sensitive: false,
+                validations: vec![],
             },
         ];
 
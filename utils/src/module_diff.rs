@@ -1,4 +1,7 @@
-use env_defs::{ModuleDiffAddition, ModuleDiffChange, ModuleDiffRemoval};
+use env_defs::{
+    ModuleDiffAddition, ModuleDiffChange, ModuleDiffRemoval, ModuleResp, ModuleUpgradeWarning,
+    ModuleUpgradeWarningKind, TfVariable,
+};
 use hcl::from_str as hcl_from_str;
 use hcl::Value as HclValue;
 use serde_json::Value as JsonValue;
@@ -30,6 +33,74 @@ pub fn diff_modules(
     diff_values(&module1, &module2, "")
 }
 
+/// Same rule as `verify_required_variables_are_set`
+fn is_required(variable: &TfVariable) -> bool {
+    match &variable.default {
+        None => true,
+        Some(serde_json::Value::Null) => !variable.nullable,
+        Some(_) => false,
+    }
+}
+
+/// Finds the variables of a claim that are affected by moving it from the module version
+/// `from` to `to`: defaults it relies on that change, variables that are removed and variables
+/// that become required without being set. `variables` are the claim variables in snake_case.
+pub fn get_module_upgrade_warnings(
+    from: &ModuleResp,
+    to: &ModuleResp,
+    variables: &JsonValue,
+) -> Vec<ModuleUpgradeWarning> {
+    upgrade_warnings(
+        (&from.version, &from.tf_variables),
+        (&to.version, &to.tf_variables),
+        variables,
+    )
+}
+
+fn upgrade_warnings(
+    (from_version, from_variables): (&str, &[TfVariable]),
+    (to_version, to_variables): (&str, &[TfVariable]),
+    variables: &JsonValue,
+) -> Vec<ModuleUpgradeWarning> {
+    let is_set = |name: &str| variables.get(name).is_some();
+    let warning = |variable: &TfVariable, kind: ModuleUpgradeWarningKind| ModuleUpgradeWarning {
+        variable: crate::to_camel_case(&variable.name),
+        kind,
+        from_version: from_version.to_string(),
+        to_version: to_version.to_string(),
+        old_default: None,
+        new_default: None,
+        set_in_claim: is_set(&variable.name),
+    };
+
+    let mut warnings = vec![];
+    for old in from_variables.iter() {
+        match to_variables.iter().find(|v| v.name == old.name) {
+            None => warnings.push(warning(old, ModuleUpgradeWarningKind::Removed)),
+            Some(new) if is_set(&new.name) => {}
+            Some(new) if is_required(new) => {
+                warnings.push(warning(new, ModuleUpgradeWarningKind::BecameRequired))
+            }
+            Some(new) if new.default != old.default => warnings.push(ModuleUpgradeWarning {
+                old_default: old.default.clone(),
+                new_default: new.default.clone(),
+                ..warning(new, ModuleUpgradeWarningKind::DefaultChanged)
+            }),
+            Some(_) => {}
+        }
+    }
+    // New variables without a default are also required
+    for new in to_variables.iter() {
+        if !from_variables.iter().any(|v| v.name == new.name)
+            && !is_set(&new.name)
+            && is_required(new)
+        {
+            warnings.push(warning(new, ModuleUpgradeWarningKind::BecameRequired));
+        }
+    }
+    warnings
+}
+
 // Compare two serde_json::Value objects and collect the differences
 fn diff_values(
     value1: &JsonValue,
@@ -339,4 +410,75 @@ mod tests {
         assert_eq!(changes, expected_changes);
         assert_eq!(removals, expected_removals);
     }
+
+    fn variable(name: &str, default: Option<JsonValue>) -> TfVariable {
+        TfVariable {
+            name: name.to_string(),
+            _type: serde_json::json!("string"),
+            default,
+            description: "".to_string(),
+            nullable: true,
+            sensitive: false,
+            validations: vec![],
+        }
+    }
+
+    #[test]
+    fn test_module_upgrade_warnings() {
+        let from = vec![
+            variable("bucket_name", None),
+            variable("versioning", Some(serde_json::json!(true))),
+            variable("retention_days", Some(serde_json::json!(7))),
+            variable("acl", Some(serde_json::json!("private"))),
+            variable("legacy_tags", Some(serde_json::json!({}))),
+            variable("legacy_policy", Some(serde_json::json!(""))),
+        ];
+        let to = vec![
+            variable("bucket_name", None),
+            variable("versioning", Some(serde_json::json!(false))),
+            variable("retention_days", None),
+            variable("acl", Some(serde_json::json!("private"))),
+            variable("kms_key_id", None),
+            variable("log_bucket", Some(serde_json::Value::Null)),
+        ];
+        let claim = serde_json::json!({
+            "bucket_name": "bucket",
+            "legacy_tags": {"team": "a"},
+        });
+
+        let warnings = upgrade_warnings(("1.0.0", &from), ("2.0.0", &to), &claim);
+        let summary: Vec<(String, bool)> = warnings
+            .iter()
+            .map(|w| (w.to_string(), w.is_error()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "Default of versioning changes from true to false (1.0.0 -> 2.0.0), \
+                    the claim does not set it"
+                        .to_string(),
+                    false
+                ),
+                (
+                    "Variable retentionDays is required in 2.0.0, set it in the claim".to_string(),
+                    true
+                ),
+                (
+                    "Variable legacyTags is removed in 2.0.0, remove it from the claim".to_string(),
+                    true
+                ),
+                (
+                    "Variable legacyPolicy is removed in 2.0.0".to_string(),
+                    false
+                ),
+                (
+                    "Variable kmsKeyId is required in 2.0.0, set it in the claim".to_string(),
+                    true
+                ),
+            ]
+        );
+
+        assert!(upgrade_warnings(("1.0.0", &from), ("1.0.1", &from), &claim).is_empty());
+    }
 }
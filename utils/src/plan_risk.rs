@@ -0,0 +1,380 @@
+use env_defs::{
+    ResourceAction, ResourceMode, RiskAssessment, RiskFinding, RiskFindingKind, RiskLevel,
+    SanitizedResourceChange,
+};
+use serde_json::Value;
+
+const CREATE_SCORE: u32 = 1;
+const UPDATE_SCORE: u32 = 2;
+const REPLACE_SCORE: u32 = 5;
+const DELETE_SCORE: u32 = 5;
+const STATEFUL_SCORE: u32 = 40;
+const IAM_SCORE: u32 = 15;
+const NETWORK_SCORE: u32 = 10;
+const SENSITIVE_SCORE: u32 = 10;
+const MAX_SCORE: u32 = 100;
+
+/// Resource types holding data that is lost when they are replaced or deleted
+const STATEFUL_RESOURCE_TYPES: &[&str] = &[
+    "aws_db_instance",
+    "aws_docdb_cluster",
+    "aws_dynamodb_table",
+    "aws_ebs_volume",
+    "aws_efs_file_system",
+    "aws_elasticache_cluster",
+    "aws_elasticache_replication_group",
+    "aws_elasticsearch_domain",
+    "aws_kinesis_stream",
+    "aws_kms_key",
+    "aws_msk_cluster",
+    "aws_neptune_cluster",
+    "aws_opensearch_domain",
+    "aws_rds_cluster",
+    "aws_redshift_cluster",
+    "aws_s3_bucket",
+    "aws_secretsmanager_secret",
+    "aws_sqs_queue",
+    "azurerm_cosmosdb_account",
+    "azurerm_key_vault",
+    "azurerm_managed_disk",
+    "azurerm_mssql_database",
+    "azurerm_mysql_flexible_server",
+    "azurerm_postgresql_flexible_server",
+    "azurerm_redis_cache",
+    "azurerm_storage_account",
+    "google_bigquery_dataset",
+    "google_compute_disk",
+    "google_kms_crypto_key",
+    "google_spanner_instance",
+    "google_sql_database_instance",
+    "google_storage_bucket",
+];
+
+const IAM_RESOURCE_PREFIXES: &[&str] = &["aws_iam_", "azurerm_role_", "azuread_"];
+
+/// Resource policies and permissions outside of the IAM resource types
+const IAM_RESOURCE_TYPES: &[&str] = &[
+    "aws_kms_grant",
+    "aws_lambda_permission",
+    "aws_s3_bucket_policy",
+    "aws_sns_topic_policy",
+    "aws_sqs_queue_policy",
+];
+
+const NETWORK_RESOURCE_PREFIXES: &[&str] = &[
+    "aws_alb",
+    "aws_ec2_transit_gateway",
+    "aws_eip",
+    "aws_internet_gateway",
+    "aws_lb",
+    "aws_nat_gateway",
+    "aws_network_acl",
+    "aws_networkfirewall_",
+    "aws_route",
+    "aws_security_group",
+    "aws_subnet",
+    "aws_vpc",
+    "aws_vpn_",
+    "azurerm_application_gateway",
+    "azurerm_firewall",
+    "azurerm_lb",
+    "azurerm_network_",
+    "azurerm_public_ip",
+    "azurerm_route",
+    "azurerm_subnet",
+    "azurerm_virtual_network",
+    "google_compute_firewall",
+    "google_compute_network",
+    "google_compute_route",
+    "google_compute_subnetwork",
+];
+
+fn is_stateful(resource_type: &str) -> bool {
+    STATEFUL_RESOURCE_TYPES.contains(&resource_type)
+}
+
+fn is_iam(resource_type: &str) -> bool {
+    // Google has an IAM resource per resource type, such as `google_storage_bucket_iam_member`
+    IAM_RESOURCE_PREFIXES
+        .iter()
+        .any(|p| resource_type.starts_with(p))
+        || IAM_RESOURCE_TYPES.contains(&resource_type)
+        || (resource_type.starts_with("google_") && resource_type.contains("_iam_"))
+}
+
+fn is_network(resource_type: &str) -> bool {
+    NETWORK_RESOURCE_PREFIXES
+        .iter()
+        .any(|p| resource_type.starts_with(p))
+}
+
+fn get_action_verb(action: &ResourceAction) -> &'static str {
+    match action {
+        ResourceAction::Create => "created",
+        ResourceAction::Update => "updated",
+        ResourceAction::Replace => "replaced",
+        ResourceAction::Delete => "deleted",
+        ResourceAction::NoOp => "unchanged",
+    }
+}
+
+/// Paths of changed attributes that are redacted because they are sensitive, only known for
+/// updates and replaces since created and deleted resources have sensitive values removed
+fn get_sensitive_changes(change: &SanitizedResourceChange) -> Vec<String> {
+    let redacted = Value::String("[REDACTED]".to_string());
+    let mut paths: Vec<String> = change
+        .changes
+        .iter()
+        .flatten()
+        .filter(|(_, c)| c.get("before") == Some(&redacted) || c.get("after") == Some(&redacted))
+        .map(|(path, _)| path.clone())
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn get_risk_level(score: u32) -> RiskLevel {
+    match score {
+        0..10 => RiskLevel::Low,
+        10..30 => RiskLevel::Medium,
+        30..60 => RiskLevel::High,
+        _ => RiskLevel::Critical,
+    }
+}
+
+/// Scores the changes of a plan by the number of changed resources, stateful resources that are
+/// replaced or deleted, IAM and network changes and changes to sensitive attributes
+pub fn assess_plan_risk(changes: &[SanitizedResourceChange]) -> RiskAssessment {
+    let (mut creates, mut updates, mut replaces, mut deletes) = (0, 0, 0, 0);
+    let mut score = 0;
+    let mut findings = vec![];
+
+    for change in changes.iter().filter(|c| c.mode == ResourceMode::Managed) {
+        let address = &change.address;
+        let resource_type = change.resource_type.as_str();
+        let verb = get_action_verb(&change.action);
+        match change.action {
+            ResourceAction::Create => {
+                creates += 1;
+                score += CREATE_SCORE;
+            }
+            ResourceAction::Update => {
+                updates += 1;
+                score += UPDATE_SCORE;
+            }
+            ResourceAction::Replace => {
+                replaces += 1;
+                score += REPLACE_SCORE;
+            }
+            ResourceAction::Delete => {
+                deletes += 1;
+                score += DELETE_SCORE;
+            }
+            ResourceAction::NoOp => continue,
+        }
+
+        if is_stateful(resource_type) {
+            let kind = match change.action {
+                ResourceAction::Replace => Some(RiskFindingKind::StatefulReplace),
+                ResourceAction::Delete => Some(RiskFindingKind::StatefulDelete),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                findings.push(RiskFinding {
+                    kind,
+                    address: address.clone(),
+                    description: format!(
+                        "Stateful resource {} is {}, its data may be lost",
+                        address, verb
+                    ),
+                    score: STATEFUL_SCORE,
+                });
+            }
+        }
+        if is_iam(resource_type) {
+            findings.push(RiskFinding {
+                kind: RiskFindingKind::IamChange,
+                address: address.clone(),
+                description: format!("IAM resource {} is {}", address, verb),
+                score: IAM_SCORE,
+            });
+        }
+        if is_network(resource_type) {
+            findings.push(RiskFinding {
+                kind: RiskFindingKind::NetworkChange,
+                address: address.clone(),
+                description: format!("Network resource {} is {}", address, verb),
+                score: NETWORK_SCORE,
+            });
+        }
+        let sensitive_changes = get_sensitive_changes(change);
+        if !sensitive_changes.is_empty() {
+            findings.push(RiskFinding {
+                kind: RiskFindingKind::SensitiveChange,
+                address: address.clone(),
+                description: format!(
+                    "Sensitive attributes of {} change: {}",
+                    address,
+                    sensitive_changes.join(", ")
+                ),
+                score: SENSITIVE_SCORE,
+            });
+        }
+    }
+
+    let score = (score + findings.iter().map(|f| f.score).sum::<u32>()).min(MAX_SCORE);
+    RiskAssessment {
+        score,
+        level: get_risk_level(score),
+        creates,
+        updates,
+        replaces,
+        deletes,
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use env_defs::sanitize_resource_changes;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn resource_change(address: &str, actions: &[&str], change: Value) -> Value {
+        let (resource_type, name) = address.split_once('.').unwrap();
+        let mut change = change;
+        change["actions"] = json!(actions);
+        json!({
+            "address": address,
+            "mode": "managed",
+            "type": resource_type,
+            "name": name,
+            "change": change
+        })
+    }
+
+    #[test]
+    fn test_assess_plan_risk_no_changes() {
+        let changes = sanitize_resource_changes(&json!([resource_change(
+            "aws_db_instance.db",
+            &["no-op"],
+            json!({"before": {}, "after": {}})
+        )]));
+        let assessment = assess_plan_risk(&changes);
+        assert_eq!(assessment.score, 0);
+        assert_eq!(assessment.level, RiskLevel::Low);
+        assert!(assessment.findings.is_empty());
+    }
+
+    #[test]
+    fn test_assess_plan_risk() {
+        let changes = sanitize_resource_changes(&json!([
+            resource_change(
+                "aws_db_instance.db",
+                &["delete", "create"],
+                json!({
+                    "before": {"engine": "postgres", "password": "old"},
+                    "after": {"engine": "mysql", "password": "new"},
+                    "before_sensitive": {"password": true},
+                    "after_sensitive": {"password": true}
+                })
+            ),
+            resource_change(
+                "aws_iam_role.app",
+                &["create"],
+                json!({"before": null, "after": {"name": "app"}})
+            ),
+            resource_change(
+                "aws_security_group_rule.ingress",
+                &["update"],
+                json!({"before": {"cidr_blocks": ["10.0.0.0/8"]}, "after": {"cidr_blocks": ["0.0.0.0/0"]}})
+            ),
+            resource_change(
+                "aws_s3_bucket.logs",
+                &["create"],
+                json!({"before": null, "after": {"bucket": "logs"}})
+            ),
+            json!({
+                "address": "data.aws_iam_policy_document.doc",
+                "mode": "data",
+                "type": "aws_iam_policy_document",
+                "name": "doc",
+                "change": {"actions": ["read"], "before": null, "after": {}}
+            }),
+        ]));
+        let assessment = assess_plan_risk(&changes);
+
+        assert_eq!(
+            (
+                assessment.creates,
+                assessment.updates,
+                assessment.replaces,
+                assessment.deletes
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(
+            assessment
+                .findings
+                .iter()
+                .map(|f| (f.kind, f.address.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (RiskFindingKind::StatefulReplace, "aws_db_instance.db"),
+                (RiskFindingKind::SensitiveChange, "aws_db_instance.db"),
+                (RiskFindingKind::IamChange, "aws_iam_role.app"),
+                (
+                    RiskFindingKind::NetworkChange,
+                    "aws_security_group_rule.ingress"
+                ),
+            ]
+        );
+        assert_eq!(
+            assessment.findings[1].description,
+            "Sensitive attributes of aws_db_instance.db change: password"
+        );
+        // 5 + 1 + 2 + 1 for the changes, 40 + 10 + 15 + 10 for the findings
+        assert_eq!(assessment.score, 84);
+        assert_eq!(
+            assessment.to_string(),
+            "critical (score 84): 2 to create, 1 to update, 1 to replace, 0 to delete"
+        );
+    }
+
+    #[test]
+    fn test_risk_score_is_capped() {
+        let changes = sanitize_resource_changes(&json!([
+            resource_change(
+                "aws_rds_cluster.a",
+                &["delete"],
+                json!({"before": {}, "after": null})
+            ),
+            resource_change(
+                "aws_s3_bucket.b",
+                &["delete"],
+                json!({"before": {}, "after": null})
+            ),
+            resource_change(
+                "aws_kms_key.c",
+                &["delete"],
+                json!({"before": {}, "after": null})
+            ),
+        ]));
+        let assessment = assess_plan_risk(&changes);
+        assert_eq!(assessment.deletes, 3);
+        assert_eq!(assessment.score, 100);
+        assert_eq!(assessment.level, RiskLevel::Critical);
+    }
+
+    #[test]
+    fn test_classify_resource_types() {
+        assert!(is_iam("google_storage_bucket_iam_member"));
+        assert!(is_iam("aws_lambda_permission"));
+        assert!(!is_iam("aws_lambda_function"));
+        assert!(is_network("aws_vpc_endpoint"));
+        assert!(!is_network("aws_lambda_function"));
+        assert!(is_stateful("azurerm_storage_account"));
+        assert!(!is_stateful("aws_s3_bucket_policy"));
+    }
+}
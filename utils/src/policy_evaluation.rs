@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use regorus::Engine as RegoEngine;
+use serde_json::Value;
+
+/// Plan policies are packages below `infraweave`, such as `package infraweave.terraform_plan`
+const POLICY_PACKAGE_PREFIX: &str = "data.infraweave.";
+
+/// `.rego` files in `dir` as (file name, contents), sorted by file name
+pub fn read_rego_files(dir: &Path) -> Result<Vec<(String, String)>, anyhow::Error> {
+    let mut rego_files = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("rego") {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string();
+            rego_files.push((name, std::fs::read_to_string(&path)?));
+        }
+    }
+    rego_files.sort();
+    Ok(rego_files)
+}
+
+fn to_json(value: regorus::Value) -> Result<Value, anyhow::Error> {
+    match value {
+        regorus::Value::Undefined => Ok(Value::Null),
+        value => Ok(serde_json::to_value(value)?),
+    }
+}
+
+fn load_rego_files(rego_files: &[(String, String)]) -> Result<RegoEngine, anyhow::Error> {
+    let mut engine = RegoEngine::new();
+    // Policies are written for OPA 0.x, where rules do not need `if` and `contains`
+    engine.set_rego_v0(true);
+    for (name, rego) in rego_files {
+        engine
+            .add_policy(name.clone(), rego.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to load policy {}: {}", name, e))?;
+    }
+    Ok(engine)
+}
+
+/// Checks that `rego_files` parse and that at least one of them is a policy package below
+/// `infraweave`. Returns the names of the policy packages below `infraweave`
+pub fn validate_rego_files(rego_files: &[(String, String)]) -> Result<Vec<String>, anyhow::Error> {
+    let engine = load_rego_files(rego_files)?;
+    let mut packages: Vec<String> = engine
+        .get_packages()
+        .map_err(|e| anyhow::anyhow!("Failed to read policy packages: {}", e))?
+        .iter()
+        .filter_map(|p| p.strip_prefix(POLICY_PACKAGE_PREFIX))
+        .map(|p| p.to_string())
+        .collect();
+    packages.sort();
+    packages.dedup();
+    if packages.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No policy package found, the .rego files need a package below infraweave, such as `package infraweave.terraform_plan`"
+        ));
+    }
+    Ok(packages)
+}
+
+/// Violations per package below `infraweave` of the `deny`, `warn` and `info` rules of a policy.
+/// Only violations of `deny` rules fail a job
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolicyViolations {
+    pub deny: BTreeMap<String, Vec<Value>>,
+    pub warn: BTreeMap<String, Vec<Value>>,
+    pub info: BTreeMap<String, Vec<Value>>,
+}
+
+/// Violations of `rule` keyed by the name below `infraweave` of the packages that have any
+fn evaluate_rule(
+    engine: &mut RegoEngine,
+    packages: &[String],
+    rule: &str,
+) -> Result<BTreeMap<String, Vec<Value>>, anyhow::Error> {
+    let mut violations = BTreeMap::new();
+    for package in packages {
+        let name = package.trim_start_matches(POLICY_PACKAGE_PREFIX);
+        let results = engine
+            .eval_query(format!("{}.{}", package, rule), false)
+            .map_err(|e| anyhow::anyhow!("Failed to evaluate policy {}: {}", name, e))?;
+        let value = match results
+            .result
+            .into_iter()
+            .next()
+            .and_then(|r| r.expressions.into_iter().next())
+        {
+            Some(expression) => to_json(expression.value)?,
+            None => Value::Null,
+        };
+        let value = match value {
+            Value::Array(value) => value,
+            Value::Null => vec![],
+            value => {
+                return Err(anyhow::anyhow!(
+                    "{} of policy {} is {}, not a set",
+                    rule,
+                    name,
+                    value
+                ))
+            }
+        };
+        if !value.is_empty() {
+            violations.insert(name.to_string(), value);
+        }
+    }
+    Ok(violations)
+}
+
+/// Evaluates the `deny`, `warn` and `info` rules of every policy package in `rego_files` with the
+/// plan as `input`. Each document in `data` is merged into `data`, so `{"cost_estimate": ...}`
+/// becomes `data.cost_estimate`
+pub fn evaluate_rego_policy(
+    rego_files: &[(String, String)],
+    input: &Value,
+    data: &[Value],
+) -> Result<PolicyViolations, anyhow::Error> {
+    let mut engine = load_rego_files(rego_files)?;
+    for document in data.iter().filter(|d| d.is_object()) {
+        engine
+            .add_data(regorus::Value::from(document.clone()))
+            .map_err(|e| anyhow::anyhow!("Failed to add policy data: {}", e))?;
+    }
+    engine.set_input(regorus::Value::from(input.clone()));
+
+    let mut packages: Vec<String> = engine
+        .get_packages()
+        .map_err(|e| anyhow::anyhow!("Failed to read policy packages: {}", e))?
+        .into_iter()
+        .filter(|p| p.starts_with(POLICY_PACKAGE_PREFIX))
+        .collect();
+    packages.sort();
+    packages.dedup();
+
+    Ok(PolicyViolations {
+        deny: evaluate_rule(&mut engine, &packages, "deny")?,
+        warn: evaluate_rule(&mut engine, &packages, "warn")?,
+        info: evaluate_rule(&mut engine, &packages, "info")?,
+    })
+}
+
+/// Environment variables of the runner available to policies as `data.env`
+const POLICY_ENV_VARS: [&str; 2] = ["AWS_DEFAULT_REGION", "AWS_REGION"];
+
+/// Files the runner writes when it shows the plan, with the `data.cost_estimate` and
+/// `data.risk_assessment` documents
+const POLICY_DATA_FILES: [&str; 2] = ["cost_estimate.json", "risk_assessment.json"];
+
+/// The data documents the runner evaluates every policy with besides the `data` of the policy:
+/// `data.env` from the environment and the documents in the files of `POLICY_DATA_FILES` in `dir`
+pub fn get_policy_data(dir: &Path) -> Result<Vec<Value>, anyhow::Error> {
+    let env: serde_json::Map<String, Value> = POLICY_ENV_VARS
+        .iter()
+        .map(|name| {
+            (
+                name.to_string(),
+                Value::String(std::env::var(name).unwrap_or_default()),
+            )
+        })
+        .collect();
+    let mut data = vec![serde_json::json!({ "env": env })];
+    for file in POLICY_DATA_FILES {
+        let path = dir.join(file);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            data.push(
+                serde_json::from_str(&content)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?,
+            );
+        }
+    }
+    Ok(data)
+}
+
+/// Evaluates the `.rego` files in `dir` with the plan as `input` and the `data` documents, as the
+/// runner does for every policy that applies to a deployment
+pub fn evaluate_policy(
+    dir: &Path,
+    input: &Value,
+    data: &[Value],
+) -> Result<PolicyViolations, anyhow::Error> {
+    let rego_files = read_rego_files(dir)?;
+    evaluate_rego_policy(&rego_files, input, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    const REGION_POLICY: &str = r#"
+package infraweave.terraform_plan
+
+deny[msg] {
+    not input.variables.region.value == data.allowed_region
+    msg := sprintf("Invalid region: '%s'", [input.variables.region.value])
+}
+
+deny[msg] {
+    data.cost_estimate.monthly_delta > 100
+    msg := "Over budget"
+}
+"#;
+
+    const HELPER_POLICY: &str = r#"
+package infraweave.helpers
+
+is_delete(change) {
+    change.actions[_] == "delete"
+}
+"#;
+
+    fn rego_files() -> Vec<(String, String)> {
+        vec![
+            ("region.rego".to_string(), REGION_POLICY.to_string()),
+            ("helpers.rego".to_string(), HELPER_POLICY.to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_evaluate_rego_policy_with_violations() {
+        let violations = evaluate_rego_policy(
+            &rego_files(),
+            &json!({"variables": {"region": {"value": "eu-central-1"}}}),
+            &[
+                json!({"allowed_region": "eu-west-1"}),
+                json!({"cost_estimate": {"monthly_delta": 150.0}}),
+                Value::Null,
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            violations.deny,
+            BTreeMap::from([(
+                "terraform_plan".to_string(),
+                vec![
+                    json!("Invalid region: 'eu-central-1'"),
+                    json!("Over budget")
+                ]
+            )])
+        );
+    }
+
+    #[test]
+    fn test_evaluate_rego_policy_without_violations() {
+        let violations = evaluate_rego_policy(
+            &rego_files(),
+            &json!({"variables": {"region": {"value": "eu-west-1"}}}),
+            &[json!({"allowed_region": "eu-west-1"})],
+        )
+        .unwrap();
+        assert_eq!(violations, PolicyViolations::default());
+    }
+
+    #[test]
+    fn test_evaluate_rego_policy_severities() {
+        let rego_files = vec![(
+            "tags.rego".to_string(),
+            r#"
+package infraweave.tags
+
+deny[msg] {
+    not input.variables.tags.value.owner
+    msg := "Missing tag owner"
+}
+
+warn[msg] {
+    not input.variables.tags.value["cost-center"]
+    msg := "Missing tag cost-center"
+}
+
+info[msg] {
+    msg := sprintf("Owned by %s", [input.variables.tags.value.owner])
+}
+"#
+            .to_string(),
+        )];
+        let violations = evaluate_rego_policy(
+            &rego_files,
+            &json!({"variables": {"tags": {"value": {"owner": "payments"}}}}),
+            &[],
+        )
+        .unwrap();
+        assert!(violations.deny.is_empty());
+        assert_eq!(
+            violations.warn,
+            BTreeMap::from([("tags".to_string(), vec![json!("Missing tag cost-center")])])
+        );
+        assert_eq!(
+            violations.info,
+            BTreeMap::from([("tags".to_string(), vec![json!("Owned by payments")])])
+        );
+    }
+
+    #[test]
+    fn test_evaluate_invalid_rego_policy() {
+        let rego_files = vec![(
+            "broken.rego".to_string(),
+            "package infraweave.x\ndeny[".to_string(),
+        )];
+        let error = evaluate_rego_policy(&rego_files, &json!({}), &[]).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Failed to load policy broken.rego"));
+    }
+
+    #[test]
+    fn test_validate_rego_files() {
+        assert_eq!(
+            validate_rego_files(&rego_files()).unwrap(),
+            vec!["helpers", "terraform_plan"]
+        );
+
+        let rego_files = vec![(
+            "other.rego".to_string(),
+            "package other\ndeny[msg] { msg := \"no\" }".to_string(),
+        )];
+        assert!(validate_rego_files(&rego_files)
+            .unwrap_err()
+            .to_string()
+            .starts_with("No policy package found"));
+        assert!(validate_rego_files(&[]).is_err());
+    }
+
+    #[test]
+    fn test_get_policy_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = get_policy_data(dir.path()).unwrap();
+        assert_eq!(data.len(), 1);
+        assert!(data[0]["env"]["AWS_REGION"].is_string());
+        assert!(data[0]["env"]["AWS_DEFAULT_REGION"].is_string());
+
+        std::fs::write(
+            dir.path().join("cost_estimate.json"),
+            json!({"cost_estimate": {"monthly_delta": 150.0}}).to_string(),
+        )
+        .unwrap();
+        let data = get_policy_data(dir.path()).unwrap();
+        assert_eq!(data[1], json!({"cost_estimate": {"monthly_delta": 150.0}}));
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use env_defs::PolicyManifest;
+use serde_json::Value;
+
+use crate::policy_evaluation::{evaluate_policy, get_policy_data};
+
+/// Directory of a policy with a directory per test case
+pub const POLICY_TESTS_DIR: &str = "tests";
+const PLAN_FILE: &str = "plan.json";
+const DATA_FILE: &str = "data.json";
+const EXPECTED_FILE: &str = "expected.json";
+
+/// A test case in `tests/<name>` of a policy: the plan from `terraform show -json` in `plan.json`,
+/// the violations of `deny` rules it should have per package in `expected.json` and optionally
+/// data documents, such as `{"cost_estimate": ...}`, in `data.json`. The test case is evaluated
+/// with the same data documents as in the runner, those in `data.json` replace the ones of the
+/// runner with the same name, e.g. `{"env": {"AWS_REGION": ...}}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyTestCase {
+    pub name: String,
+    pub plan: Value,
+    pub data: Value,
+    pub expected: BTreeMap<String, Vec<Value>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyTestResult {
+    pub name: String,
+    pub expected: BTreeMap<String, Vec<Value>>,
+    /// The violations of the evaluated policy or the error evaluating it
+    pub actual: Result<BTreeMap<String, Vec<Value>>, String>,
+}
+
+impl PolicyTestResult {
+    pub fn passed(&self) -> bool {
+        match &self.actual {
+            Ok(actual) => get_violation_diff(&self.expected, actual).is_empty(),
+            Err(_) => false,
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value, anyhow::Error> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), anyhow::Error> {
+    std::fs::write(path, serde_json::to_string_pretty(value)? + "\n")
+        .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The `data` the policy in `policy_dir` is published with
+pub fn read_policy_data(policy_dir: &Path) -> Result<Value, anyhow::Error> {
+    let path = policy_dir.join("policy.yaml");
+    let manifest = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let manifest: PolicyManifest = serde_yaml::from_str(&manifest)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(manifest.spec.data)
+}
+
+/// Test cases of the policy in `policy_dir`, sorted by name
+pub fn read_policy_test_cases(policy_dir: &Path) -> Result<Vec<PolicyTestCase>, anyhow::Error> {
+    let tests_dir = policy_dir.join(POLICY_TESTS_DIR);
+    let entries = std::fs::read_dir(&tests_dir)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", tests_dir.display(), e))?;
+
+    let mut test_cases = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let data = match path.join(DATA_FILE).exists() {
+            true => read_json(&path.join(DATA_FILE))?,
+            false => serde_json::json!({}),
+        };
+        let expected =
+            serde_json::from_value(read_json(&path.join(EXPECTED_FILE))?).map_err(|e| {
+                anyhow::anyhow!(
+                    "{} of test case {} is not violations per package: {}",
+                    EXPECTED_FILE,
+                    name,
+                    e
+                )
+            })?;
+        test_cases.push(PolicyTestCase {
+            plan: read_json(&path.join(PLAN_FILE))?,
+            name,
+            data,
+            expected,
+        });
+    }
+    test_cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(test_cases)
+}
+
+/// The data documents of the runner in `case_dir` with those of the test case in place of the ones
+/// with the same name, followed by the `data` of the policy
+fn get_test_case_data(
+    case_dir: &Path,
+    case_data: &Value,
+    policy_data: &Value,
+) -> Result<Vec<Value>, anyhow::Error> {
+    let mut data = get_policy_data(case_dir)?;
+    if let Some(case_data) = case_data.as_object() {
+        data.retain(|document| {
+            document
+                .as_object()
+                .is_none_or(|document| !document.keys().any(|key| case_data.contains_key(key)))
+        });
+    }
+    data.push(case_data.clone());
+    data.push(policy_data.clone());
+    Ok(data)
+}
+
+/// Evaluates the policy in `policy_dir` against each of its test cases
+pub fn run_policy_tests(policy_dir: &Path) -> Result<Vec<PolicyTestResult>, anyhow::Error> {
+    let policy_data = read_policy_data(policy_dir)?;
+    let test_cases = read_policy_test_cases(policy_dir)?;
+    if test_cases.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No test cases found in {}",
+            policy_dir.join(POLICY_TESTS_DIR).display()
+        ));
+    }
+
+    Ok(test_cases
+        .into_iter()
+        .map(|test_case| PolicyTestResult {
+            actual: get_test_case_data(
+                &policy_dir.join(POLICY_TESTS_DIR).join(&test_case.name),
+                &test_case.data,
+                &policy_data,
+            )
+            .and_then(|data| evaluate_policy(policy_dir, &test_case.plan, &data))
+            .map(|violations| violations.deny)
+            .map_err(|e| e.to_string()),
+            name: test_case.name,
+            expected: test_case.expected,
+        })
+        .collect())
+}
+
+/// Writes a test case `name` for the policy in `policy_dir` with the plan and data documents. The
+/// current violations of the policy are written as expected, to be reviewed before committing
+pub fn write_policy_test_case(
+    policy_dir: &Path,
+    name: &str,
+    plan: &Value,
+    data: &Value,
+) -> Result<BTreeMap<String, Vec<Value>>, anyhow::Error> {
+    let policy_data = read_policy_data(policy_dir)?;
+    let case_dir = policy_dir.join(POLICY_TESTS_DIR).join(name);
+    let violations = evaluate_policy(
+        policy_dir,
+        plan,
+        &get_test_case_data(&case_dir, data, &policy_data)?,
+    )?
+    .deny;
+
+    if case_dir.exists() {
+        return Err(anyhow::anyhow!(
+            "Test case {} already exists in {}",
+            name,
+            case_dir.display()
+        ));
+    }
+    std::fs::create_dir_all(&case_dir)?;
+    write_json(&case_dir.join(PLAN_FILE), plan)?;
+    if data.as_object().is_some_and(|d| !d.is_empty()) {
+        write_json(&case_dir.join(DATA_FILE), data)?;
+    }
+    write_json(
+        &case_dir.join(EXPECTED_FILE),
+        &serde_json::to_value(&violations)?,
+    )?;
+    Ok(violations)
+}
+
+fn get_violation_text(violation: &Value) -> String {
+    match violation.as_str() {
+        Some(text) => text.to_string(),
+        None => violation.to_string(),
+    }
+}
+
+/// Violations missing from `actual` prefixed with `-` and unexpected violations in `actual`
+/// prefixed with `+`, as `<package>: <violation>`. The order of the violations does not matter
+pub fn get_violation_diff(
+    expected: &BTreeMap<String, Vec<Value>>,
+    actual: &BTreeMap<String, Vec<Value>>,
+) -> Vec<String> {
+    let get_violations = |violations: &BTreeMap<String, Vec<Value>>| -> BTreeSet<(String, String)> {
+        violations
+            .iter()
+            .flat_map(|(package, v)| v.iter().map(|v| (package.clone(), get_violation_text(v))))
+            .collect()
+    };
+    let (expected, actual) = (get_violations(expected), get_violations(actual));
+
+    let mut diff: Vec<(&String, &String, char)> = expected
+        .difference(&actual)
+        .map(|(p, v)| (p, v, '-'))
+        .chain(actual.difference(&expected).map(|(p, v)| (p, v, '+')))
+        .collect();
+    diff.sort();
+    diff.into_iter()
+        .map(|(package, violation, sign)| format!("{} {}: {}", sign, package, violation))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    const POLICY_YAML: &str = r#"
+apiVersion: infraweave.io/v1
+kind: Policy
+metadata:
+  name: allowed-regions
+spec:
+  policyName: Allowed regions
+  version: 0.1.0
+  description: Only eu-west-1 is allowed
+  reference: ""
+  data:
+    allowed_region: eu-west-1
+"#;
+
+    const REGION_POLICY: &str = r#"
+package infraweave.regions
+
+deny[msg] {
+    not input.variables.region.value == data.allowed_region
+    msg := sprintf("Invalid region: '%s'", [input.variables.region.value])
+}
+
+deny[msg] {
+    data.cost_estimate.monthly_delta > 100
+    msg := "Over budget"
+}
+"#;
+
+    fn policy_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("policy.yaml"), POLICY_YAML).unwrap();
+        std::fs::write(dir.path().join("regions.rego"), REGION_POLICY).unwrap();
+        dir
+    }
+
+    fn region_plan(region: &str) -> Value {
+        json!({"variables": {"region": {"value": region}}})
+    }
+
+    #[test]
+    fn test_run_policy_tests() {
+        let dir = policy_dir();
+        write_policy_test_case(dir.path(), "allowed", &region_plan("eu-west-1"), &json!({}))
+            .unwrap();
+        let violations = write_policy_test_case(
+            dir.path(),
+            "denied",
+            &region_plan("us-east-1"),
+            &json!({"cost_estimate": {"monthly_delta": 150.0}}),
+        )
+        .unwrap();
+        assert_eq!(
+            violations,
+            BTreeMap::from([(
+                "regions".to_string(),
+                vec![json!("Invalid region: 'us-east-1'"), json!("Over budget")]
+            )])
+        );
+        assert!(!dir.path().join("tests/allowed/data.json").exists());
+        assert!(
+            write_policy_test_case(dir.path(), "allowed", &region_plan("x"), &json!({})).is_err()
+        );
+
+        let results = run_policy_tests(dir.path()).unwrap();
+        assert_eq!(
+            results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+            vec!["allowed", "denied"]
+        );
+        assert!(results.iter().all(|r| r.passed()));
+
+        // The policy is changed to allow us-east-1 instead
+        std::fs::write(
+            dir.path().join("policy.yaml"),
+            POLICY_YAML.replace("eu-west-1", "us-east-1"),
+        )
+        .unwrap();
+        let results = run_policy_tests(dir.path()).unwrap();
+        assert!(!results[0].passed());
+        assert_eq!(
+            get_violation_diff(&results[0].expected, results[0].actual.as_ref().unwrap()),
+            vec!["+ regions: Invalid region: 'eu-west-1'"]
+        );
+        assert_eq!(
+            get_violation_diff(&results[1].expected, results[1].actual.as_ref().unwrap()),
+            vec!["- regions: Invalid region: 'us-east-1'"]
+        );
+    }
+
+    #[test]
+    fn test_run_policy_tests_with_runner_data() {
+        let dir = policy_dir();
+        std::fs::write(
+            dir.path().join("env.rego"),
+            r#"
+package infraweave.env
+
+deny[msg] {
+    data.env.AWS_REGION != "eu-west-1"
+    msg := "Runner not in eu-west-1"
+}
+"#,
+        )
+        .unwrap();
+        write_policy_test_case(
+            dir.path(),
+            "runner-region",
+            &region_plan("eu-west-1"),
+            &json!({"env": {"AWS_REGION": "eu-west-1"}}),
+        )
+        .unwrap();
+        let case_dir = dir.path().join(POLICY_TESTS_DIR).join("runner-region");
+        std::fs::write(
+            case_dir.join("cost_estimate.json"),
+            json!({"cost_estimate": {"monthly_delta": 150.0}}).to_string(),
+        )
+        .unwrap();
+
+        let results = run_policy_tests(dir.path()).unwrap();
+        assert_eq!(
+            get_violation_diff(&results[0].expected, results[0].actual.as_ref().unwrap()),
+            vec!["+ regions: Over budget"]
+        );
+    }
+
+    #[test]
+    fn test_run_policy_tests_without_test_cases() {
+        let dir = policy_dir();
+        assert!(run_policy_tests(dir.path()).is_err());
+        std::fs::create_dir(dir.path().join(POLICY_TESTS_DIR)).unwrap();
+        assert!(run_policy_tests(dir.path())
+            .unwrap_err()
+            .to_string()
+            .starts_with("No test cases found"));
+    }
+
+    #[test]
+    fn test_get_violation_diff_ignores_order() {
+        let expected = BTreeMap::from([("regions".to_string(), vec![json!("b"), json!("a")])]);
+        let actual = BTreeMap::from([("regions".to_string(), vec![json!("a"), json!("b")])]);
+        assert!(get_violation_diff(&expected, &actual).is_empty());
+        assert_eq!(
+            get_violation_diff(&expected, &BTreeMap::new()),
+            vec!["- regions: a", "- regions: b"]
+        );
+    }
+}
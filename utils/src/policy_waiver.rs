@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::time::UNIX_EPOCH;
+
+use env_defs::PolicyWaiver;
+use humantime::parse_rfc3339_weak;
+use serde_json::Value;
+
+fn get_expires_at_epoch(waiver: &PolicyWaiver) -> Result<u128, anyhow::Error> {
+    Ok(parse_rfc3339_weak(&waiver.expires_at)
+        .map_err(|e| anyhow::anyhow!("Invalid expiresAt \"{}\": {}", waiver.expires_at, e))?
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| anyhow::anyhow!("Invalid expiresAt \"{}\"", waiver.expires_at))?
+        .as_millis())
+}
+
+/// Checks that every waiver names its rule, finding and deployment, has a justification, is signed
+/// off by someone else than `published_by` and has not expired. `approvedBy` is not verified
+/// further, it records who signed off but does not enforce it
+pub fn validate_policy_waivers(
+    waivers: &[PolicyWaiver],
+    published_by: &str,
+    now: u128,
+) -> Result<(), anyhow::Error> {
+    for waiver in waivers {
+        if waiver.rule.trim().is_empty()
+            || waiver.finding.trim().is_empty()
+            || waiver.deployment_id.trim().is_empty()
+        {
+            return Err(anyhow::anyhow!(
+                "Every waiver needs the rule, the finding and the deploymentId it applies to"
+            ));
+        }
+        let name = format!(
+            "Waiver of {} \"{}\" for {}",
+            waiver.rule, waiver.finding, waiver.deployment_id
+        );
+        if waiver.justification.trim().is_empty() {
+            return Err(anyhow::anyhow!("{} has no justification", name));
+        }
+        let approved_by = waiver.approved_by.trim();
+        if approved_by.is_empty() {
+            return Err(anyhow::anyhow!("{} is not signed off in approvedBy", name));
+        }
+        if approved_by.eq_ignore_ascii_case(published_by.trim()) {
+            return Err(anyhow::anyhow!(
+                "{} must be signed off by someone else than {} who publishes it",
+                name,
+                published_by
+            ));
+        }
+        let expires_at_epoch =
+            get_expires_at_epoch(waiver).map_err(|e| anyhow::anyhow!("{}: {}", name, e))?;
+        if expires_at_epoch <= now {
+            return Err(anyhow::anyhow!("{} expired at {}", name, waiver.expires_at));
+        }
+    }
+    Ok(())
+}
+
+/// Violations of the `deny` rules of a policy split into the ones that fail the job and the ones
+/// allowed by a waiver, with the waivers that allowed them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WaivedViolations {
+    pub deny: BTreeMap<String, Vec<Value>>,
+    pub waived: BTreeMap<String, Vec<Value>>,
+    pub waivers: Vec<PolicyWaiver>,
+}
+
+/// Waives the violations that have a waiver of their package and finding for `deployment_id` that
+/// has not expired. Waivers with an invalid `expiresAt` are ignored
+pub fn apply_policy_waivers(
+    deny: BTreeMap<String, Vec<Value>>,
+    waivers: &[PolicyWaiver],
+    deployment_id: &str,
+    now: u128,
+) -> WaivedViolations {
+    let active_waivers: Vec<&PolicyWaiver> = waivers
+        .iter()
+        .filter(|w| w.deployment_id == deployment_id)
+        .filter(|w| get_expires_at_epoch(w).is_ok_and(|expires_at| expires_at > now))
+        .collect();
+
+    let mut result = WaivedViolations::default();
+    for (package, violations) in deny {
+        for violation in violations {
+            match active_waivers
+                .iter()
+                .find(|w| w.applies_to(&package, &violation))
+            {
+                Some(waiver) => {
+                    if !result.waivers.contains(waiver) {
+                        result.waivers.push((*waiver).clone());
+                    }
+                    result
+                        .waived
+                        .entry(package.clone())
+                        .or_default()
+                        .push(violation);
+                }
+                None => {
+                    result
+                        .deny
+                        .entry(package.clone())
+                        .or_default()
+                        .push(violation);
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    // 2026-01-01T00:00:00Z
+    const NOW: u128 = 1_767_225_600_000;
+
+    fn waiver(rule: &str, deployment_id: &str, expires_at: &str) -> PolicyWaiver {
+        PolicyWaiver {
+            rule: rule.to_string(),
+            finding: match rule {
+                "regions" => "Invalid region",
+                "budget" => "Over budget",
+                _ => "Missing tag owner",
+            }
+            .to_string(),
+            deployment_id: deployment_id.to_string(),
+            expires_at: expires_at.to_string(),
+            justification: "Migrating to a new bucket, CHG-1234".to_string(),
+            approved_by: "security@example.com".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_policy_waivers() {
+        assert!(validate_policy_waivers(
+            &[waiver("regions", "s3bucket/logs", "2026-03-31T00:00:00Z")],
+            "developer@example.com",
+            NOW
+        )
+        .is_ok());
+        assert_eq!(
+            validate_policy_waivers(
+                &[waiver("regions", "s3bucket/logs", "2025-12-31T00:00:00Z")],
+                "developer@example.com",
+                NOW
+            )
+            .unwrap_err()
+            .to_string(),
+            "Waiver of regions \"Invalid region\" for s3bucket/logs expired at 2025-12-31T00:00:00Z"
+        );
+        assert!(validate_policy_waivers(
+            &[waiver("regions", "s3bucket/logs", "next month")],
+            "developer@example.com",
+            NOW
+        )
+        .is_err());
+        assert_eq!(
+            validate_policy_waivers(
+                &[PolicyWaiver {
+                    approved_by: " ".to_string(),
+                    ..waiver("regions", "s3bucket/logs", "2026-03-31T00:00:00Z")
+                }],
+                "developer@example.com",
+                NOW
+            )
+            .unwrap_err()
+            .to_string(),
+            "Waiver of regions \"Invalid region\" for s3bucket/logs is not signed off in approvedBy"
+        );
+        assert_eq!(
+            validate_policy_waivers(
+                &[waiver("regions", "s3bucket/logs", "2026-03-31T00:00:00Z")],
+                "Security@example.com",
+                NOW
+            )
+            .unwrap_err()
+            .to_string(),
+            "Waiver of regions \"Invalid region\" for s3bucket/logs must be signed off by someone else than Security@example.com who publishes it"
+        );
+        assert!(validate_policy_waivers(
+            &[PolicyWaiver {
+                finding: "".to_string(),
+                ..waiver("regions", "s3bucket/logs", "2026-03-31T00:00:00Z")
+            }],
+            "developer@example.com",
+            NOW
+        )
+        .is_err());
+        assert!(validate_policy_waivers(
+            &[waiver("", "s3bucket/logs", "2026-03-31T00:00:00Z")],
+            "developer@example.com",
+            NOW
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_apply_policy_waivers() {
+        let deny = BTreeMap::from([
+            ("regions".to_string(), vec![json!("Invalid region")]),
+            ("budget".to_string(), vec![json!("Over budget")]),
+            ("tags".to_string(), vec![json!("Missing tag owner")]),
+        ]);
+        let waivers = vec![
+            waiver("regions", "s3bucket/logs", "2026-03-31T00:00:00Z"),
+            waiver("budget", "s3bucket/logs", "2025-12-31T00:00:00Z"),
+            waiver("tags", "s3bucket/other", "2026-03-31T00:00:00Z"),
+        ];
+
+        let result = apply_policy_waivers(deny, &waivers, "s3bucket/logs", NOW);
+        assert_eq!(
+            result.deny,
+            BTreeMap::from([
+                ("budget".to_string(), vec![json!("Over budget")]),
+                ("tags".to_string(), vec![json!("Missing tag owner")]),
+            ])
+        );
+        assert_eq!(
+            result.waived,
+            BTreeMap::from([("regions".to_string(), vec![json!("Invalid region")])])
+        );
+        assert_eq!(result.waivers, vec![waivers[0].clone()]);
+    }
+
+    #[test]
+    fn test_apply_policy_waivers_to_one_finding() {
+        let deny = BTreeMap::from([(
+            "regions".to_string(),
+            vec![
+                json!("Invalid region"),
+                json!({"id": "replication", "msg": "Replication to us-east-1"}),
+                json!({"id": "encryption", "msg": "Bucket is not encrypted"}),
+            ],
+        )]);
+        let waivers = vec![
+            waiver("regions", "s3bucket/logs", "2026-03-31T00:00:00Z"),
+            PolicyWaiver {
+                finding: "replication".to_string(),
+                ..waiver("regions", "s3bucket/logs", "2026-03-31T00:00:00Z")
+            },
+        ];
+
+        let result = apply_policy_waivers(deny, &waivers, "s3bucket/logs", NOW);
+        assert_eq!(
+            result.deny,
+            BTreeMap::from([(
+                "regions".to_string(),
+                vec![json!({"id": "encryption", "msg": "Bucket is not encrypted"})]
+            )])
+        );
+        assert_eq!(
+            result.waived,
+            BTreeMap::from([(
+                "regions".to_string(),
+                vec![
+                    json!("Invalid region"),
+                    json!({"id": "replication", "msg": "Replication to us-east-1"}),
+                ]
+            )])
+        );
+        assert_eq!(result.waivers, waivers);
+    }
+}
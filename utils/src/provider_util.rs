@@ -196,6 +196,13 @@ pub async fn _get_events(
     }
 }
 
+const NO_CHANGE_RECORD_FOUND: &str = "No change record found";
+
+/// Whether the error of getting a change record means that the change record does not exist
+pub fn is_change_record_not_found(error: &anyhow::Error) -> bool {
+    error.to_string() == NO_CHANGE_RECORD_FOUND
+}
+
 pub async fn _get_change_records(
     provider: &dyn CloudProvider,
     query: Value,
@@ -208,7 +215,7 @@ pub async fn _get_change_records(
                         .expect("Failed to parse change record");
                 Ok(change_record)
             } else if change_records.is_empty() {
-                Err(anyhow::anyhow!("No change record found"))
+                Err(anyhow::anyhow!(NO_CHANGE_RECORD_FOUND))
             } else {
                 panic!("Expected exactly one change record");
             }
@@ -107,12 +107,43 @@ properties:
         type: string
       data:
         type: object
+      modules:
+        type: array
+        items:
+          type: string
+      labels:
+        type: object
+        additionalProperties:
+          type: string
+      waivers:
+        type: array
+        items:
+          type: object
+          properties:
+            rule:
+              type: string
+            finding:
+              type: string
+            deploymentId:
+              type: string
+            expiresAt:
+              type: string
+            justification:
+              type: string
+            approvedBy:
+              type: string
+          required:
+            - rule
+            - finding
+            - deploymentId
+            - expiresAt
+            - justification
+            - approvedBy
     required:
       - policyName
       - version
       - description
       - reference
-      - data
 required:
   - apiVersion
   - kind
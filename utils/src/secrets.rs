@@ -0,0 +1,192 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+/// Matches `{{ secret::name }}`, the name is passed as-is to the secret backend of the provider
+const SECRET_REFERENCE_PATTERN: &str = r"\{\{\s*secret::([A-Za-z0-9_./-]+)\s*\}\}";
+
+fn secret_reference_regex() -> regex::Regex {
+    regex::Regex::new(SECRET_REFERENCE_PATTERN).unwrap()
+}
+
+pub fn get_secret_reference(name: &str) -> String {
+    format!("{{{{ secret::{} }}}}", name)
+}
+
+pub fn is_secret_reference(value: &str) -> bool {
+    secret_reference_regex().is_match(value)
+}
+
+/// Names of all secrets referenced in strings anywhere in `value`
+pub fn get_secret_references(value: &Value) -> BTreeSet<String> {
+    let re = secret_reference_regex();
+    let mut names = BTreeSet::new();
+    collect_secret_references(&re, value, &mut names);
+    names
+}
+
+fn collect_secret_references(re: &regex::Regex, value: &Value, names: &mut BTreeSet<String>) {
+    match value {
+        Value::String(s) => {
+            for caps in re.captures_iter(s) {
+                names.insert(caps[1].to_string());
+            }
+        }
+        Value::Array(values) => values
+            .iter()
+            .for_each(|v| collect_secret_references(re, v, names)),
+        Value::Object(map) => map
+            .values()
+            .for_each(|v| collect_secret_references(re, v, names)),
+        _ => {}
+    }
+}
+
+/// Replaces every secret reference in `value` with the value of the secret, references inside a
+/// longer string are substituted in place
+pub fn resolve_secret_references(
+    value: &Value,
+    secrets: &BTreeMap<String, String>,
+) -> Result<Value, anyhow::Error> {
+    let re = secret_reference_regex();
+    resolve_value(&re, value, secrets)
+}
+
+fn resolve_value(
+    re: &regex::Regex,
+    value: &Value,
+    secrets: &BTreeMap<String, String>,
+) -> Result<Value, anyhow::Error> {
+    match value {
+        Value::String(s) => {
+            let mut missing = None;
+            let resolved =
+                re.replace_all(s, |caps: &regex::Captures| match secrets.get(&caps[1]) {
+                    Some(secret) => secret.clone(),
+                    None => {
+                        missing = Some(caps[1].to_string());
+                        String::new()
+                    }
+                });
+            match missing {
+                Some(name) => Err(anyhow::anyhow!("Secret {} is not resolved", name)),
+                None => Ok(Value::String(resolved.into_owned())),
+            }
+        }
+        Value::Array(values) => Ok(Value::Array(
+            values
+                .iter()
+                .map(|v| resolve_value(re, v, secrets))
+                .collect::<Result<Vec<Value>, anyhow::Error>>()?,
+        )),
+        Value::Object(map) => {
+            let mut resolved = serde_json::Map::new();
+            for (key, v) in map {
+                resolved.insert(key.clone(), resolve_value(re, v, secrets)?);
+            }
+            Ok(Value::Object(resolved))
+        }
+        _ => Ok(value.clone()),
+    }
+}
+
+/// Replaces the values of resolved secrets in `text` with their references
+pub fn redact_secret_values(text: &str, secrets: &BTreeMap<String, String>) -> String {
+    let mut redacted = text.to_string();
+    // Longest first so a secret containing another secret is not partially redacted
+    let mut secrets: Vec<(&String, &String)> = secrets.iter().collect();
+    secrets.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+    for (name, value) in secrets {
+        if !value.is_empty() {
+            redacted = redacted.replace(value.as_str(), &get_secret_reference(name));
+        }
+    }
+    redacted
+}
+
+/// Replaces the values of resolved secrets in all strings of `value` with their references
+pub fn redact_secret_values_in_json(value: &Value, secrets: &BTreeMap<String, String>) -> Value {
+    match value {
+        Value::String(s) => Value::String(redact_secret_values(s, secrets)),
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|v| redact_secret_values_in_json(v, secrets))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, v)| (key.clone(), redact_secret_values_in_json(v, secrets)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn secrets() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("db-password".to_string(), "hunter2".to_string()),
+            ("/team/api_key".to_string(), "abc123".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_get_secret_references() {
+        let variables = json!({
+            "password": "{{ secret::db-password }}",
+            "url": "https://{{secret::/team/api_key}}@example.com",
+            "tags": ["{{ secret::db-password }}", "{{ S3Bucket::bucket::arn }}"],
+            "count": 3,
+        });
+        assert_eq!(
+            get_secret_references(&variables),
+            BTreeSet::from(["/team/api_key".to_string(), "db-password".to_string()])
+        );
+        assert_eq!(is_secret_reference("{{ S3Bucket::bucket::arn }}"), false);
+    }
+
+    #[test]
+    fn test_resolve_secret_references() {
+        let variables = json!({
+            "password": "{{ secret::db-password }}",
+            "url": "https://{{secret::/team/api_key}}@example.com",
+            "nested": {"list": ["{{ secret::db-password }}", true]},
+        });
+        assert_eq!(
+            resolve_secret_references(&variables, &secrets()).unwrap(),
+            json!({
+                "password": "hunter2",
+                "url": "https://abc123@example.com",
+                "nested": {"list": ["hunter2", true]},
+            })
+        );
+
+        let missing = json!({"token": "{{ secret::token }}"});
+        assert!(resolve_secret_references(&missing, &secrets()).is_err());
+    }
+
+    #[test]
+    fn test_redact_secret_values() {
+        let plan = json!({
+            "variables": {"password": {"value": "hunter2"}},
+            "resource_changes": [{"after": {"connection": "hunter2@db"}}],
+        });
+        assert_eq!(
+            redact_secret_values_in_json(&plan, &secrets()),
+            json!({
+                "variables": {"password": {"value": "{{ secret::db-password }}"}},
+                "resource_changes": [{"after": {"connection": "{{ secret::db-password }}@db"}}],
+            })
+        );
+        assert_eq!(
+            redact_secret_values("password = \"hunter2\"", &secrets()),
+            "password = \"{{ secret::db-password }}\""
+        );
+    }
+}
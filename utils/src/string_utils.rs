@@ -8,6 +8,26 @@ pub fn to_camel_case(s: &str) -> String {
     s.to_lower_camel_case()
 }
 
+/// Whether `value` matches `pattern`, where `*` matches any characters
+pub fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !value.starts_with(first) || value.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut rest = &value[first.len()..value.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    value.ends_with(last)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,4 +176,15 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("k8s-prod/*", "k8s-prod/team-a"));
+        assert!(matches_pattern("*/team-a", "k8s-prod/team-a"));
+        assert!(matches_pattern("k8s-*/team-*", "k8s-prod/team-a"));
+        assert!(matches_pattern("*", "anything"));
+        assert!(!matches_pattern("k8s-prod/*", "k8s-dev/team-a"));
+        assert!(!matches_pattern("k8s-prod", "k8s-prod/team-a"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+    }
 }
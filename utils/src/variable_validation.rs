@@ -0,0 +1,475 @@
+use std::collections::BTreeSet;
+
+use env_defs::{ModuleResp, TfValidation, TfVariable};
+use hcl::eval::{Context, ErrorKind, Evaluate, FuncArgs, FuncDef, ParamType};
+use hcl::{Expression, Map, Operation, Template, Value};
+use log::debug;
+
+/// Evaluates the `validation` blocks of the module variables against the claim variables.
+///
+/// Only a subset of HCL is supported (operators, `length`, `regex`, `contains`, `can` and a few
+/// string functions). Conditions that use anything else, or variables whose value is only known
+/// at apply time, are skipped here and left to Terraform.
+pub fn verify_variable_validations(
+    module: &ModuleResp,
+    variables: &serde_json::Value,
+) -> Result<(), anyhow::Error> {
+    validate_variables(&module.tf_variables, variables)
+}
+
+fn validate_variables(
+    tf_variables: &[TfVariable],
+    variables: &serde_json::Value,
+) -> Result<(), anyhow::Error> {
+    let variables_map = variables.as_object().cloned().unwrap_or_default();
+
+    let mut values = Map::new();
+    for variable in tf_variables {
+        let value = match variables_map
+            .get(&variable.name)
+            .or(variable.default.as_ref())
+        {
+            Some(value) => value,
+            None => continue,
+        };
+        if contains_reference(value) {
+            continue;
+        }
+        if let Ok(value) = hcl::to_value(value) {
+            values.insert(variable.name.clone(), value);
+        }
+    }
+    let known: BTreeSet<String> = values.keys().cloned().collect();
+    let ctx = validation_context(values);
+
+    let mut errors = vec![];
+    for variable in tf_variables {
+        for validation in variable.validations.iter() {
+            match evaluate_validation(validation, &known, &ctx) {
+                Ok(true) => {}
+                Ok(false) => errors.push(format!(
+                    "Invalid value for variable \"{}\": {}",
+                    variable.name,
+                    evaluate_message(&validation.message, &ctx)
+                )),
+                Err(e) => debug!(
+                    "Skipping validation \"{}\" of variable {}: {}",
+                    validation.expression, variable.name, e
+                ),
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(anyhow::anyhow!("{}", errors.join("\n")));
+    }
+    Ok(())
+}
+
+/// `{{ Kind::claim::output }}` and secret references are resolved after the claim is accepted
+fn contains_reference(value: &serde_json::Value) -> bool {
+    let re = regex::Regex::new(r"\{\{\s*(\w+)::(\w+)::(\w+)\s*\}\}").unwrap();
+    match value {
+        serde_json::Value::String(s) => re.is_match(s) || crate::is_secret_reference(s),
+        serde_json::Value::Array(values) => values.iter().any(contains_reference),
+        serde_json::Value::Object(map) => map.values().any(contains_reference),
+        _ => false,
+    }
+}
+
+/// Returns whether the condition holds, or an error if it cannot be evaluated locally
+fn evaluate_validation(
+    validation: &TfValidation,
+    known: &BTreeSet<String>,
+    ctx: &Context,
+) -> Result<bool, String> {
+    let re = regex::Regex::new(r"\bvar\.([A-Za-z_][A-Za-z0-9_-]*)").unwrap();
+    for caps in re.captures_iter(&validation.expression) {
+        if !known.contains(&caps[1]) {
+            return Err(format!("variable {} is not known", &caps[1]));
+        }
+    }
+
+    let mut expression = parse_expression(&validation.expression)?;
+    resolve_can(&mut expression, ctx)?;
+    match expression.evaluate(ctx).map_err(|e| e.to_string())? {
+        Value::Bool(result) => Ok(result),
+        other => Err(format!(
+            "condition evaluated to {} instead of a bool",
+            other
+        )),
+    }
+}
+
+fn parse_expression(expression: &str) -> Result<Expression, String> {
+    let body = hcl::parse(&format!("condition = {}", expression)).map_err(|e| e.to_string())?;
+    body.attributes()
+        .next()
+        .map(|attribute| attribute.expr.clone())
+        .ok_or_else(|| "empty condition".to_string())
+}
+
+fn evaluate_message(message: &str, ctx: &Context) -> String {
+    message
+        .parse::<Template>()
+        .ok()
+        .and_then(|template| template.evaluate(ctx).ok())
+        .unwrap_or_else(|| message.to_string())
+}
+
+/// Replaces `can(...)` calls with their result since errors cannot be caught during evaluation.
+/// Undefined functions are not an error of the value, so the condition is not evaluated at all.
+fn resolve_can(expression: &mut Expression, ctx: &Context) -> Result<(), String> {
+    let result = match expression {
+        Expression::FuncCall(call) => {
+            for arg in call.args.iter_mut() {
+                resolve_can(arg, ctx)?;
+            }
+            if call.name.namespace.is_empty()
+                && call.name.name.as_str() == "can"
+                && call.args.len() == 1
+            {
+                match call.args[0].evaluate(ctx) {
+                    Ok(_) => Some(true),
+                    Err(e) => match e.kind() {
+                        ErrorKind::UndefinedFunc(_) | ErrorKind::UndefinedVar(_) => {
+                            return Err(e.to_string());
+                        }
+                        _ => Some(false),
+                    },
+                }
+            } else {
+                None
+            }
+        }
+        Expression::Parenthesis(inner) => {
+            resolve_can(inner, ctx)?;
+            None
+        }
+        Expression::Conditional(conditional) => {
+            resolve_can(&mut conditional.cond_expr, ctx)?;
+            resolve_can(&mut conditional.true_expr, ctx)?;
+            resolve_can(&mut conditional.false_expr, ctx)?;
+            None
+        }
+        Expression::Operation(operation) => {
+            match operation.as_mut() {
+                Operation::Unary(unary) => resolve_can(&mut unary.expr, ctx)?,
+                Operation::Binary(binary) => {
+                    resolve_can(&mut binary.lhs_expr, ctx)?;
+                    resolve_can(&mut binary.rhs_expr, ctx)?;
+                }
+            }
+            None
+        }
+        Expression::Array(values) => {
+            for value in values.iter_mut() {
+                resolve_can(value, ctx)?;
+            }
+            None
+        }
+        Expression::Object(object) => {
+            for value in object.values_mut() {
+                resolve_can(value, ctx)?;
+            }
+            None
+        }
+        Expression::Traversal(traversal) => {
+            resolve_can(&mut traversal.expr, ctx)?;
+            None
+        }
+        _ => None,
+    };
+    if let Some(result) = result {
+        *expression = Expression::Bool(result);
+    }
+    Ok(())
+}
+
+fn validation_context(values: Map<String, Value>) -> Context<'static> {
+    let mut ctx = Context::new();
+    ctx.declare_var("var", Value::Object(values));
+    ctx.declare_func(
+        "length",
+        FuncDef::builder().param(ParamType::Any).build(length),
+    );
+    ctx.declare_func(
+        "regex",
+        FuncDef::builder()
+            .param(ParamType::String)
+            .param(ParamType::String)
+            .build(regex),
+    );
+    ctx.declare_func(
+        "regexall",
+        FuncDef::builder()
+            .param(ParamType::String)
+            .param(ParamType::String)
+            .build(regexall),
+    );
+    ctx.declare_func(
+        "contains",
+        FuncDef::builder()
+            .param(ParamType::array_of(ParamType::Any))
+            .param(ParamType::Any)
+            .build(contains),
+    );
+    ctx.declare_func(
+        "startswith",
+        FuncDef::builder()
+            .param(ParamType::String)
+            .param(ParamType::String)
+            .build(startswith),
+    );
+    ctx.declare_func(
+        "endswith",
+        FuncDef::builder()
+            .param(ParamType::String)
+            .param(ParamType::String)
+            .build(endswith),
+    );
+    ctx.declare_func(
+        "lower",
+        FuncDef::builder().param(ParamType::String).build(lower),
+    );
+    ctx.declare_func(
+        "upper",
+        FuncDef::builder().param(ParamType::String).build(upper),
+    );
+    ctx.declare_func(
+        "alltrue",
+        FuncDef::builder()
+            .param(ParamType::array_of(ParamType::Bool))
+            .build(alltrue),
+    );
+    ctx.declare_func(
+        "anytrue",
+        FuncDef::builder()
+            .param(ParamType::array_of(ParamType::Bool))
+            .build(anytrue),
+    );
+    ctx
+}
+
+fn length(args: FuncArgs) -> Result<Value, String> {
+    match &args[0] {
+        Value::String(s) => Ok(Value::from(s.chars().count() as u64)),
+        Value::Array(values) => Ok(Value::from(values.len() as u64)),
+        Value::Object(map) => Ok(Value::from(map.len() as u64)),
+        other => Err(format!("length of {} is not defined", other)),
+    }
+}
+
+fn compile_regex(pattern: &str) -> Result<regex::Regex, String> {
+    regex::Regex::new(pattern).map_err(|e| format!("invalid regular expression: {}", e))
+}
+
+/// Same result shapes as Terraform: the match, a list of the groups or an object of the named
+/// groups
+fn regex_match(re: &regex::Regex, caps: &regex::Captures) -> Value {
+    let group = |m: Option<regex::Match>| m.map(|m| Value::from(m.as_str())).unwrap_or(Value::Null);
+    let names: Vec<&str> = re.capture_names().flatten().collect();
+    if re.captures_len() == 1 {
+        group(caps.get(0))
+    } else if !names.is_empty() {
+        Value::Object(
+            names
+                .iter()
+                .map(|name| (name.to_string(), group(caps.name(name))))
+                .collect(),
+        )
+    } else {
+        Value::Array((1..re.captures_len()).map(|i| group(caps.get(i))).collect())
+    }
+}
+
+fn regex(args: FuncArgs) -> Result<Value, String> {
+    let re = compile_regex(args[0].as_str().unwrap_or_default())?;
+    let caps = re
+        .captures(args[1].as_str().unwrap_or_default())
+        .ok_or_else(|| "pattern did not match".to_string())?;
+    Ok(regex_match(&re, &caps))
+}
+
+fn regexall(args: FuncArgs) -> Result<Value, String> {
+    let re = compile_regex(args[0].as_str().unwrap_or_default())?;
+    Ok(Value::Array(
+        re.captures_iter(args[1].as_str().unwrap_or_default())
+            .map(|caps| regex_match(&re, &caps))
+            .collect(),
+    ))
+}
+
+fn contains(args: FuncArgs) -> Result<Value, String> {
+    let values = args[0].as_array().cloned().unwrap_or_default();
+    Ok(Value::Bool(values.contains(&args[1])))
+}
+
+fn startswith(args: FuncArgs) -> Result<Value, String> {
+    let s = args[0].as_str().unwrap_or_default();
+    Ok(Value::Bool(
+        s.starts_with(args[1].as_str().unwrap_or_default()),
+    ))
+}
+
+fn endswith(args: FuncArgs) -> Result<Value, String> {
+    let s = args[0].as_str().unwrap_or_default();
+    Ok(Value::Bool(
+        s.ends_with(args[1].as_str().unwrap_or_default()),
+    ))
+}
+
+fn lower(args: FuncArgs) -> Result<Value, String> {
+    Ok(Value::from(
+        args[0].as_str().unwrap_or_default().to_lowercase(),
+    ))
+}
+
+fn upper(args: FuncArgs) -> Result<Value, String> {
+    Ok(Value::from(
+        args[0].as_str().unwrap_or_default().to_uppercase(),
+    ))
+}
+
+fn alltrue(args: FuncArgs) -> Result<Value, String> {
+    let values = args[0].as_array().cloned().unwrap_or_default();
+    Ok(Value::Bool(
+        values.iter().all(|v| v.as_bool() == Some(true)),
+    ))
+}
+
+fn anytrue(args: FuncArgs) -> Result<Value, String> {
+    let values = args[0].as_array().cloned().unwrap_or_default();
+    Ok(Value::Bool(
+        values.iter().any(|v| v.as_bool() == Some(true)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn variable(
+        name: &str,
+        default: Option<serde_json::Value>,
+        validations: &[(&str, &str)],
+    ) -> TfVariable {
+        TfVariable {
+            name: name.to_string(),
+            _type: json!("any"),
+            default,
+            description: "".to_string(),
+            nullable: true,
+            sensitive: false,
+            validations: validations
+                .iter()
+                .map(|(expression, message)| TfValidation {
+                    expression: expression.to_string(),
+                    message: message.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn variables() -> Vec<TfVariable> {
+        vec![
+            variable(
+                "bucket_name",
+                None,
+                &[
+                    (
+                        "length(var.bucket_name) >= 3 && length(var.bucket_name) <= 63",
+                        "The bucket name must be between 3 and 63 characters.",
+                    ),
+                    (
+                        "can(regex(\"^[a-z0-9.-]+$\", var.bucket_name))",
+                        "The bucket name ${var.bucket_name} must be lowercase.",
+                    ),
+                ],
+            ),
+            variable(
+                "environment",
+                Some(json!("dev")),
+                &[(
+                    "contains([\"dev\", \"prod\"], var.environment)",
+                    "The environment must be dev or prod.",
+                )],
+            ),
+            variable(
+                "retention_days",
+                Some(json!(7)),
+                &[(
+                    "var.retention_days > 0 && var.retention_days % 7 == 0",
+                    "The retention must be whole weeks.",
+                )],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_valid_variables() {
+        let claim = json!({"bucket_name": "my-bucket", "retention_days": 14});
+        assert!(validate_variables(&variables(), &claim).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_variables() {
+        let claim = json!({"bucket_name": "My_Bucket", "environment": "test"});
+        assert_eq!(
+            validate_variables(&variables(), &claim)
+                .unwrap_err()
+                .to_string(),
+            "Invalid value for variable \"bucket_name\": The bucket name My_Bucket must be lowercase.\n\
+            Invalid value for variable \"environment\": The environment must be dev or prod."
+        );
+
+        let claim = json!({"bucket_name": "ab", "retention_days": 10});
+        assert_eq!(
+            validate_variables(&variables(), &claim)
+                .unwrap_err()
+                .to_string(),
+            "Invalid value for variable \"bucket_name\": The bucket name must be between 3 and 63 characters.\n\
+            Invalid value for variable \"retention_days\": The retention must be whole weeks."
+        );
+    }
+
+    #[test]
+    fn test_unsupported_validations_are_skipped() {
+        let tf_variables = vec![
+            variable(
+                "cidr",
+                None,
+                &[("can(cidrhost(var.cidr, 0))", "Must be a CIDR.")],
+            ),
+            variable(
+                "vpc_id",
+                None,
+                &[("startswith(var.vpc_id, \"vpc-\")", "Must be a VPC id.")],
+            ),
+        ];
+        let claim = json!({
+            "cidr": "not-a-cidr",
+            "vpc_id": "{{ Vpc::network::vpcId }}",
+        });
+        assert!(validate_variables(&tf_variables, &claim).is_ok());
+
+        let claim = json!({"cidr": "not-a-cidr", "vpc_id": "subnet-123"});
+        assert!(validate_variables(&tf_variables, &claim).is_err());
+    }
+
+    #[test]
+    fn test_regex_result_shapes() {
+        let tf_variables = vec![variable(
+            "version",
+            None,
+            &[(
+                "regex(\"^(?P<major>\\\\d+)\\\\.(\\\\d+)$\", var.version).major == \"1\"",
+                "Only major version 1 is supported.",
+            )],
+        )];
+        assert!(validate_variables(&tf_variables, &json!({"version": "1.2"})).is_ok());
+        assert!(validate_variables(&tf_variables, &json!({"version": "2.0"})).is_err());
+    }
+}
@@ -47,47 +47,31 @@ pub fn verify_variable_existence_and_type(
             .find(|v| v.name == *variable_key)
         {
             Some(module_variable) => {
-                let variable_value_type = match variable_value {
-                    serde_json::Value::String(_) => "string",
-                    serde_json::Value::Number(_) => "number",
-                    serde_json::Value::Bool(_) => "bool",
-                    serde_json::Value::Array(_) => "list",
-                    serde_json::Value::Null => "null",
-                    serde_json::Value::Object(_) => "object",
-                };
-
-                let module_variable_type = match &module_variable._type {
-                    serde_json::Value::Bool(_) => "bool",
-                    serde_json::Value::Number(_) => "number",
-                    serde_json::Value::String(val) => {
-                        if val.starts_with("map(") || val.starts_with("object(") {
-                            // Covers map(string), map(number), etc.
-                            "object"
-                        } else if val.starts_with("list(") || val.starts_with("set(") {
-                            // Covers list(string), list(number), etc.
-                            "list"
-                        } else {
-                            &val.to_string()
-                        }
+                let tf_type = match parse_tf_type(&module_variable._type) {
+                    Ok(tf_type) => tf_type,
+                    Err(e) => {
+                        log::warn!(
+                            "Type of variable \"{}\" is not checked: {}",
+                            variable_key,
+                            e
+                        );
+                        continue;
                     }
-                    serde_json::Value::Array(_) => "list",
-                    serde_json::Value::Object(_) => "object",
-                    serde_json::Value::Null => "null",
                 };
 
-                let is_reference = variable_value.as_str().is_some_and(|s| re.is_match(s));
-
-                if module_variable_type == "any" {
-                    continue;
-                }
+                let is_reference = variable_value
+                    .as_str()
+                    .is_some_and(|s| re.is_match(s) || crate::is_secret_reference(s));
 
-                if variable_value_type != module_variable_type {
-                    if is_reference {
+                if is_reference {
+                    if !matches!(tf_type, TfType::String | TfType::Any) {
                         log::warn!("
                             Variable \"{}\" is a reference and its type is not checked since output type of reference cannot be implied. Please ensure it matches the expected type.",
                             variable_key
                         );
-                    } else if variable_value_type == "null" && module_variable.nullable {
+                    }
+                } else if variable_value.is_null() {
+                    if module_variable.nullable {
                         // This is valid when a user explicitly wants to set a nullable variable to null
                         log::debug!(
                             "Variable \"{}\" is set to null, which is allowed because it is nullable",
@@ -95,10 +79,19 @@ pub fn verify_variable_existence_and_type(
                         );
                     } else {
                         errors.push(format!(
-                            "Variable \"{}\" is of type {} but should be of type {}",
-                            variable_key, variable_value_type, module_variable_type
+                            "Variable \"{}\" is of type null but should be of type {}",
+                            variable_key,
+                            tf_type.name()
                         ));
                     }
+                } else {
+                    verify_value_type(
+                        variable_value,
+                        &tf_type,
+                        &format!("variables.{}", crate::to_camel_case(variable_key)),
+                        &re,
+                        &mut errors,
+                    );
                 }
             }
             None => {
@@ -117,6 +110,240 @@ pub fn verify_variable_existence_and_type(
     }
 }
 
+/// A Terraform type constraint such as `list(object({ port = number }))`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TfType {
+    Any,
+    String,
+    Number,
+    Bool,
+    List(Box<TfType>),
+    Set(Box<TfType>),
+    Map(Box<TfType>),
+    Tuple(Vec<TfType>),
+    Object(Vec<TfObjectAttribute>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TfObjectAttribute {
+    pub name: String,
+    pub _type: TfType,
+    pub optional: bool,
+}
+
+impl TfType {
+    fn name(&self) -> &'static str {
+        match self {
+            TfType::Any => "any",
+            TfType::String => "string",
+            TfType::Number => "number",
+            TfType::Bool => "bool",
+            TfType::List(_) => "list",
+            TfType::Set(_) => "set",
+            TfType::Map(_) => "map",
+            TfType::Tuple(_) => "tuple",
+            TfType::Object(_) => "object",
+        }
+    }
+}
+
+/// Parses the `type` of a Terraform variable as stored on the module
+pub fn parse_tf_type(_type: &serde_json::Value) -> Result<TfType, anyhow::Error> {
+    match _type {
+        serde_json::Value::String(s) => {
+            let body = hcl::parse(&format!("type = {}", s))
+                .map_err(|e| anyhow::anyhow!("Failed to parse type {}: {}", s, e))?;
+            let expr = body
+                .attributes()
+                .next()
+                .map(|attribute| attribute.expr().clone())
+                .ok_or_else(|| anyhow::anyhow!("Empty type"))?;
+            parse_type_expression(&expr)
+        }
+        serde_json::Value::Bool(_) => Ok(TfType::Bool),
+        serde_json::Value::Number(_) => Ok(TfType::Number),
+        serde_json::Value::Array(_) => Ok(TfType::List(Box::new(TfType::Any))),
+        serde_json::Value::Object(_) => Ok(TfType::Map(Box::new(TfType::Any))),
+        serde_json::Value::Null => Ok(TfType::Any),
+    }
+}
+
+fn parse_type_expression(expr: &hcl::Expression) -> Result<TfType, anyhow::Error> {
+    match expr {
+        hcl::Expression::Variable(variable) => match variable.as_str() {
+            "any" => Ok(TfType::Any),
+            "string" => Ok(TfType::String),
+            "number" => Ok(TfType::Number),
+            "bool" => Ok(TfType::Bool),
+            other => Err(anyhow::anyhow!("Unknown type {}", other)),
+        },
+        // Types quoted as strings are from Terraform 0.11
+        hcl::Expression::String(s) => match s.as_str() {
+            "string" => Ok(TfType::String),
+            "list" => Ok(TfType::List(Box::new(TfType::Any))),
+            "map" => Ok(TfType::Map(Box::new(TfType::Any))),
+            other => Err(anyhow::anyhow!("Unknown type \"{}\"", other)),
+        },
+        hcl::Expression::Parenthesis(inner) => parse_type_expression(inner),
+        hcl::Expression::FuncCall(call) => {
+            let name = call.name.name.as_str();
+            match (name, call.args.as_slice()) {
+                ("list", [element]) => Ok(TfType::List(Box::new(parse_type_expression(element)?))),
+                ("set", [element]) => Ok(TfType::Set(Box::new(parse_type_expression(element)?))),
+                ("map", [element]) => Ok(TfType::Map(Box::new(parse_type_expression(element)?))),
+                ("tuple", [hcl::Expression::Array(elements)]) => Ok(TfType::Tuple(
+                    elements
+                        .iter()
+                        .map(parse_type_expression)
+                        .collect::<Result<Vec<TfType>, anyhow::Error>>()?,
+                )),
+                ("object", [hcl::Expression::Object(attributes)]) => {
+                    let mut object_attributes = vec![];
+                    for (key, value) in attributes.iter() {
+                        let name = match key {
+                            hcl::ObjectKey::Identifier(identifier) => identifier.to_string(),
+                            hcl::ObjectKey::Expression(hcl::Expression::String(s)) => s.clone(),
+                            hcl::ObjectKey::Expression(hcl::Expression::Variable(v)) => {
+                                v.to_string()
+                            }
+                            other => {
+                                return Err(anyhow::anyhow!("Invalid attribute name {:?}", other))
+                            }
+                        };
+                        let (_type, optional) = match value {
+                            hcl::Expression::FuncCall(optional)
+                                if optional.name.name.as_str() == "optional"
+                                    && (1..=2).contains(&optional.args.len()) =>
+                            {
+                                (parse_type_expression(&optional.args[0])?, true)
+                            }
+                            _ => (parse_type_expression(value)?, false),
+                        };
+                        object_attributes.push(TfObjectAttribute {
+                            name,
+                            _type,
+                            optional,
+                        });
+                    }
+                    Ok(TfType::Object(object_attributes))
+                }
+                _ => Err(anyhow::anyhow!("Invalid type {}", expr)),
+            }
+        }
+        _ => Err(anyhow::anyhow!("Invalid type {}", expr)),
+    }
+}
+
+fn value_type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::Bool(_) => "bool",
+        serde_json::Value::Array(_) => "list",
+        serde_json::Value::Object(_) => "object",
+        serde_json::Value::Null => "null",
+    }
+}
+
+/// Checks `value` against `tf_type` and adds an error for every mismatch with the path to it,
+/// like `variables.rules[2].port: expected number, got string`
+fn verify_value_type(
+    value: &serde_json::Value,
+    tf_type: &TfType,
+    path: &str,
+    reference: &regex::Regex,
+    errors: &mut Vec<String>,
+) {
+    let mismatch = |errors: &mut Vec<String>| {
+        errors.push(format!(
+            "{}: expected {}, got {}",
+            path,
+            tf_type.name(),
+            value_type_name(value)
+        ))
+    };
+    match (tf_type, value) {
+        // Nested values can be null and references are only known when they are resolved
+        (_, serde_json::Value::Null) | (TfType::Any, _) => {}
+        (_, serde_json::Value::String(s)) if reference.is_match(s) => {}
+        (_, serde_json::Value::String(s)) if crate::is_secret_reference(s) => {}
+        (TfType::String, serde_json::Value::String(_))
+        | (TfType::Number, serde_json::Value::Number(_))
+        | (TfType::Bool, serde_json::Value::Bool(_)) => {}
+        (TfType::List(element) | TfType::Set(element), serde_json::Value::Array(values)) => {
+            for (index, value) in values.iter().enumerate() {
+                verify_value_type(
+                    value,
+                    element,
+                    &format!("{}[{}]", path, index),
+                    reference,
+                    errors,
+                );
+            }
+        }
+        (TfType::Tuple(elements), serde_json::Value::Array(values)) => {
+            if elements.len() != values.len() {
+                errors.push(format!(
+                    "{}: expected tuple of {} elements, got {}",
+                    path,
+                    elements.len(),
+                    values.len()
+                ));
+                return;
+            }
+            for (index, (element, value)) in elements.iter().zip(values).enumerate() {
+                verify_value_type(
+                    value,
+                    element,
+                    &format!("{}[{}]", path, index),
+                    reference,
+                    errors,
+                );
+            }
+        }
+        (TfType::Map(element), serde_json::Value::Object(map)) => {
+            for (key, value) in map {
+                verify_value_type(
+                    value,
+                    element,
+                    &format!("{}[\"{}\"]", path, key),
+                    reference,
+                    errors,
+                );
+            }
+        }
+        (TfType::Object(attributes), serde_json::Value::Object(map)) => {
+            for attribute in attributes {
+                let attribute_path = format!("{}.{}", path, attribute.name);
+                match map.get(&attribute.name) {
+                    Some(value) => verify_value_type(
+                        value,
+                        &attribute._type,
+                        &attribute_path,
+                        reference,
+                        errors,
+                    ),
+                    None if attribute.optional => {}
+                    None => {
+                        errors.push(format!("{}: required attribute is missing", attribute_path))
+                    }
+                }
+            }
+            for key in map.keys() {
+                if !attributes.iter().any(|a| &a.name == key) {
+                    // Terraform discards attributes that are not in the type
+                    log::warn!(
+                        "{}.{} is not an attribute of the type and is ignored",
+                        path,
+                        key
+                    );
+                }
+            }
+        }
+        _ => mismatch(errors),
+    }
+}
+
 pub fn verify_required_variables_are_set(
     module: &ModuleResp,
     variables: &serde_json::Value,
@@ -470,6 +697,7 @@ mod tests {
                     description: "A nullable variable with a default value".to_string(),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     name: "another_var".to_string(),
@@ -478,6 +706,7 @@ mod tests {
                     description: "A required non-nullable variable".to_string(),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -547,6 +776,7 @@ mod tests {
                 description: "A non-nullable required variable".to_string(),
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             }],
             tf_extra_environment_variables: vec![],
             tf_providers: Vec::with_capacity(0),
@@ -578,6 +808,125 @@ mod tests {
         );
     }
 
+    fn module_with_variable(name: &str, _type: &str) -> ModuleResp {
+        let mut module = s3bucket_module();
+        module.tf_variables = vec![TfVariable {
+            default: None,
+            name: name.to_string(),
+            description: "".to_string(),
+            _type: Value::String(_type.to_string()),
+            nullable: true,
+            sensitive: false,
+            validations: vec![],
+        }];
+        module
+    }
+
+    #[test]
+    fn test_parse_tf_type() {
+        assert_eq!(
+            parse_tf_type(&Value::String(
+                "list(object({\n  name = string\n  port = optional(number, 80)\n  \"tags\" = map(string)\n}))"
+                    .to_string()
+            ))
+            .unwrap(),
+            TfType::List(Box::new(TfType::Object(vec![
+                TfObjectAttribute {
+                    name: "name".to_string(),
+                    _type: TfType::String,
+                    optional: false,
+                },
+                TfObjectAttribute {
+                    name: "port".to_string(),
+                    _type: TfType::Number,
+                    optional: true,
+                },
+                TfObjectAttribute {
+                    name: "tags".to_string(),
+                    _type: TfType::Map(Box::new(TfType::String)),
+                    optional: false,
+                },
+            ])))
+        );
+        assert_eq!(
+            parse_tf_type(&Value::String(
+                "tuple([string, set(number), any])".to_string()
+            ))
+            .unwrap(),
+            TfType::Tuple(vec![
+                TfType::String,
+                TfType::Set(Box::new(TfType::Number)),
+                TfType::Any
+            ])
+        );
+        assert!(parse_tf_type(&Value::String("list(strng)".to_string())).is_err());
+        assert!(parse_tf_type(&Value::String("optional(string)".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_parse_published_tf_type() {
+        let variables_str = r#"
+variable "rules" {
+  type = list(object({
+    port     = number
+    protocol = optional(string, "tcp")
+  }))
+}
+"#;
+        let variable = crate::get_variables_from_tf_files(variables_str).unwrap()[0].clone();
+        assert_eq!(
+            parse_tf_type(&variable._type).unwrap(),
+            TfType::List(Box::new(TfType::Object(vec![
+                TfObjectAttribute {
+                    name: "port".to_string(),
+                    _type: TfType::Number,
+                    optional: false,
+                },
+                TfObjectAttribute {
+                    name: "protocol".to_string(),
+                    _type: TfType::String,
+                    optional: true,
+                },
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_nested_variable_type_errors() {
+        let module = module_with_variable(
+            "rules",
+            "list(object({ port = number, protocol = optional(string), cidrs = list(string) }))",
+        );
+        let variables = serde_json::json!({
+            "rules": [
+                {"port": 80, "cidrs": ["10.0.0.0/8"]},
+                {"port": 443, "protocol": "tcp", "cidrs": []},
+                {"port": "22", "protocol": 6, "cidrs": ["10.0.0.0/8", 10]},
+                {"port": 8080},
+            ]
+        });
+        assert_eq!(
+            verify_variable_existence_and_type(&module, &variables)
+                .unwrap_err()
+                .to_string(),
+            "variables.rules[2].port: expected number, got string; \
+            variables.rules[2].protocol: expected string, got number; \
+            variables.rules[2].cidrs[1]: expected string, got number; \
+            variables.rules[3].cidrs: required attribute is missing"
+        );
+
+        let module = module_with_variable("settings", "map(tuple([string, bool]))");
+        let variables = serde_json::json!({
+            "settings": {"a": ["x", true], "b": ["y"], "c": ["z", "{{ S3Bucket::bucket::enabled }}"]}
+        });
+        assert_eq!(
+            verify_variable_existence_and_type(&module, &variables)
+                .unwrap_err()
+                .to_string(),
+            "variables.settings[\"b\"]: expected tuple of 2 elements, got 1"
+        );
+    }
+
     fn s3bucket_module() -> ModuleResp {
         ModuleResp {
             oci_artifact_set: None,
@@ -627,6 +976,7 @@ mod tests {
                     _type: Value::String("string".to_string()),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     default: Some(serde_json::Value::Null),
@@ -635,6 +985,7 @@ mod tests {
                     _type: Value::Bool(false),
                     nullable: false,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     default: Some(serde_json::Value::Null), // This is set to null
@@ -643,6 +994,7 @@ mod tests {
                     _type: Value::Null,
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
                 TfVariable {
                     default: None, // This is not set
@@ -651,6 +1003,7 @@ mod tests {
                     _type: Value::String("string".to_string()),
                     nullable: true,
                     sensitive: false,
+                    validations: vec![],
                 },
             ],
             tf_extra_environment_variables: vec![],
@@ -690,6 +1043,7 @@ mod tests {
                             _type: Value::String("map(string)".to_string()),
                             nullable: false,
                             sensitive: false,
+                            validations: vec![],
                         }
                     ],
                     tf_extra_environment_variables: Vec::new(),
@@ -713,6 +1067,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "max_size".to_string(),
@@ -721,6 +1076,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "enable_logging".to_string(),
@@ -729,6 +1085,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
         ];
 
@@ -747,6 +1104,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "region".to_string(),
@@ -755,6 +1113,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
         ];
 
@@ -773,6 +1132,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "bucket_v2".to_string(),
@@ -781,6 +1141,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
         ];
 
@@ -799,6 +1160,7 @@ mod tests {
             default: None,
             nullable: false,
             sensitive: false,
+            validations: vec![],
         }];
 
         let result = verify_variable_name_roundtrip(&variables);
@@ -819,6 +1181,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "bucket_name".to_string(),
@@ -827,6 +1190,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
         ];
 
@@ -847,6 +1211,7 @@ mod tests {
             default: None,
             nullable: false,
             sensitive: false,
+            validations: vec![],
         }];
 
         let result = verify_variable_name_roundtrip(&variables);
@@ -875,6 +1240,7 @@ mod tests {
             default: None,
             nullable: false,
             sensitive: false,
+            validations: vec![],
         }];
 
         let result = verify_variable_name_roundtrip(&variables);
@@ -899,6 +1265,7 @@ mod tests {
             default: None,
             nullable: false,
             sensitive: false,
+            validations: vec![],
         }];
 
         let result = verify_variable_name_roundtrip(&variables);
@@ -919,6 +1286,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "maxSize".to_string(), // Invalid - camelCase
@@ -927,6 +1295,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "enable_logging".to_string(), // Valid
@@ -935,6 +1304,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
             TfVariable {
                 name: "tag__value".to_string(), // Invalid - double underscore
@@ -943,6 +1313,7 @@ mod tests {
                 default: None,
                 nullable: false,
                 sensitive: false,
+                validations: vec![],
             },
         ];
 
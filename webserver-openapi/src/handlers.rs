@@ -16,7 +16,7 @@ use utoipa::{IntoParams, Modify, OpenApi};
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(describe_deployment, get_modules, get_projects, get_deployments, read_logs, get_policies, get_policy_version, get_module_version, get_deployments_for_module, get_events, get_all_versions_for_module, get_stacks, get_stack_version, get_change_record, get_all_versions_for_stack),
+    paths(describe_deployment, get_modules, get_projects, get_deployments, read_logs, get_policies, get_policy_version, get_module_version, get_deployments_for_module, get_events, get_all_versions_for_module, get_stacks, get_stack_version, get_change_record, get_all_versions_for_stack, cancel_deployment_job),
     components(schemas(ModuleResp, DeploymentResp, PolicyResp, Dependency, Dependent, ProjectData)),
     modifiers(&SecurityAddon),
     tags(
@@ -91,6 +91,39 @@ pub async fn describe_deployment(
     (StatusCode::OK, Json(deployment)).into_response()
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/deployment/{project}/{region}/{environment}/{deployment_id}/cancel",
+    responses(
+        (status = 200, description = "Job cancelled", body = serde_json::Value),
+        (status = 400, description = "No job in progress or it could not be cancelled", body = serde_json::Value)
+    ),
+    params(
+        ("project" = str, Path, description = "Project id of the deployment"),
+        ("region" = str, Path, description = "Region of the deployment"),
+        ("environment" = str, Path, description = "Environment of the deployment"),
+        ("deployment_id" = str, Path, description = "Deployment id to cancel the job in progress for"),
+    ),
+    description = "Cancel the job in progress for a deployment, returns once the job has stopped"
+)]
+pub async fn cancel_deployment_job(
+    Path((project, region, environment, deployment_id)): Path<(String, String, String, String)>,
+) -> impl IntoResponse {
+    match env_common::logic::cancel_deployment_job(
+        &GenericCloudHandler::workload(&project, &region).await,
+        &deployment_id,
+        &environment,
+    )
+    .await
+    {
+        Ok(job_id) => (StatusCode::OK, Json(json!({"job_id": job_id}))).into_response(),
+        Err(e) => {
+            let error_json = json!({"error": format!("{:?}", e)});
+            (StatusCode::BAD_REQUEST, Json(error_json)).into_response()
+        }
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/stack/${track}/{stack_name}/{stack_version}",
@@ -45,6 +45,10 @@ pub async fn run_server_with_listener(
             "/api/v1/deployment/{project}/{region}/{environment}/{deployment_id}",
             axum::routing::get(handlers::describe_deployment),
         )
+        .route(
+            "/api/v1/deployment/{project}/{region}/{environment}/{deployment_id}/cancel",
+            axum::routing::post(handlers::cancel_deployment_job),
+        )
         .route(
             "/api/v1/deployments/module/{project}/{region}/{module}",
             axum::routing::get(handlers::get_deployments_for_module),
@@ -147,6 +151,10 @@ pub async fn run_server_on_port(
             "/api/v1/deployment/{project}/{region}/{environment}/{deployment_id}",
             axum::routing::get(handlers::describe_deployment),
         )
+        .route(
+            "/api/v1/deployment/{project}/{region}/{environment}/{deployment_id}/cancel",
+            axum::routing::post(handlers::cancel_deployment_job),
+        )
         .route(
             "/api/v1/deployments/module/{project}/{region}/{module}",
             axum::routing::get(handlers::get_deployments_for_module),